@@ -0,0 +1,202 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, SubMsg,
+    Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::Cw20ExecuteMsg::Transfer;
+
+use crate::{
+    error::AssetVaultErr,
+    helpers::{ConfigResponse, OperatorsResponse, ProcessedRequest, ProcessedRequestResponse},
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{AssetVault, ASSET_VAULT, OPERATORS, PROCESSED_REQUESTS},
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:asset-vault";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, AssetVaultErr> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    ASSET_VAULT.save(
+        deps.storage,
+        &AssetVault {
+            admin: info.sender.clone(),
+        },
+    )?;
+    for operator in msg.operators.iter() {
+        OPERATORS.save(
+            deps.storage,
+            deps.api.addr_validate(operator.as_str())?,
+            &true,
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("admin", info.sender))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, AssetVaultErr> {
+    match msg {
+        ExecuteMsg::SetOperators {
+            operators,
+            is_operators,
+        } => execute::set_operators(deps, info, operators, is_operators),
+        ExecuteMsg::SendAsset {
+            request_id,
+            asset_address,
+            amount,
+            recipient,
+        } => execute::send_asset(
+            deps,
+            env,
+            info,
+            request_id,
+            asset_address,
+            amount,
+            recipient,
+        ),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query::get_config(deps)?),
+        QueryMsg::Operators {} => to_binary(&query::get_operators(deps)?),
+        QueryMsg::ProcessedRequest { request_id } => {
+            to_binary(&query::get_processed_request(deps, request_id)?)
+        }
+    }
+}
+
+pub mod execute {
+    use super::*;
+
+    pub fn set_operators(
+        deps: DepsMut,
+        info: MessageInfo,
+        operators: Vec<String>,
+        is_operators: Vec<bool>,
+    ) -> Result<Response, AssetVaultErr> {
+        if info.sender != ASSET_VAULT.load(deps.storage)?.admin {
+            return Err(AssetVaultErr::NotAdmin {
+                account: info.sender.into(),
+            });
+        }
+        if operators.len() != is_operators.len() {
+            return Err(AssetVaultErr::LengthMismatch {});
+        }
+        for (i, new_operator) in operators.iter().enumerate() {
+            OPERATORS.save(
+                deps.storage,
+                deps.api.addr_validate(new_operator.as_str())?,
+                is_operators.get(i).unwrap(),
+            )?;
+        }
+        Ok(Response::new().add_attribute("action", "set_operators"))
+    }
+
+    pub fn send_asset(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        request_id: String,
+        asset_address: String,
+        amount: Uint128,
+        recipient: String,
+    ) -> Result<Response, AssetVaultErr> {
+        // Only operators can send assets out of the vault
+        if !OPERATORS
+            .may_load(deps.storage, info.sender.clone())?
+            .unwrap_or_default()
+        {
+            return Err(AssetVaultErr::NotOperator {
+                account: info.sender.to_string(),
+            });
+        }
+
+        // Each request can only be processed once
+        if PROCESSED_REQUESTS.has(deps.storage, request_id.as_str()) {
+            return Err(AssetVaultErr::RequestProcessed { request_id });
+        }
+        let asset_address = deps.api.addr_validate(asset_address.as_str())?;
+        let recipient = deps.api.addr_validate(recipient.as_str())?;
+        PROCESSED_REQUESTS.save(
+            deps.storage,
+            request_id.as_str(),
+            &ProcessedRequest {
+                asset_address: asset_address.clone(),
+                amount,
+                recipient: recipient.clone(),
+                operator: info.sender,
+                height: env.block.height,
+            },
+        )?;
+
+        // Transfer asset from vault to recipient
+        let transfer_msg = SubMsg::new(WasmMsg::Execute {
+            contract_addr: asset_address.to_string(),
+            msg: to_binary(&Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        });
+
+        Ok(Response::new()
+            .add_attribute("action", "send_asset")
+            .add_attribute("request_id", request_id)
+            .add_submessage(transfer_msg))
+    }
+}
+
+pub mod query {
+    use super::*;
+
+    pub fn get_config(deps: Deps) -> StdResult<ConfigResponse> {
+        let admin = ASSET_VAULT.load(deps.storage)?.admin.to_string();
+        Ok(ConfigResponse { admin })
+    }
+
+    pub fn get_operators(deps: Deps) -> StdResult<OperatorsResponse> {
+        let operators = OPERATORS
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter_map(|item| match item {
+                Ok((operator, true)) => Some(Ok(operator.to_string())),
+                Ok((_, false)) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(OperatorsResponse { operators })
+    }
+
+    pub fn get_processed_request(
+        deps: Deps,
+        request_id: String,
+    ) -> StdResult<ProcessedRequestResponse> {
+        let request = PROCESSED_REQUESTS.may_load(deps.storage, request_id.as_str())?;
+        Ok(ProcessedRequestResponse {
+            request_id,
+            processed: request.is_some(),
+            asset_address: request.as_ref().map(|r| r.asset_address.to_string()),
+            amount: request.as_ref().map(|r| r.amount),
+            recipient: request.map(|r| r.recipient.to_string()),
+        })
+    }
+}
@@ -11,4 +11,10 @@ pub enum AssetVaultErr {
 
     #[error("AssetVaultErr: caller is not operator ({account:?})")]
     NotOperator { account: String },
+
+    #[error("AssetVaultErr: lengths mismatch")]
+    LengthMismatch {},
+
+    #[error("AssetVaultErr: request already processed ({request_id:?})")]
+    RequestProcessed { request_id: String },
 }
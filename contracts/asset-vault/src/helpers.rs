@@ -0,0 +1,33 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Which asset has been sent to whom for a request
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ProcessedRequest {
+    pub asset_address: Addr,
+    pub amount: Uint128,
+    pub recipient: Addr,
+    pub operator: Addr,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub admin: String,
+}
+
+#[cw_serde]
+pub struct OperatorsResponse {
+    pub operators: Vec<String>,
+}
+
+#[cw_serde]
+pub struct ProcessedRequestResponse {
+    pub request_id: String,
+    pub processed: bool,
+    pub asset_address: Option<String>,
+    pub amount: Option<Uint128>,
+    pub recipient: Option<String>,
+}
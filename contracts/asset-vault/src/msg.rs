@@ -1,10 +1,7 @@
-use bond_token::helpers::{Denomination, FunctionSetup};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Uint128, Uint64};
-use cw20::{Cw20Coin, MinterResponse};
-use cw20_base::msg::InstantiateMarketingInfo;
+use cosmwasm_std::Uint128;
 
-use crate::helpers::ContractInfo;
+use crate::helpers::{ConfigResponse, OperatorsResponse, ProcessedRequestResponse};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -27,4 +24,13 @@ pub enum ExecuteMsg {
 
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    #[returns(ConfigResponse)]
+    Config {},
+
+    #[returns(OperatorsResponse)]
+    Operators {},
+
+    #[returns(ProcessedRequestResponse)]
+    ProcessedRequest { request_id: String },
+}
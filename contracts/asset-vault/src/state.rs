@@ -1,8 +1,10 @@
-use cosmwasm_std::{Addr, Uint64};
+use cosmwasm_std::Addr;
 use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::helpers::ProcessedRequest;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct AssetVault {
     pub admin: Addr,
@@ -10,3 +12,4 @@ pub struct AssetVault {
 
 pub const ASSET_VAULT: Item<AssetVault> = Item::new("asset_vault");
 pub const OPERATORS: Map<Addr, bool> = Map::new("operators");
+pub const PROCESSED_REQUESTS: Map<&str, ProcessedRequest> = Map::new("processed_requests"); // maps from a request ID to the asset sent for it
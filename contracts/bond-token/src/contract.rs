@@ -1,23 +1,31 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
 };
 use cw2::set_contract_version;
+use cw20::BalanceResponse;
 use cw20_base::{
-    contract::{create_accounts, execute as cw20_execute, query as cw20_query},
+    contract::{create_accounts, query as cw20_query},
     state::{MinterData, TokenInfo, BALANCES, TOKEN_INFO},
 };
+use cw_storage_plus::Bound;
 
 use crate::{
     error::{AdditionalError, BondTokenErr},
     execute,
     helpers::{
-        CurrencyResponse, Holder, HoldersResponse, IssuerResponse, Phase, RedemptionAmountResponse,
-        MAX_FEE_PERCENTAGE,
+        AllowlistResponse, BondScheduleResponse, CouponEntitlementResponse, CouponPeriodResponse,
+        CouponScheduleResponse, CurrencyResponse, Holder, HoldersResponse, IdentityCheck,
+        IssuerResponse, Phase, RedemptionAmountResponse, SubscriptionLimitsResponse,
+        TotalSupplyAtResponse, UnclaimedCouponsResponse, DEFAULT_LIMIT, MAX_FEE_PERCENTAGE,
+        MAX_LIMIT,
     },
     msg::{AdditionalExecuteMsg, AdditionalQueryMsg, ExecuteMsg, InstantiateMsg, QueryMsg},
-    state::{BondToken, BOND_TOKEN},
+    state::{
+        BondToken, CouponEntitlement, ALLOWLIST, BALANCE_SNAPSHOTS, BOND_TOKEN,
+        COUPON_ENTITLEMENTS, PAID_COUPONS, TOTAL_SUPPLY_SNAPSHOTS,
+    },
 };
 
 const CONTRACT_NAME: &str = "crates.io:bond-token";
@@ -26,21 +34,65 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, BondTokenErr> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let terms = msg.terms;
 
-    match msg.subscription_fee_percentage {
-        Some(percentage) => {
-            if percentage.u128() > MAX_FEE_PERCENTAGE {
-                return Err(BondTokenErr::AdditionalError(
-                    AdditionalError::FeePercentageTooHigh { percentage },
-                ));
-            }
+    if let Some(percentage) = terms.subscription_fee_percentage {
+        if percentage.u128() > MAX_FEE_PERCENTAGE {
+            return Err(BondTokenErr::AdditionalError(
+                AdditionalError::FeePercentageTooHigh { percentage },
+            ));
         }
-        None => (),
+    }
+
+    if terms.subscription_start >= terms.subscription_end
+        || terms.subscription_end > terms.distribution_deadline
+        || terms.subscription_end > terms.issue_date
+        || terms.issue_date >= terms.maturity_date
+    {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::InvalidSchedule,
+        ));
+    }
+
+    let limits = &terms.subscription_limits;
+    if limits
+        .min_subscription
+        .zip(limits.max_subscription_per_investor)
+        .is_some_and(|(min, max)| min > max)
+        || limits.hard_cap.is_some_and(|hard_cap| {
+            [
+                limits.soft_cap,
+                limits.min_subscription,
+                limits.max_subscription_per_investor,
+            ]
+            .iter()
+            .flatten()
+            .any(|limit| *limit > hard_cap)
+        })
+    {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::InvalidSubscriptionLimits,
+        ));
+    }
+
+    let coupon_dates = &terms.coupon_terms.dates;
+    if terms.coupon_terms.frequency == 0
+        || coupon_dates
+            .first()
+            .is_some_and(|first| *first <= terms.issue_date)
+        || coupon_dates
+            .last()
+            .is_some_and(|last| *last > terms.maturity_date)
+        || coupon_dates.windows(2).any(|dates| dates[0] >= dates[1])
+    {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::InvalidCouponTerms,
+        ));
     }
 
     // Handle special information
@@ -53,12 +105,37 @@ pub fn instantiate(
             currency: deps.api.addr_validate(msg.currency.as_str())?,
             placeholder: deps.api.addr_validate(msg.placeholder.as_str())?,
             router: deps.api.addr_validate(msg.router.as_str())?,
-            denomination: msg.denomination,
-            subscription_fee_percentage: msg.subscription_fee_percentage,
-            subscription_fee: msg.subscription_fee,
+            denomination: terms.denomination,
+            subscription_fee_percentage: terms.subscription_fee_percentage,
+            subscription_fee: terms.subscription_fee,
+            subscription_limits: terms.subscription_limits,
+            cancellation_fee_policy: terms.cancellation_fee_policy,
             current_phase: Phase::Subscription,
+            subscription_start: terms.subscription_start,
+            subscription_end: terms.subscription_end,
+            distribution_deadline: terms.distribution_deadline,
+            issue_date: terms.issue_date,
+            maturity_date: terms.maturity_date,
+            coupon_terms: terms.coupon_terms,
+            identity_check: terms
+                .identity_check
+                .map(|check| -> StdResult<_> {
+                    Ok(IdentityCheck {
+                        registry: deps.api.addr_validate(check.registry.as_str())?.to_string(),
+                        requirements: check.requirements,
+                    })
+                })
+                .transpose()?,
+            allowlist_enabled: terms.allowlist.is_some(),
         },
     )?;
+    for investor in terms.allowlist.unwrap_or_default() {
+        ALLOWLIST.save(
+            deps.storage,
+            &deps.api.addr_validate(investor.as_str())?,
+            &true,
+        )?;
+    }
 
     // Handle CW20 basic information - based on cw20_base's implementation
     msg.basic_info.validate().unwrap();
@@ -84,6 +161,13 @@ pub fn instantiate(
         mint,
     };
     TOKEN_INFO.save(deps.storage, &data)?;
+    let initial_holders = msg
+        .basic_info
+        .initial_balances
+        .iter()
+        .map(|coin| deps.api.addr_validate(coin.address.as_str()))
+        .collect::<StdResult<Vec<_>>>()?;
+    execute::checkpoint_balances(deps.storage, env.block.height, &initial_holders)?;
 
     Ok(Response::new().add_attribute("action", "instantiate"))
 }
@@ -105,20 +189,42 @@ pub fn execute(
         ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::BurnFromHolder {
             issuer,
             holder,
-        }) => execute::burn_from_holder(deps, info, issuer, holder),
+        }) => execute::burn_from_holder(deps, env, info, issuer, holder),
         ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::Subscribe {
             subscription_amount,
             fee_amount,
-        }) => execute::subscribe(deps, info, subscription_amount, fee_amount),
+        }) => execute::subscribe(deps, env, info, subscription_amount, fee_amount),
+        ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::CancelSubscription {}) => {
+            execute::cancel_subscription(deps, env, info, None)
+        }
+        ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::ReduceSubscription { amount }) => {
+            execute::cancel_subscription(deps, env, info, Some(amount))
+        }
         ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::UpdatePhase { phase }) => {
-            execute::update_phase(deps, info, phase)
+            execute::update_phase(deps, env, info, phase)
+        }
+        ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::ClaimRefund {}) => {
+            execute::claim_refund(deps, env, info)
         }
+        ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::RecordCouponPayment { period }) => {
+            execute::record_coupon_payment(deps, env, info, period)
+        }
+        ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::RecordCouponEntitlements {
+            period,
+        }) => execute::record_coupon_entitlements(deps, env, info, period),
+        ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::ClaimCouponEntitlement {
+            period,
+            investor,
+        }) => execute::claim_coupon_entitlement(deps, info, period, investor),
+        ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::AddToAllowlist { investors }) => {
+            execute::update_allowlist(deps, info, investors, true)
+        }
+        ExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::RemoveFromAllowlist {
+            investors,
+        }) => execute::update_allowlist(deps, info, investors, false),
 
         /* Other basic functions */
-        _ => match cw20_execute(deps, env, info, msg.basic_execute_msg()) {
-            Ok(response) => Ok(response),
-            Err(err) => Err(BondTokenErr::BasicError(err)),
-        },
+        _ => execute::execute_cw20(deps, env, info, msg.basic_execute_msg()),
     }
 }
 
@@ -129,6 +235,9 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::GetHolders {}) => {
             to_binary(&query::get_holders(deps)?)
         }
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::Holders { start_after, limit }) => {
+            to_binary(&query::get_holders_page(deps, start_after, limit)?)
+        }
         QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::GetIssuer {}) => {
             to_binary(&query::get_issuer(deps)?)
         }
@@ -138,6 +247,36 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::EstimateRedempmtionAmount {}) => {
             to_binary(&query::estimate_redemption_amount(deps)?)
         }
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::BondSchedule {}) => {
+            to_binary(&query::get_bond_schedule(deps)?)
+        }
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::CouponSchedule {}) => {
+            to_binary(&query::get_coupon_schedule(deps)?)
+        }
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::SubscriptionLimits {}) => {
+            to_binary(&query::get_subscription_limits(deps, env)?)
+        }
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::CouponEntitlement {
+            period,
+            investor,
+        }) => to_binary(&query::get_coupon_entitlement(deps, period, investor)?),
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::UnclaimedCoupons { investor }) => {
+            to_binary(&query::get_unclaimed_coupons(deps, investor)?)
+        }
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::HoldersAt {
+            height,
+            start_after,
+            limit,
+        }) => to_binary(&query::get_holders_at(deps, height, start_after, limit)?),
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::BalanceAt { address, height }) => {
+            to_binary(&query::get_balance_at(deps, address, height)?)
+        }
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::TotalSupplyAt { height }) => {
+            to_binary(&query::get_total_supply_at(deps, height)?)
+        }
+        QueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::Allowlist { start_after, limit }) => {
+            to_binary(&query::get_allowlist(deps, start_after, limit)?)
+        }
 
         /* Basic CW20 queries */
         _ => cw20_query(deps, env, msg.basic_query_msg()),
@@ -167,6 +306,35 @@ pub mod query {
         Ok(HoldersResponse { holders })
     }
 
+    pub fn get_holders_page(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<HoldersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|account| deps.api.addr_validate(account.as_str()))
+            .transpose()?;
+        let denomination = BOND_TOKEN.load(deps.storage)?.denomination;
+        let holders = BALANCES
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| {
+                item.map(|(account, bond_balance)| Holder {
+                    account: account.to_string(),
+                    balance_in_currency: bond_balance * denomination.currency_amount
+                        / denomination.bond_amount,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(HoldersResponse { holders })
+    }
+
     pub fn get_issuer(deps: Deps) -> StdResult<IssuerResponse> {
         let issuer = BOND_TOKEN.load(deps.storage)?.issuer.to_string();
         Ok(IssuerResponse { issuer })
@@ -185,4 +353,170 @@ pub mod query {
                 / denomination.bond_amount,
         })
     }
+
+    pub fn get_bond_schedule(deps: Deps) -> StdResult<BondScheduleResponse> {
+        let bond_token = BOND_TOKEN.load(deps.storage)?;
+        Ok(BondScheduleResponse {
+            subscription_start: bond_token.subscription_start,
+            subscription_end: bond_token.subscription_end,
+            distribution_deadline: bond_token.distribution_deadline,
+            issue_date: bond_token.issue_date,
+            maturity_date: bond_token.maturity_date,
+            current_phase: bond_token.current_phase,
+        })
+    }
+
+    pub fn get_coupon_schedule(deps: Deps) -> StdResult<CouponScheduleResponse> {
+        let bond_token = BOND_TOKEN.load(deps.storage)?;
+        let mut periods: Vec<CouponPeriodResponse> = vec![];
+        for period in 1..=bond_token.coupon_terms.dates.len() as u32 {
+            let (start, end) = bond_token.coupon_period(period).unwrap();
+            periods.push(CouponPeriodResponse {
+                period,
+                start,
+                end,
+                payment_date: bond_token.coupon_terms.payment_date(end),
+                accrual_fraction: bond_token.coupon_terms.accrual_fraction(start, end),
+                paid: PAID_COUPONS.has(deps.storage, period),
+            });
+        }
+        Ok(CouponScheduleResponse {
+            rate: bond_token.coupon_terms.rate,
+            frequency: bond_token.coupon_terms.frequency,
+            day_count: bond_token.coupon_terms.day_count,
+            business_day_convention: bond_token.coupon_terms.business_day_convention,
+            periods,
+        })
+    }
+
+    pub fn get_subscription_limits(deps: Deps, env: Env) -> StdResult<SubscriptionLimitsResponse> {
+        let bond_token = BOND_TOKEN.load(deps.storage)?;
+        let total_subscribed = execute::placeholder_total_subscribed(deps, &env, &bond_token)?;
+        Ok(SubscriptionLimitsResponse {
+            limits: bond_token.subscription_limits,
+            total_subscribed,
+        })
+    }
+
+    pub fn get_coupon_entitlement(
+        deps: Deps,
+        period: u32,
+        investor: String,
+    ) -> StdResult<CouponEntitlementResponse> {
+        let investor = deps.api.addr_validate(investor.as_str())?;
+        let entitlement = match COUPON_ENTITLEMENTS.may_load(deps.storage, (period, &investor))? {
+            Some(entitlement) => entitlement,
+            None => CouponEntitlement {
+                amount: execute::coupon_entitlement(deps, period, &investor)?,
+                claimed: false,
+            },
+        };
+        Ok(CouponEntitlementResponse {
+            period,
+            investor: investor.to_string(),
+            amount: entitlement.amount,
+            claimed: entitlement.claimed,
+        })
+    }
+
+    pub fn get_unclaimed_coupons(
+        deps: Deps,
+        investor: String,
+    ) -> StdResult<UnclaimedCouponsResponse> {
+        let investor = deps.api.addr_validate(investor.as_str())?;
+        let periods = BOND_TOKEN.load(deps.storage)?.coupon_terms.dates.len() as u32;
+        let mut entitlements: Vec<CouponEntitlementResponse> = vec![];
+        for period in 1..=periods {
+            if COUPON_ENTITLEMENTS.has(deps.storage, (period, &investor)) {
+                continue;
+            }
+            let amount = execute::coupon_entitlement(deps, period, &investor)?;
+            if !amount.is_zero() {
+                entitlements.push(CouponEntitlementResponse {
+                    period,
+                    investor: investor.to_string(),
+                    amount,
+                    claimed: false,
+                });
+            }
+        }
+        Ok(UnclaimedCouponsResponse { entitlements })
+    }
+
+    pub fn get_holders_at(
+        deps: Deps,
+        height: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<HoldersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|account| deps.api.addr_validate(account.as_str()))
+            .transpose()?;
+        let denomination = BOND_TOKEN.load(deps.storage)?.denomination;
+        let accounts: Vec<Addr> = BALANCE_SNAPSHOTS
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        let mut holders: Vec<Holder> = vec![];
+        for account in accounts {
+            let bond_balance = BALANCE_SNAPSHOTS
+                .may_load_at_height(deps.storage, &account, height)?
+                .unwrap_or_default();
+            holders.push(Holder {
+                account: account.to_string(),
+                balance_in_currency: bond_balance * denomination.currency_amount
+                    / denomination.bond_amount,
+            });
+        }
+        Ok(HoldersResponse { holders })
+    }
+
+    pub fn get_balance_at(deps: Deps, address: String, height: u64) -> StdResult<BalanceResponse> {
+        let address = deps.api.addr_validate(address.as_str())?;
+        let balance = BALANCE_SNAPSHOTS
+            .may_load_at_height(deps.storage, &address, height)?
+            .unwrap_or_default();
+        Ok(BalanceResponse { balance })
+    }
+
+    pub fn get_total_supply_at(deps: Deps, height: u64) -> StdResult<TotalSupplyAtResponse> {
+        let total_supply = TOTAL_SUPPLY_SNAPSHOTS
+            .may_load_at_height(deps.storage, height)?
+            .unwrap_or_default();
+        let denomination = BOND_TOKEN.load(deps.storage)?.denomination;
+        Ok(TotalSupplyAtResponse {
+            total_supply,
+            total_supply_in_currency: total_supply * denomination.currency_amount
+                / denomination.bond_amount,
+        })
+    }
+
+    pub fn get_allowlist(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllowlistResponse> {
+        let enabled = BOND_TOKEN.load(deps.storage)?.allowlist_enabled;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|investor| deps.api.addr_validate(investor.as_str()))
+            .transpose()?;
+        let investors = ALLOWLIST
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|investor| investor.map(String::from))
+            .collect::<StdResult<_>>()?;
+        Ok(AllowlistResponse { enabled, investors })
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{StdError, Uint128};
+use cosmwasm_std::{StdError, Timestamp, Uint128};
 use cw20_base::ContractError as BasicError;
 use std::fmt;
 use thiserror::Error;
@@ -11,6 +11,27 @@ pub enum AdditionalError {
     #[error("BondToken: function {function:?} not supported")]
     FunctionNotSupported { function: String },
 
+    #[error("BondToken: invalid subscription limits, minimum must not exceed maximum and no limit can exceed hard cap")]
+    InvalidSubscriptionLimits,
+
+    #[error("BondToken: subscription below minimum ({amount:?} {minimum:?})")]
+    SubscriptionBelowMinimum { amount: Uint128, minimum: Uint128 },
+
+    #[error("BondToken: subscription above maximum per investor ({amount:?} {maximum:?})")]
+    SubscriptionAboveMaximum { amount: Uint128, maximum: Uint128 },
+
+    #[error("BondToken: hard cap exceeded ({amount:?} {hard_cap:?})")]
+    HardCapExceeded { amount: Uint128, hard_cap: Uint128 },
+
+    #[error("BondToken: soft cap not reached ({amount:?} {soft_cap:?})")]
+    SoftCapNotReached { amount: Uint128, soft_cap: Uint128 },
+
+    #[error("BondToken: refund not available")]
+    RefundNotAvailable,
+
+    #[error("BondToken: nothing to refund ({investor:?})")]
+    NothingToRefund { investor: String },
+
     #[error("BondToken: {caller:?} is not issuer")]
     NotIssuer { caller: String },
 
@@ -31,6 +52,39 @@ pub enum AdditionalError {
 
     #[error("BondToken: invalid phase")]
     InvalidPhase,
+
+    #[error("BondToken: bond token is not transferable ({reason:?})")]
+    TransferNotAllowed { reason: String },
+
+    #[error("BondToken: account is not allowlisted ({account:?})")]
+    NotAllowlisted { account: String },
+
+    #[error("BondToken: identity not verified ({account:?} {reason:?})")]
+    IdentityNotVerified { account: String, reason: String },
+
+    #[error("BondToken: invalid schedule, dates must be in order (subscription start, subscription end, issue date, maturity date) and distribution deadline must not be before subscription end")]
+    InvalidSchedule,
+
+    #[error("BondToken: {action:?} is not allowed before {time:?}")]
+    TooEarly { action: String, time: Timestamp },
+
+    #[error("BondToken: {action:?} is not allowed after {time:?}")]
+    TooLate { action: String, time: Timestamp },
+
+    #[error("BondToken: invalid coupon terms, dates must be increasing and between issue date and maturity date")]
+    InvalidCouponTerms,
+
+    #[error("BondToken: coupon period not found ({period:?})")]
+    CouponPeriodNotFound { period: u32 },
+
+    #[error("BondToken: coupon period already paid ({period:?})")]
+    CouponAlreadyPaid { period: u32 },
+
+    #[error("BondToken: no coupon entitlement ({period:?} {investor:?})")]
+    CouponEntitlementNotFound { period: u32, investor: String },
+
+    #[error("BondToken: coupon already claimed ({period:?} {investor:?})")]
+    CouponAlreadyClaimed { period: u32, investor: String },
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -42,6 +96,10 @@ pub enum BondTokenErr {
 
 impl fmt::Display for BondTokenErr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self)
+        match self {
+            BondTokenErr::Std(err) => write!(f, "{}", err),
+            BondTokenErr::BasicError(err) => write!(f, "{}", err),
+            BondTokenErr::AdditionalError(err) => write!(f, "{}", err),
+        }
     }
 }
@@ -1,23 +1,32 @@
 use cosmwasm_std::{
-    to_binary, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg, Uint128, WasmMsg,
+    to_binary, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, SubMsg,
+    Uint128, WasmMsg,
 };
 use cw20_base::{
     contract,
     msg::ExecuteMsg as Cw20ExecuteMsg,
     state::{BALANCES, TOKEN_INFO},
 };
-use placeholder::msg::ExecuteMsg as PlaceholderExecuteMsg;
+use identity_registry::{helpers::VerificationResponse, msg::QueryMsg as IdentityRegistryQueryMsg};
+use placeholder::{
+    helpers::{SubscriptionResponse, SubscriptionTotalResponse},
+    msg::{ExecuteMsg as PlaceholderExecuteMsg, QueryMsg as PlaceholderQueryMsg},
+};
 
 use crate::{
     error::{AdditionalError, BondTokenErr},
-    helpers::{Phase, MAX_FEE_PERCENTAGE},
-    state::BOND_TOKEN,
+    helpers::{CancellationFeePolicy, Phase, MAX_FEE_PERCENTAGE},
+    state::{
+        BondToken, CouponEntitlement, ALLOWLIST, BALANCE_SNAPSHOTS, BOND_TOKEN,
+        COUPON_ENTITLEMENTS, COUPON_RECORD_HEIGHTS, PAID_COUPONS, TOTAL_SUPPLY_SNAPSHOTS,
+    },
 };
 
 /* Overrided CW20 functions */
 
 pub fn burn_from_holder(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     issuer: String,
     holder: String,
@@ -38,7 +47,7 @@ pub fn burn_from_holder(
     }
 
     // Validate issuer
-    if BOND_TOKEN.load(deps.storage)?.issuer.to_string() != issuer {
+    if BOND_TOKEN.load(deps.storage)?.issuer != issuer {
         return Err(BondTokenErr::AdditionalError(AdditionalError::NotIssuer {
             caller: info.sender.to_string(),
         }));
@@ -46,16 +55,17 @@ pub fn burn_from_holder(
 
     // Burn all bond token from this holder
     let holder_addr = deps.api.addr_validate(holder.as_str())?;
-    BALANCES.update(deps.storage, &holder_addr, |_| -> StdResult<_> {
-        Ok(Uint128::zero())
-    })?;
+    let balance = BALANCES
+        .may_load(deps.storage, &holder_addr)?
+        .unwrap_or_default();
+    BALANCES.save(deps.storage, &holder_addr, &Uint128::zero())?;
 
     // Reduce total_supply
-    let balance = BALANCES.load(deps.storage, &holder_addr)?;
     TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
         info.total_supply = info.total_supply.checked_sub(balance)?;
         Ok(info)
     })?;
+    checkpoint_balances(deps.storage, env.block.height, &[holder_addr])?;
 
     Ok(Response::new()
         .add_attribute("action", "burn_from_holder")
@@ -83,7 +93,7 @@ pub fn mint_to_investor(
     }
 
     // Validate issuer
-    if BOND_TOKEN.load(deps.storage)?.issuer.to_string() != issuer {
+    if BOND_TOKEN.load(deps.storage)?.issuer != issuer {
         return Err(BondTokenErr::AdditionalError(AdditionalError::NotIssuer {
             caller: issuer,
         }));
@@ -93,16 +103,169 @@ pub fn mint_to_investor(
     let denomination = BOND_TOKEN.load(deps.storage)?.denomination;
     let bond_amount = currency_amount * denomination.bond_amount / denomination.currency_amount;
 
-    match contract::execute_mint(deps, env, info, recipient, bond_amount) {
-        Ok(response) => Ok(response),
-        Err(err) => Err(BondTokenErr::BasicError(err)),
+    execute_cw20(
+        deps,
+        env,
+        info,
+        Cw20ExecuteMsg::Mint {
+            recipient,
+            amount: bond_amount,
+        },
+    )
+}
+
+// Runs a basic cw20 function, then checkpoints the balances it may have changed
+pub fn execute_cw20(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw20ExecuteMsg,
+) -> Result<Response, BondTokenErr> {
+    let bond_token = BOND_TOKEN.load(deps.storage)?;
+
+    // Whoever receives bonds must be allowlisted and pass the identity check
+    let recipient = match &msg {
+        Cw20ExecuteMsg::Transfer { recipient, .. }
+        | Cw20ExecuteMsg::TransferFrom { recipient, .. }
+        | Cw20ExecuteMsg::Mint { recipient, .. } => Some(recipient),
+        Cw20ExecuteMsg::Send { contract, .. } | Cw20ExecuteMsg::SendFrom { contract, .. } => {
+            Some(contract)
+        }
+        _ => None,
+    };
+    // Nothing is minted once the bond is cancelled or being redeemed
+    if matches!(msg, Cw20ExecuteMsg::Mint { .. })
+        && matches!(
+            bond_token.current_phase,
+            Phase::Cancelled | Phase::Redemption
+        )
+    {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::ActionNotAllowed {
+                action: String::from("mint"),
+            },
+        ));
+    }
+    if let Some(recipient) = recipient {
+        check_allowlist(deps.as_ref(), &bond_token, recipient)?;
+        check_identity(deps.as_ref(), &bond_token, recipient)?;
+    }
+
+    let accounts: Vec<String> = match &msg {
+        Cw20ExecuteMsg::Transfer { recipient, .. } => {
+            vec![info.sender.to_string(), recipient.clone()]
+        }
+        Cw20ExecuteMsg::Send { contract, .. } => vec![info.sender.to_string(), contract.clone()],
+        Cw20ExecuteMsg::Burn { .. } => vec![info.sender.to_string()],
+        Cw20ExecuteMsg::Mint { recipient, .. } => vec![recipient.clone()],
+        Cw20ExecuteMsg::TransferFrom {
+            owner, recipient, ..
+        } => vec![owner.clone(), recipient.clone()],
+        Cw20ExecuteMsg::SendFrom {
+            owner, contract, ..
+        } => vec![owner.clone(), contract.clone()],
+        Cw20ExecuteMsg::BurnFrom { owner, .. } => vec![owner.clone()],
+        _ => vec![],
+    };
+    if matches!(
+        msg,
+        Cw20ExecuteMsg::Transfer { .. }
+            | Cw20ExecuteMsg::Send { .. }
+            | Cw20ExecuteMsg::TransferFrom { .. }
+            | Cw20ExecuteMsg::SendFrom { .. }
+    ) {
+        check_transferable(&bond_token)?;
+    }
+    let height = env.block.height;
+    let response =
+        contract::execute(deps.branch(), env, info, msg).map_err(BondTokenErr::BasicError)?;
+
+    // Addresses were validated by cw20 when it succeeded
+    let accounts = accounts
+        .iter()
+        .map(|account| deps.api.addr_validate(account.as_str()))
+        .collect::<StdResult<Vec<_>>>()?;
+    checkpoint_balances(deps.storage, height, &accounts)?;
+    Ok(response)
+}
+
+// Bonds move between holders only if the issuer allows it, after distribution and before redemption
+fn check_transferable(bond_token: &BondToken) -> Result<(), BondTokenErr> {
+    let reason = if !bond_token.function_setup.transfer {
+        "transfer is disabled"
+    } else if bond_token.current_phase == Phase::Subscription {
+        "subscription phase"
+    } else if bond_token.current_phase == Phase::Redemption {
+        "redemption phase"
+    } else if bond_token.current_phase == Phase::Cancelled {
+        "bond cancelled"
+    } else {
+        return Ok(());
+    };
+    Err(BondTokenErr::AdditionalError(
+        AdditionalError::TransferNotAllowed {
+            reason: String::from(reason),
+        },
+    ))
+}
+
+// In allowlist mode, only investors named by the issuer can hold this bond
+fn check_allowlist(deps: Deps, bond_token: &BondToken, account: &str) -> Result<(), BondTokenErr> {
+    if bond_token.allowlist_enabled
+        && !ALLOWLIST.has(deps.storage, &deps.api.addr_validate(account)?)
+    {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::NotAllowlisted {
+                account: account.to_string(),
+            },
+        ));
     }
+    Ok(())
+}
+
+// Asks the bond's identity registry whether `account` meets its requirements
+fn check_identity(deps: Deps, bond_token: &BondToken, account: &str) -> Result<(), BondTokenErr> {
+    let identity_check = match &bond_token.identity_check {
+        Some(identity_check) => identity_check,
+        None => return Ok(()),
+    };
+    let verification_response: VerificationResponse = deps.querier.query_wasm_smart(
+        identity_check.registry.clone(),
+        &IdentityRegistryQueryMsg::Verify {
+            investor: account.to_string(),
+            requirements: identity_check.requirements.clone(),
+        },
+    )?;
+    if !verification_response.verified {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::IdentityNotVerified {
+                account: account.to_string(),
+                reason: verification_response.reason.unwrap_or_default(),
+            },
+        ));
+    }
+    Ok(())
+}
+
+// Copies current balances of `accounts` and total supply into their snapshots
+pub fn checkpoint_balances(
+    storage: &mut dyn Storage,
+    height: u64,
+    accounts: &[Addr],
+) -> StdResult<()> {
+    for account in accounts {
+        let balance = BALANCES.may_load(storage, account)?.unwrap_or_default();
+        BALANCE_SNAPSHOTS.save(storage, account, &balance, height)?;
+    }
+    let total_supply = TOKEN_INFO.load(storage)?.total_supply;
+    TOTAL_SUPPLY_SNAPSHOTS.save(storage, &total_supply, height)
 }
 
 /* Additional functions */
 
 pub fn subscribe(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     subscription_amount: Uint128,
     fee_amount: Uint128,
@@ -125,14 +288,29 @@ pub fn subscribe(
         ));
     }
 
+    // Can only subscribe within the subscription window
+    if env.block.time < bond_token_platform.subscription_start {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+            action: String::from("subscription"),
+            time: bond_token_platform.subscription_start,
+        }));
+    }
+    if env.block.time >= bond_token_platform.subscription_end {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::TooLate {
+            action: String::from("subscription"),
+            time: bond_token_platform.subscription_end,
+        }));
+    }
+
+    // Only allowlisted and verified investors can subscribe
+    check_allowlist(deps.as_ref(), &bond_token_platform, info.sender.as_str())?;
+    check_identity(deps.as_ref(), &bond_token_platform, info.sender.as_str())?;
+
     let currency_amount = subscription_amount + fee_amount;
 
     // Calculate fee, charging by percentage is more prioritized than charging by fixed value
-    let mut subscription_fee = Uint128::zero();
-    match BOND_TOKEN.load(deps.storage)?.subscription_fee_percentage {
-        Some(percentage) => {
-            subscription_fee = currency_amount * percentage / Uint128::from(MAX_FEE_PERCENTAGE)
-        }
+    let subscription_fee = match BOND_TOKEN.load(deps.storage)?.subscription_fee_percentage {
+        Some(percentage) => currency_amount * percentage / Uint128::from(MAX_FEE_PERCENTAGE),
         None => match BOND_TOKEN.load(deps.storage)?.subscription_fee {
             Some(fee) => {
                 if currency_amount <= fee {
@@ -142,43 +320,157 @@ pub fn subscribe(
                         },
                     ));
                 }
-                subscription_fee = fee;
+                fee
             }
-            None => subscription_fee = fee_amount,
+            None => fee_amount,
         },
+    };
+
+    // Subscriptions must stay within the issuer's limits
+    let net_amount = currency_amount - subscription_fee;
+    let total_subscribed =
+        placeholder_total_subscribed(deps.as_ref(), &env, &bond_token_platform)? + net_amount;
+    let investor_subscribed = placeholder_subscription(
+        deps.as_ref(),
+        &env,
+        &bond_token_platform,
+        info.sender.as_str(),
+    )?
+    .currency_amount
+        + net_amount;
+    let limits = &bond_token_platform.subscription_limits;
+    if let Some(minimum) = limits.min_subscription {
+        if investor_subscribed < minimum {
+            return Err(BondTokenErr::AdditionalError(
+                AdditionalError::SubscriptionBelowMinimum {
+                    amount: investor_subscribed,
+                    minimum,
+                },
+            ));
+        }
+    }
+    if let Some(maximum) = limits.max_subscription_per_investor {
+        if investor_subscribed > maximum {
+            return Err(BondTokenErr::AdditionalError(
+                AdditionalError::SubscriptionAboveMaximum {
+                    amount: investor_subscribed,
+                    maximum,
+                },
+            ));
+        }
+    }
+    if let Some(hard_cap) = limits.hard_cap {
+        if total_subscribed > hard_cap {
+            return Err(BondTokenErr::AdditionalError(
+                AdditionalError::HardCapExceeded {
+                    amount: total_subscribed,
+                    hard_cap,
+                },
+            ));
+        }
     }
 
     // Transfer currency from investor's wallet to system placeholder
-    let mut messages: Vec<SubMsg> = vec![];
-    messages.push(SubMsg::new(WasmMsg::Execute {
-        contract_addr: bond_token_platform.currency.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-            owner: info.sender.to_string(),
-            recipient: bond_token_platform.placeholder.to_string(),
-            amount: currency_amount,
-        })?,
-        funds: vec![],
-    }));
-
-    // Register investor's subscription in placeholder
-    messages.push(SubMsg::new(WasmMsg::Execute {
-        contract_addr: BOND_TOKEN.load(deps.storage)?.placeholder.to_string(),
-        msg: to_binary(&PlaceholderExecuteMsg::RegisterSubscription {
-            investor: info.sender.to_string(),
-            currency: bond_token_platform.currency.to_string(),
-            subscription_amount: currency_amount - subscription_fee,
-            fee_amount: subscription_fee,
-        })?,
-        funds: vec![],
-    }));
+    let messages: Vec<SubMsg> = vec![
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: bond_token_platform.currency.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: bond_token_platform.placeholder.to_string(),
+                amount: currency_amount,
+            })?,
+            funds: vec![],
+        }),
+        // Register investor's subscription in placeholder
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: BOND_TOKEN.load(deps.storage)?.placeholder.to_string(),
+            msg: to_binary(&PlaceholderExecuteMsg::RegisterSubscription {
+                investor: info.sender.to_string(),
+                subscription_amount: net_amount,
+                fee_amount: subscription_fee,
+            })?,
+            funds: vec![],
+        }),
+    ];
 
     Ok(Response::new()
         .add_attribute("action", "subscribe")
         .add_submessages(messages))
 }
 
+// Investors can take back part or all of their subscription while the window is open,
+// `amount` is net of fee and None takes back everything
+pub fn cancel_subscription(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+) -> Result<Response, BondTokenErr> {
+    let bond_token = BOND_TOKEN.load(deps.storage)?;
+    if bond_token.current_phase != Phase::Subscription {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::ActionNotAllowed {
+                action: String::from("cancellation"),
+            },
+        ));
+    }
+    if env.block.time >= bond_token.subscription_end {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::TooLate {
+            action: String::from("cancellation"),
+            time: bond_token.subscription_end,
+        }));
+    }
+
+    let subscribed =
+        placeholder_subscription(deps.as_ref(), &env, &bond_token, info.sender.as_str())?
+            .currency_amount;
+    if subscribed.is_zero() {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::NothingToRefund {
+                investor: info.sender.to_string(),
+            },
+        ));
+    }
+    let currency_amount = amount.unwrap_or(subscribed);
+    if currency_amount.is_zero() || currency_amount > subscribed {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::InsufficientSubscriptionAmount {
+                amount: currency_amount,
+            },
+        ));
+    }
+
+    // What is left must still meet the minimum subscription
+    let remaining = subscribed - currency_amount;
+    if let Some(minimum) = bond_token.subscription_limits.min_subscription {
+        if !remaining.is_zero() && remaining < minimum {
+            return Err(BondTokenErr::AdditionalError(
+                AdditionalError::SubscriptionBelowMinimum {
+                    amount: remaining,
+                    minimum,
+                },
+            ));
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_subscription")
+        .add_attribute("investor", info.sender.to_string())
+        .add_attribute("amount", currency_amount)
+        .add_submessage(SubMsg::new(WasmMsg::Execute {
+            contract_addr: bond_token.placeholder.to_string(),
+            msg: to_binary(&PlaceholderExecuteMsg::RefundSubscription {
+                investor: info.sender.to_string(),
+                currency_amount,
+                refund_fee: bond_token.cancellation_fee_policy == CancellationFeePolicy::Refund,
+            })?,
+            funds: vec![],
+        })))
+}
+
 pub fn update_phase(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     phase: Phase,
 ) -> Result<Response, BondTokenErr> {
@@ -191,13 +483,33 @@ pub fn update_phase(
         ));
     }
 
+    let bond_token = BOND_TOKEN.load(deps.storage)?;
+    let soft_cap_missed = match (&bond_token.current_phase, &phase) {
+        (Phase::Subscription, Phase::Distribution) => {
+            soft_cap_missed(deps.as_ref(), &env, &bond_token)?
+        }
+        _ => None,
+    };
     BOND_TOKEN.update(deps.storage, |mut bond_token| -> Result<_, BondTokenErr> {
         // Can only increase at most one phase
         match bond_token.current_phase {
             Phase::Subscription => {
-                if phase != Phase::Distribution {
+                if phase != Phase::Distribution && phase != Phase::Cancelled {
                     return Err(BondTokenErr::AdditionalError(AdditionalError::InvalidPhase));
                 }
+                // Distribution only starts once the subscription window has closed
+                if phase == Phase::Distribution && env.block.time < bond_token.subscription_end {
+                    return Err(BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+                        action: String::from("distribution"),
+                        time: bond_token.subscription_end,
+                    }));
+                }
+                // Bonds are not distributed when too little was subscribed
+                if let Some((amount, soft_cap)) = soft_cap_missed {
+                    return Err(BondTokenErr::AdditionalError(
+                        AdditionalError::SoftCapNotReached { amount, soft_cap },
+                    ));
+                }
             }
             Phase::Distribution => {
                 if phase != Phase::Coupon {
@@ -208,8 +520,15 @@ pub fn update_phase(
                 if phase != Phase::Coupon && phase != Phase::Redemption {
                     return Err(BondTokenErr::AdditionalError(AdditionalError::InvalidPhase));
                 }
+                // Principals can only be redeemed at maturity
+                if phase == Phase::Redemption && env.block.time < bond_token.maturity_date {
+                    return Err(BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+                        action: String::from("redemption"),
+                        time: bond_token.maturity_date,
+                    }));
+                }
             }
-            Phase::Redemption => {
+            Phase::Redemption | Phase::Cancelled => {
                 return Err(BondTokenErr::AdditionalError(AdditionalError::InvalidPhase));
             }
         }
@@ -221,3 +540,259 @@ pub fn update_phase(
 
     Ok(Response::new().add_attribute("action", "update_phase"))
 }
+
+// Investors get their subscription back when the window closed below the soft cap
+pub fn claim_refund(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, BondTokenErr> {
+    let bond_token = BOND_TOKEN.load(deps.storage)?;
+    if bond_token.current_phase != Phase::Subscription {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::RefundNotAvailable,
+        ));
+    }
+    if env.block.time < bond_token.subscription_end {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+            action: String::from("refund"),
+            time: bond_token.subscription_end,
+        }));
+    }
+    if soft_cap_missed(deps.as_ref(), &env, &bond_token)?.is_none() {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::RefundNotAvailable,
+        ));
+    }
+
+    let currency_amount =
+        placeholder_subscription(deps.as_ref(), &env, &bond_token, info.sender.as_str())?
+            .currency_amount;
+    if currency_amount.is_zero() {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::NothingToRefund {
+                investor: info.sender.to_string(),
+            },
+        ));
+    }
+
+    // Placeholder returns the subscription and its fee to the investor, the bond never went ahead
+    Ok(Response::new()
+        .add_attribute("action", "claim_refund")
+        .add_attribute("investor", info.sender.to_string())
+        .add_attribute("amount", currency_amount)
+        .add_submessage(SubMsg::new(WasmMsg::Execute {
+            contract_addr: bond_token.placeholder.to_string(),
+            msg: to_binary(&PlaceholderExecuteMsg::RefundSubscription {
+                investor: info.sender.to_string(),
+                currency_amount,
+                refund_fee: true,
+            })?,
+            funds: vec![],
+        })))
+}
+
+// Investor's subscription to this bond, as registered in the placeholder
+fn placeholder_subscription(
+    deps: Deps,
+    env: &Env,
+    bond_token: &BondToken,
+    investor: &str,
+) -> StdResult<SubscriptionResponse> {
+    deps.querier.query_wasm_smart(
+        bond_token.placeholder.to_string(),
+        &PlaceholderQueryMsg::SubscriptionOf {
+            bond_token: env.contract.address.to_string(),
+            investor: investor.to_string(),
+        },
+    )
+}
+
+// Sum of all subscriptions to this bond, as registered in the placeholder
+pub fn placeholder_total_subscribed(
+    deps: Deps,
+    env: &Env,
+    bond_token: &BondToken,
+) -> StdResult<Uint128> {
+    let total_response: SubscriptionTotalResponse = deps.querier.query_wasm_smart(
+        bond_token.placeholder.to_string(),
+        &PlaceholderQueryMsg::SubscriptionTotal {
+            bond_token: env.contract.address.to_string(),
+        },
+    )?;
+    Ok(total_response.currency_amount)
+}
+
+// Returns the total subscribed and the soft cap when the total falls short of it
+fn soft_cap_missed(
+    deps: Deps,
+    env: &Env,
+    bond_token: &BondToken,
+) -> StdResult<Option<(Uint128, Uint128)>> {
+    let soft_cap = match bond_token.subscription_limits.soft_cap {
+        Some(soft_cap) => soft_cap,
+        None => return Ok(None),
+    };
+    let total_subscribed = placeholder_total_subscribed(deps, env, bond_token)?;
+    Ok((total_subscribed < soft_cap).then_some((total_subscribed, soft_cap)))
+}
+
+pub fn update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    investors: Vec<String>,
+    allowed: bool,
+) -> Result<Response, BondTokenErr> {
+    // Only issuer manages the allowlist
+    if info.sender != BOND_TOKEN.load(deps.storage)?.issuer {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::NotIssuer {
+            caller: info.sender.to_string(),
+        }));
+    }
+
+    for investor in investors.iter() {
+        let investor = deps.api.addr_validate(investor.as_str())?;
+        if allowed {
+            ALLOWLIST.save(deps.storage, &investor, &true)?;
+        } else {
+            ALLOWLIST.remove(deps.storage, &investor);
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute(
+            "action",
+            if allowed {
+                "add_to_allowlist"
+            } else {
+                "remove_from_allowlist"
+            },
+        )
+        .add_attribute("investors", investors.join(",")))
+}
+
+pub fn record_coupon_payment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    period: u32,
+) -> Result<Response, BondTokenErr> {
+    // Only router can record coupon payments
+    let bond_token = BOND_TOKEN.load(deps.storage)?;
+    if info.sender != bond_token.router {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::NotRouter {
+            caller: info.sender.to_string(),
+        }));
+    }
+    mark_coupon_paid(deps.storage, &env, &bond_token, period)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "record_coupon_payment")
+        .add_attribute("period", period.to_string()))
+}
+
+pub fn record_coupon_entitlements(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    period: u32,
+) -> Result<Response, BondTokenErr> {
+    // Only router can record coupon entitlements
+    let bond_token = BOND_TOKEN.load(deps.storage)?;
+    if info.sender != bond_token.router {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::NotRouter {
+            caller: info.sender.to_string(),
+        }));
+    }
+    mark_coupon_paid(deps.storage, &env, &bond_token, period)?;
+
+    // Each holder is owed its coupon on the balance it held at the start of this block,
+    // worked out when it claims
+    COUPON_RECORD_HEIGHTS.save(deps.storage, period, &env.block.height)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "record_coupon_entitlements")
+        .add_attribute("period", period.to_string())
+        .add_attribute("record_height", env.block.height.to_string()))
+}
+
+pub fn claim_coupon_entitlement(
+    deps: DepsMut,
+    info: MessageInfo,
+    period: u32,
+    investor: String,
+) -> Result<Response, BondTokenErr> {
+    // Only router can mark entitlements as claimed, it holds the coupon funds
+    if info.sender != BOND_TOKEN.load(deps.storage)?.router {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::NotRouter {
+            caller: info.sender.to_string(),
+        }));
+    }
+
+    let investor_addr = deps.api.addr_validate(investor.as_str())?;
+    if COUPON_ENTITLEMENTS.has(deps.storage, (period, &investor_addr)) {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::CouponAlreadyClaimed { period, investor },
+        ));
+    }
+    let amount = coupon_entitlement(deps.as_ref(), period, &investor_addr)?;
+    if amount.is_zero() {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::CouponEntitlementNotFound { period, investor },
+        ));
+    }
+    let entitlement = CouponEntitlement {
+        amount,
+        claimed: true,
+    };
+    COUPON_ENTITLEMENTS.save(deps.storage, (period, &investor_addr), &entitlement)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_coupon_entitlement")
+        .add_attribute("period", period.to_string())
+        .add_attribute("investor", investor)
+        .add_attribute("amount", entitlement.amount))
+}
+
+// Coupon owed to `investor` for a period paid through entitlements, from its balance at the
+// period's record height. Zero if the period has no record height.
+pub fn coupon_entitlement(deps: Deps, period: u32, investor: &Addr) -> StdResult<Uint128> {
+    let bond_token = BOND_TOKEN.load(deps.storage)?;
+    let (Some(record_height), Some((start, end))) = (
+        COUPON_RECORD_HEIGHTS.may_load(deps.storage, period)?,
+        bond_token.coupon_period(period),
+    ) else {
+        return Ok(Uint128::zero());
+    };
+    let bond_balance = BALANCE_SNAPSHOTS
+        .may_load_at_height(deps.storage, investor, record_height)?
+        .unwrap_or_default();
+    let balance_in_currency = bond_balance * bond_token.denomination.currency_amount
+        / bond_token.denomination.bond_amount;
+    Ok(balance_in_currency
+        * (bond_token.coupon_terms.rate * bond_token.coupon_terms.accrual_fraction(start, end)))
+}
+
+// Each period is paid once, on or after its payment date
+fn mark_coupon_paid(
+    storage: &mut dyn Storage,
+    env: &Env,
+    bond_token: &BondToken,
+    period: u32,
+) -> Result<(), BondTokenErr> {
+    let (_, end) = bond_token
+        .coupon_period(period)
+        .ok_or(BondTokenErr::AdditionalError(
+            AdditionalError::CouponPeriodNotFound { period },
+        ))?;
+    let payment_date = bond_token.coupon_terms.payment_date(end);
+    if env.block.time < payment_date {
+        return Err(BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+            action: String::from("coupon payment"),
+            time: payment_date,
+        }));
+    }
+    if PAID_COUPONS.has(storage, period) {
+        return Err(BondTokenErr::AdditionalError(
+            AdditionalError::CouponAlreadyPaid { period },
+        ));
+    }
+    PAID_COUPONS.save(storage, period, &env.block.time)?;
+    Ok(())
+}
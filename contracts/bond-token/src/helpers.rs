@@ -1,10 +1,16 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Timestamp, Uint128};
+use day_count::{AccrualPeriod, BusinessDayConvention, DayCount};
+use identity_registry::helpers::IdentityRequirements;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub const MAX_FEE_PERCENTAGE: u128 = 10000;
 
+// Pagination
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct FunctionSetup {
     pub transfer: bool,
@@ -13,6 +19,63 @@ pub struct FunctionSetup {
     pub subscribe: bool,
 }
 
+#[cw_serde]
+#[derive(Eq)]
+pub struct CouponTerms {
+    pub rate: Decimal,         // annual coupon rate, 0.05 means 5% per year
+    pub frequency: u32,        // number of coupon payments per year
+    pub dates: Vec<Timestamp>, // unadjusted coupon dates, period N ends on the N-th date
+    pub day_count: DayCount,
+    pub business_day_convention: BusinessDayConvention,
+    pub holidays: Vec<Timestamp>, // issuer's non-business days, on top of weekends
+}
+
+impl CouponTerms {
+    // Share of the annual rate accrued from `start` to `end`, accrual runs on unadjusted dates
+    pub fn accrual_fraction(&self, start: Timestamp, end: Timestamp) -> Decimal {
+        self.day_count.year_fraction(
+            start,
+            end,
+            AccrualPeriod {
+                start,
+                end,
+                frequency: self.frequency,
+            },
+        )
+    }
+
+    // Date on which a coupon falling on `date` is actually paid
+    pub fn payment_date(&self, date: Timestamp) -> Timestamp {
+        self.business_day_convention.adjust(date, &self.holidays)
+    }
+}
+
+// Limits on subscriptions, in currency net of the subscription fee, None means no limit
+#[cw_serde]
+#[derive(Eq, Default)]
+pub struct SubscriptionLimits {
+    pub hard_cap: Option<Uint128>, // total subscriptions can never exceed it
+    pub soft_cap: Option<Uint128>, // total needed by the end of the window for the bond to be distributed
+    pub min_subscription: Option<Uint128>, // smallest total an investor can subscribe
+    pub max_subscription_per_investor: Option<Uint128>,
+}
+
+// What happens to the subscription fee when an investor cancels or reduces a subscription
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum CancellationFeePolicy {
+    Keep,   // the platform keeps the whole fee
+    Refund, // the fee paid on the cancelled part goes back to the investor
+}
+
+// Identity registry consulted before an account can receive this bond
+#[cw_serde]
+#[derive(Eq)]
+pub struct IdentityCheck {
+    pub registry: String,
+    pub requirements: IdentityRequirements,
+}
+
 #[cw_serde]
 pub struct Holder {
     pub account: String,
@@ -27,6 +90,24 @@ pub struct Denomination {
     pub bond_amount: Uint128,
 }
 
+// Economic terms and schedule of a bond, given at instantiation
+#[cw_serde]
+pub struct BondTerms {
+    pub denomination: Denomination,
+    pub subscription_fee_percentage: Option<Uint128>, // fee charged by percentage, values [0 -> 10000] map to [0% -> 100%]
+    pub subscription_fee: Option<Uint128>,            // fixed fee value
+    pub subscription_start: Timestamp,
+    pub subscription_end: Timestamp,
+    pub issue_date: Timestamp,
+    pub maturity_date: Timestamp,
+    pub coupon_terms: CouponTerms,
+    pub identity_check: Option<IdentityCheck>, // no identity check when None
+    pub allowlist: Option<Vec<String>>, // no allowlist when None, initial investors otherwise
+    pub subscription_limits: SubscriptionLimits,
+    pub cancellation_fee_policy: CancellationFeePolicy,
+    pub distribution_deadline: Timestamp, // investors can claim refunds from placeholder after this if nothing was distributed
+}
+
 #[cw_serde]
 pub struct BondTokenResponse {}
 
@@ -50,10 +131,71 @@ pub struct RedemptionAmountResponse {
     pub redemption_amount: Uint128,
 }
 
+#[cw_serde]
+pub struct BondScheduleResponse {
+    pub subscription_start: Timestamp,
+    pub subscription_end: Timestamp,
+    pub distribution_deadline: Timestamp,
+    pub issue_date: Timestamp,
+    pub maturity_date: Timestamp,
+    pub current_phase: Phase,
+}
+
+#[cw_serde]
+pub struct CouponPeriodResponse {
+    pub period: u32,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub payment_date: Timestamp,
+    pub accrual_fraction: Decimal,
+    pub paid: bool,
+}
+
+#[cw_serde]
+pub struct CouponScheduleResponse {
+    pub rate: Decimal,
+    pub frequency: u32,
+    pub day_count: DayCount,
+    pub business_day_convention: BusinessDayConvention,
+    pub periods: Vec<CouponPeriodResponse>,
+}
+
+#[cw_serde]
+pub struct SubscriptionLimitsResponse {
+    pub limits: SubscriptionLimits,
+    pub total_subscribed: Uint128,
+}
+
+#[cw_serde]
+pub struct AllowlistResponse {
+    pub enabled: bool,
+    pub investors: Vec<String>,
+}
+
+#[cw_serde]
+pub struct TotalSupplyAtResponse {
+    pub total_supply: Uint128,
+    pub total_supply_in_currency: Uint128,
+}
+
+#[cw_serde]
+pub struct CouponEntitlementResponse {
+    pub period: u32,
+    pub investor: String,
+    pub amount: Uint128,
+    pub claimed: bool,
+}
+
+#[cw_serde]
+pub struct UnclaimedCouponsResponse {
+    pub entitlements: Vec<CouponEntitlementResponse>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub enum Phase {
     Subscription,
     Distribution,
     Coupon,
     Redemption,
+    Cancelled, // issuer abandoned the bond before distribution, subscriptions are refunded
 }
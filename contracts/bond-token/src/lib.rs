@@ -5,4 +5,4 @@ pub mod helpers;
 pub mod msg;
 pub mod state;
 
-pub use crate::error::BondTokenErr;
+pub use crate::error::{AdditionalError, BondTokenErr};
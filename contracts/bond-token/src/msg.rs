@@ -1,9 +1,12 @@
 use crate::helpers::{
-    BondTokenResponse, CurrencyResponse, Denomination, FunctionSetup, HoldersResponse,
-    IssuerResponse, Phase, RedemptionAmountResponse,
+    AllowlistResponse, BondScheduleResponse, BondTerms, BondTokenResponse,
+    CouponEntitlementResponse, CouponScheduleResponse, CurrencyResponse, FunctionSetup,
+    HoldersResponse, IssuerResponse, Phase, RedemptionAmountResponse, SubscriptionLimitsResponse,
+    TotalSupplyAtResponse, UnclaimedCouponsResponse,
 };
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Uint128;
+use cw20::BalanceResponse;
 use cw20_base::msg::{
     ExecuteMsg as Cw20ExecuteMsg, InstantiateMsg as Cw20InstantiateMsg, QueryMsg as Cw20QueryMsg,
 };
@@ -17,9 +20,7 @@ pub struct InstantiateMsg {
     pub currency: String,
     pub placeholder: String,
     pub router: String,
-    pub denomination: Denomination,
-    pub subscription_fee_percentage: Option<Uint128>, // fee charged by percentage, values [0 -> 10000] map to [0% -> 100%]
-    pub subscription_fee: Option<Uint128>,            // fixed fee value
+    pub terms: BondTerms,
 }
 
 #[cw_serde]
@@ -72,9 +73,34 @@ pub enum AdditionalExecuteMsg {
         subscription_amount: Uint128, // In currrency
         fee_amount: Uint128,
     },
+    // whole subscription back while the window is open
+    CancelSubscription {},
+    // part of the subscription back, amount in currency net of fee
+    ReduceSubscription {
+        amount: Uint128,
+    },
     UpdatePhase {
         phase: Phase,
     },
+    // subscription and fee back when the soft cap was missed
+    ClaimRefund {},
+    RecordCouponPayment {
+        period: u32,
+    },
+    // records this block as the period's record date, holders claim through router
+    RecordCouponEntitlements {
+        period: u32,
+    },
+    ClaimCouponEntitlement {
+        period: u32,
+        investor: String,
+    },
+    AddToAllowlist {
+        investors: Vec<String>,
+    },
+    RemoveFromAllowlist {
+        investors: Vec<String>,
+    },
 }
 
 #[cw_serde]
@@ -83,6 +109,13 @@ pub enum AdditionalQueryMsg {
     #[returns(HoldersResponse)]
     GetHolders {},
 
+    // paged GetHolders
+    #[returns(HoldersResponse)]
+    Holders {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     #[returns(IssuerResponse)]
     GetIssuer {},
 
@@ -91,4 +124,39 @@ pub enum AdditionalQueryMsg {
 
     #[returns(RedemptionAmountResponse)]
     EstimateRedempmtionAmount {},
+
+    #[returns(BondScheduleResponse)]
+    BondSchedule {},
+
+    #[returns(CouponScheduleResponse)]
+    CouponSchedule {},
+
+    #[returns(SubscriptionLimitsResponse)]
+    SubscriptionLimits {},
+
+    #[returns(CouponEntitlementResponse)]
+    CouponEntitlement { period: u32, investor: String },
+
+    #[returns(UnclaimedCouponsResponse)]
+    UnclaimedCoupons { investor: String },
+
+    // paged holders with their balance at the start of block height, zero if none then
+    #[returns(HoldersResponse)]
+    HoldersAt {
+        height: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(BalanceResponse)]
+    BalanceAt { address: String, height: u64 },
+
+    #[returns(TotalSupplyAtResponse)]
+    TotalSupplyAt { height: u64 },
+
+    #[returns(AllowlistResponse)]
+    Allowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
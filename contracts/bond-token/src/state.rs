@@ -1,6 +1,9 @@
-use crate::helpers::{Denomination, FunctionSetup, Phase};
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::Item;
+use crate::helpers::{
+    CancellationFeePolicy, CouponTerms, Denomination, FunctionSetup, IdentityCheck, Phase,
+    SubscriptionLimits,
+};
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -15,7 +18,57 @@ pub struct BondToken {
     pub denomination: Denomination,
     pub subscription_fee_percentage: Option<Uint128>,
     pub subscription_fee: Option<Uint128>,
+    pub subscription_limits: SubscriptionLimits,
+    pub cancellation_fee_policy: CancellationFeePolicy,
     pub current_phase: Phase,
+    pub subscription_start: Timestamp,
+    pub subscription_end: Timestamp,
+    pub distribution_deadline: Timestamp,
+    pub issue_date: Timestamp,
+    pub maturity_date: Timestamp,
+    pub coupon_terms: CouponTerms,
+    pub identity_check: Option<IdentityCheck>,
+    pub allowlist_enabled: bool, // only allowlisted investors can subscribe and receive bonds
+}
+
+impl BondToken {
+    // Returns the start and end of a coupon period, periods are numbered from 1
+    pub fn coupon_period(&self, period: u32) -> Option<(Timestamp, Timestamp)> {
+        let dates = &self.coupon_terms.dates;
+        let index = (period as usize).checked_sub(1)?;
+        let end = *dates.get(index)?;
+        let start = match index {
+            0 => self.issue_date,
+            _ => dates[index - 1],
+        };
+        Some((start, end))
+    }
+}
+
+// Coupon one holder claimed for one period through the router
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CouponEntitlement {
+    pub amount: Uint128,
+    pub claimed: bool,
 }
 
 pub const BOND_TOKEN: Item<BondToken> = Item::new("bond_token");
+pub const ALLOWLIST: Map<&Addr, bool> = Map::new("allowlist");
+pub const PAID_COUPONS: Map<u32, Timestamp> = Map::new("paid_coupons"); // maps from a coupon period to when it was paid
+pub const COUPON_ENTITLEMENTS: Map<(u32, &Addr), CouponEntitlement> =
+    Map::new("coupon_entitlements"); // maps from (coupon period, holder) to its claimed entitlement
+pub const COUPON_RECORD_HEIGHTS: Map<u32, u64> = Map::new("coupon_record_heights"); // maps from a coupon period to the height entitlements are read at
+
+// Copies of cw20 balances and total supply, checkpointed on every change to read them at a record height
+pub const BALANCE_SNAPSHOTS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance_snapshots",
+    "balance_snapshots__checkpoints",
+    "balance_snapshots__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_SUPPLY_SNAPSHOTS: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply_snapshots",
+    "total_supply_snapshots__checkpoints",
+    "total_supply_snapshots__changelog",
+    Strategy::EveryBlock,
+);
@@ -1,21 +1,28 @@
-use bond_token::{
-    helpers::{Denomination, FunctionSetup},
-    msg::InstantiateMsg as BondTokenInstantiateMsg,
-};
+use bond_token::msg::InstantiateMsg as BondTokenInstantiateMsg;
 #[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg,
+    to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response,
+    StdResult, SubMsg, Uint64, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw20::{Cw20Coin, MinterResponse};
-use cw20_base::msg::{InstantiateMarketingInfo, InstantiateMsg as Cw20InstantiateMsg};
+use cw20::MinterResponse;
+use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+use cw_storage_plus::Bound;
+use cw_utils::parse_reply_instantiate_data;
+use placeholder::msg::ExecuteMsg as PlaceholderExecuteMsg;
 
 use crate::{
     error::FactoryErr,
-    helpers::ContractInfo,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
-    state::{FactoryPlatform, FACTORY_PLATFORM, OPERATORS},
+    helpers::{
+        BondTokenInfo, BondTokensResponse, ContractInfo, CurrenciesResponse, CurrencyInfo,
+        DEFAULT_LIMIT, INSTANTIATE_BOND_TOKEN_REPLY_ID, INSTANTIATE_CURRENCY_REPLY_ID, MAX_LIMIT,
+    },
+    msg::{ExecuteMsg, InstantiateBondTokenMsg, InstantiateMsg, QueryMsg},
+    state::{
+        bond_tokens, currencies, BondTokenRecord, CurrencyRecord, FactoryPlatform, PendingContract,
+        FACTORY_PLATFORM, OPERATORS, PENDING_CONTRACT,
+    },
 };
 
 // version info for migration info
@@ -73,40 +80,16 @@ pub fn execute(
         } => execute::instantiate_currency(
             deps,
             info,
-            name,
-            symbol,
-            decimals,
-            initial_balances,
-            mint,
-            marketing,
-        ),
-        ExecuteMsg::InstantiateBondToken {
-            issuer,
-            name,
-            symbol,
-            decimals,
-            initial_balances,
-            function_setup,
-            additional_data,
-            currency,
-            denomination,
-            subscription_fee_percentage,
-            subscription_fee,
-        } => execute::instantiate_bond_token(
-            deps,
-            info,
-            issuer,
-            name,
-            symbol,
-            decimals,
-            initial_balances,
-            function_setup,
-            additional_data,
-            currency,
-            denomination,
-            subscription_fee_percentage,
-            subscription_fee,
+            Cw20InstantiateMsg {
+                name,
+                symbol,
+                decimals,
+                initial_balances,
+                mint,
+                marketing,
+            },
         ),
+        ExecuteMsg::InstantiateBondToken(msg) => execute::instantiate_bond_token(deps, info, *msg),
         ExecuteMsg::InstantiateBatch { contract_infos } => {
             execute::instantiate_batch(deps, info, contract_infos)
         }
@@ -114,8 +97,46 @@ pub fn execute(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {}
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, FactoryErr> {
+    match msg.id {
+        INSTANTIATE_CURRENCY_REPLY_ID => reply::register_currency(deps, env, msg),
+        INSTANTIATE_BOND_TOKEN_REPLY_ID => reply::register_bond_token(deps, env, msg),
+        id => Err(FactoryErr::UnknownReplyId { id }),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Currencies { start_after, limit } => {
+            to_binary(&query::get_currencies(deps, start_after, limit)?)
+        }
+        QueryMsg::BondTokens {
+            issuer,
+            start_after,
+            limit,
+        } => to_binary(&query::get_bond_tokens(deps, issuer, start_after, limit)?),
+        QueryMsg::BondTokensByCurrency {
+            currency,
+            start_after,
+            limit,
+        } => to_binary(&query::get_bond_tokens_by_currency(
+            deps,
+            currency,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::BondTokensByCodeId {
+            code_id,
+            start_after,
+            limit,
+        } => to_binary(&query::get_bond_tokens_by_code_id(
+            deps,
+            code_id,
+            start_after,
+            limit,
+        )?),
+    }
 }
 
 pub mod execute {
@@ -167,12 +188,7 @@ pub mod execute {
     pub fn instantiate_currency(
         deps: DepsMut,
         info: MessageInfo,
-        name: String,
-        symbol: String,
-        decimals: u8,
-        initial_balances: Vec<Cw20Coin>,
-        mint: Option<MinterResponse>,
-        marketing: Option<InstantiateMarketingInfo>,
+        currency_info: Cw20InstantiateMsg,
     ) -> Result<Response, FactoryErr> {
         // Only System Addresses can instantiate bond tokens
         if !OPERATORS.load(deps.storage, info.sender.clone())? {
@@ -181,41 +197,38 @@ pub mod execute {
             });
         }
 
-        // Instantiate
-        let instantiate_msg = CosmosMsg::Wasm(WasmMsg::Instantiate {
+        // Instantiate, the new address is registered in the reply
+        let code_id = FACTORY_PLATFORM.load(deps.storage)?.currency_code_id.u64();
+        PENDING_CONTRACT.save(
+            deps.storage,
+            &PendingContract {
+                code_id,
+                issuer: None,
+                currency: None,
+                distribution_deadline: None,
+                allocation_fee_policy: None,
+            },
+        )?;
+        let instantiate_msg = WasmMsg::Instantiate {
             admin: Some(info.sender.to_string()),
-            code_id: FACTORY_PLATFORM.load(deps.storage)?.currency_code_id.u64(),
-            msg: to_binary(&Cw20InstantiateMsg {
-                name,
-                symbol,
-                decimals,
-                initial_balances,
-                mint,
-                marketing,
-            })?,
+            code_id,
+            msg: to_binary(&currency_info)?,
             funds: vec![],
             label: String::from("currency"),
-        });
+        };
 
         Ok(Response::new()
             .add_attribute("action", "instantiate_currency")
-            .add_message(instantiate_msg))
+            .add_submessage(SubMsg::reply_on_success(
+                instantiate_msg,
+                INSTANTIATE_CURRENCY_REPLY_ID,
+            )))
     }
 
     pub fn instantiate_bond_token(
         deps: DepsMut,
         info: MessageInfo,
-        issuer: String,
-        name: String,
-        symbol: String,
-        decimals: u8,
-        initial_balances: Vec<Cw20Coin>,
-        function_setup: FunctionSetup,
-        additional_data: String,
-        currency: String,
-        denomination: Denomination,
-        subscription_fee_percentage: Option<Uint128>,
-        subscription_fee: Option<Uint128>,
+        msg: InstantiateBondTokenMsg,
     ) -> Result<Response, FactoryErr> {
         // Only System Addresses can instantiate bond tokens
         if !OPERATORS.load(deps.storage, info.sender.clone())? {
@@ -224,18 +237,28 @@ pub mod execute {
             });
         }
 
-        // Instantiate
+        // Instantiate, the new address is registered in the reply
         let factory = FACTORY_PLATFORM.load(deps.storage)?;
-        let instantiate_msg = CosmosMsg::Wasm(WasmMsg::Instantiate {
+        PENDING_CONTRACT.save(
+            deps.storage,
+            &PendingContract {
+                code_id: factory.bond_token_code_id.u64(),
+                issuer: Some(deps.api.addr_validate(msg.issuer.as_str())?),
+                currency: Some(deps.api.addr_validate(msg.currency.as_str())?),
+                distribution_deadline: Some(msg.terms.distribution_deadline),
+                allocation_fee_policy: Some(msg.allocation_fee_policy),
+            },
+        )?;
+        let instantiate_msg = WasmMsg::Instantiate {
             admin: Some(info.sender.to_string()),
             code_id: factory.bond_token_code_id.u64(),
             msg: to_binary(&BondTokenInstantiateMsg {
-                issuer,
+                issuer: msg.issuer,
                 basic_info: Cw20InstantiateMsg {
-                    name,
-                    symbol,
-                    decimals,
-                    initial_balances,
+                    name: msg.name,
+                    symbol: msg.symbol,
+                    decimals: msg.decimals,
+                    initial_balances: msg.initial_balances,
                     mint: Some(MinterResponse {
                         minter: factory
                             .router
@@ -247,9 +270,9 @@ pub mod execute {
                     }),
                     marketing: None,
                 },
-                function_setup,
-                additional_data,
-                currency,
+                function_setup: msg.function_setup,
+                additional_data: msg.additional_data,
+                currency: msg.currency,
                 placeholder: factory
                     .placeholder
                     .ok_or(FactoryErr::ContractNotSetup {})
@@ -260,17 +283,18 @@ pub mod execute {
                     .ok_or(FactoryErr::ContractNotSetup {})
                     .unwrap()
                     .to_string(),
-                denomination,
-                subscription_fee_percentage,
-                subscription_fee,
+                terms: msg.terms,
             })?,
             funds: vec![],
             label: String::from("bond_token"),
-        });
+        };
 
         Ok(Response::new()
             .add_attribute("action", "instantiate_bond_token")
-            .add_message(instantiate_msg))
+            .add_submessage(SubMsg::reply_on_success(
+                instantiate_msg,
+                INSTANTIATE_BOND_TOKEN_REPLY_ID,
+            )))
     }
 
     pub fn instantiate_batch(
@@ -298,3 +322,192 @@ pub mod execute {
             .add_messages(messages))
     }
 }
+
+pub mod reply {
+    use super::*;
+
+    pub fn register_currency(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, FactoryErr> {
+        let response = parse_reply_instantiate_data(msg)?;
+        let address = deps.api.addr_validate(response.contract_address.as_str())?;
+        let pending = PENDING_CONTRACT.load(deps.storage)?;
+        PENDING_CONTRACT.remove(deps.storage);
+
+        currencies().save(
+            deps.storage,
+            address.clone(),
+            &CurrencyRecord {
+                address: address.clone(),
+                code_id: pending.code_id,
+                created_at: env.block.height,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "register_currency")
+            .add_attribute("currency", address.to_string())
+            .set_data(to_binary(&address)?))
+    }
+
+    pub fn register_bond_token(
+        deps: DepsMut,
+        env: Env,
+        msg: Reply,
+    ) -> Result<Response, FactoryErr> {
+        let response = parse_reply_instantiate_data(msg)?;
+        let address = deps.api.addr_validate(response.contract_address.as_str())?;
+        let pending = PENDING_CONTRACT.load(deps.storage)?;
+        PENDING_CONTRACT.remove(deps.storage);
+
+        let currency = pending.currency.ok_or(FactoryErr::ContractNotSetup {})?;
+        bond_tokens().save(
+            deps.storage,
+            address.clone(),
+            &BondTokenRecord {
+                address: address.clone(),
+                code_id: pending.code_id,
+                issuer: pending.issuer.ok_or(FactoryErr::ContractNotSetup {})?,
+                currency: currency.clone(),
+                created_at: env.block.height,
+            },
+        )?;
+
+        // Register the new bond token in placeholder so it can be subscribed right away
+        let placeholder = FACTORY_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(FactoryErr::ContractNotSetup {})?;
+        let register_msg = WasmMsg::Execute {
+            contract_addr: placeholder.to_string(),
+            msg: to_binary(&PlaceholderExecuteMsg::RegisterBondToken {
+                bond_token: address.to_string(),
+                currency: currency.to_string(),
+                distribution_deadline: pending
+                    .distribution_deadline
+                    .ok_or(FactoryErr::ContractNotSetup {})?,
+                allocation_fee_policy: pending
+                    .allocation_fee_policy
+                    .ok_or(FactoryErr::ContractNotSetup {})?,
+            })?,
+            funds: vec![],
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "register_bond_token")
+            .add_attribute("bond_token", address.to_string())
+            .add_message(register_msg)
+            .set_data(to_binary(&address)?))
+    }
+}
+
+pub mod query {
+    use super::*;
+
+    fn currency_info(record: CurrencyRecord) -> CurrencyInfo {
+        CurrencyInfo {
+            address: record.address.to_string(),
+            code_id: Uint64::from(record.code_id),
+            created_at: record.created_at,
+        }
+    }
+
+    fn bond_token_info(record: BondTokenRecord) -> BondTokenInfo {
+        BondTokenInfo {
+            address: record.address.to_string(),
+            code_id: Uint64::from(record.code_id),
+            issuer: record.issuer.to_string(),
+            currency: record.currency.to_string(),
+            created_at: record.created_at,
+        }
+    }
+
+    fn pagination(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<(Option<Bound<'static, Addr>>, usize)> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = match start_after {
+            Some(address) => Some(Bound::exclusive(deps.api.addr_validate(address.as_str())?)),
+            None => None,
+        };
+        Ok((start, limit))
+    }
+
+    pub fn get_currencies(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<CurrenciesResponse> {
+        let (start, limit) = pagination(deps, start_after, limit)?;
+        let currencies = currencies()
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, record)| currency_info(record)))
+            .collect::<StdResult<_>>()?;
+        Ok(CurrenciesResponse { currencies })
+    }
+
+    pub fn get_bond_tokens(
+        deps: Deps,
+        issuer: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BondTokensResponse> {
+        let (start, limit) = pagination(deps, start_after, limit)?;
+        let records = match issuer {
+            Some(issuer) => bond_tokens()
+                .idx
+                .issuer
+                .prefix(deps.api.addr_validate(issuer.as_str())?)
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?,
+            None => bond_tokens()
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?,
+        };
+        Ok(BondTokensResponse {
+            bond_tokens: records
+                .into_iter()
+                .map(|(_, record)| bond_token_info(record))
+                .collect(),
+        })
+    }
+
+    pub fn get_bond_tokens_by_currency(
+        deps: Deps,
+        currency: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BondTokensResponse> {
+        let (start, limit) = pagination(deps, start_after, limit)?;
+        let bond_tokens = bond_tokens()
+            .idx
+            .currency
+            .prefix(deps.api.addr_validate(currency.as_str())?)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, record)| bond_token_info(record)))
+            .collect::<StdResult<_>>()?;
+        Ok(BondTokensResponse { bond_tokens })
+    }
+
+    pub fn get_bond_tokens_by_code_id(
+        deps: Deps,
+        code_id: Uint64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BondTokensResponse> {
+        let (start, limit) = pagination(deps, start_after, limit)?;
+        let bond_tokens = bond_tokens()
+            .idx
+            .code_id
+            .prefix(code_id.u64())
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, record)| bond_token_info(record)))
+            .collect::<StdResult<_>>()?;
+        Ok(BondTokensResponse { bond_tokens })
+    }
+}
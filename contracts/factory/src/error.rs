@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use cw_utils::ParseReplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +7,9 @@ pub enum FactoryErr {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
     #[error("FactoryErr: {account:?} is not admin")]
     NotAdmin { account: String },
 
@@ -17,4 +21,7 @@ pub enum FactoryErr {
 
     #[error("FactoryErr: not setup")]
     ContractNotSetup {},
+
+    #[error("FactoryErr: unknown reply id ({id:?})")]
+    UnknownReplyId { id: u64 },
 }
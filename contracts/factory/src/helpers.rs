@@ -3,6 +3,12 @@ use cosmwasm_std::{Binary, Coin, Uint64};
 
 pub const NATIVE_DENOM: &str = "flavor";
 
+pub const INSTANTIATE_CURRENCY_REPLY_ID: u64 = 1;
+pub const INSTANTIATE_BOND_TOKEN_REPLY_ID: u64 = 2;
+
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
+
 #[cw_serde]
 pub struct ContractInfo {
     pub code_id: Uint64,
@@ -10,3 +16,29 @@ pub struct ContractInfo {
     pub funds: Vec<Coin>,
     pub label: String,
 }
+
+#[cw_serde]
+pub struct CurrencyInfo {
+    pub address: String,
+    pub code_id: Uint64,
+    pub created_at: u64,
+}
+
+#[cw_serde]
+pub struct BondTokenInfo {
+    pub address: String,
+    pub code_id: Uint64,
+    pub issuer: String,
+    pub currency: String,
+    pub created_at: u64,
+}
+
+#[cw_serde]
+pub struct CurrenciesResponse {
+    pub currencies: Vec<CurrencyInfo>,
+}
+
+#[cw_serde]
+pub struct BondTokensResponse {
+    pub bond_tokens: Vec<BondTokenInfo>,
+}
@@ -1,10 +1,11 @@
-use bond_token::helpers::{Denomination, FunctionSetup};
+use bond_token::helpers::{BondTerms, FunctionSetup};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Uint128, Uint64};
+use cosmwasm_std::Uint64;
 use cw20::{Cw20Coin, MinterResponse};
 use cw20_base::msg::InstantiateMarketingInfo;
+use placeholder::helpers::AllocationFeePolicy;
 
-use crate::helpers::ContractInfo;
+use crate::helpers::{BondTokensResponse, ContractInfo, CurrenciesResponse};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -30,24 +31,54 @@ pub enum ExecuteMsg {
         mint: Option<MinterResponse>,
         marketing: Option<InstantiateMarketingInfo>,
     },
-    InstantiateBondToken {
-        issuer: String,
-        name: String,
-        symbol: String,
-        decimals: u8,
-        initial_balances: Vec<Cw20Coin>,
-        function_setup: FunctionSetup,
-        additional_data: String,
-        currency: String,
-        denomination: Denomination,
-        subscription_fee_percentage: Option<Uint128>,
-        subscription_fee: Option<Uint128>,
-    },
+    InstantiateBondToken(Box<InstantiateBondTokenMsg>),
     InstantiateBatch {
         contract_infos: Vec<ContractInfo>,
     },
 }
 
+#[cw_serde]
+pub struct InstantiateBondTokenMsg {
+    pub issuer: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub function_setup: FunctionSetup,
+    pub additional_data: String,
+    pub currency: String,
+    pub terms: BondTerms,
+    pub allocation_fee_policy: AllocationFeePolicy,
+}
+
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    #[returns(CurrenciesResponse)]
+    Currencies {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Returns all bond tokens, or only those of `issuer` if given
+    #[returns(BondTokensResponse)]
+    BondTokens {
+        issuer: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(BondTokensResponse)]
+    BondTokensByCurrency {
+        currency: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(BondTokensResponse)]
+    BondTokensByCodeId {
+        code_id: Uint64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
@@ -1,5 +1,6 @@
-use cosmwasm_std::{Addr, Uint64};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Timestamp, Uint64};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use placeholder::helpers::AllocationFeePolicy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -12,5 +13,88 @@ pub struct FactoryPlatform {
     pub router: Option<Addr>,
 }
 
+// A contract which has been instantiated by the factory and is waiting for its reply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingContract {
+    pub code_id: u64,
+    pub issuer: Option<Addr>,
+    pub currency: Option<Addr>,
+    pub distribution_deadline: Option<Timestamp>, // bond tokens only
+    pub allocation_fee_policy: Option<AllocationFeePolicy>, // bond tokens only
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CurrencyRecord {
+    pub address: Addr,
+    pub code_id: u64,
+    pub created_at: u64, // block height
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BondTokenRecord {
+    pub address: Addr,
+    pub code_id: u64,
+    pub issuer: Addr,
+    pub currency: Addr,
+    pub created_at: u64, // block height
+}
+
+pub struct CurrencyIndexes<'a> {
+    pub code_id: MultiIndex<'a, u64, CurrencyRecord, Addr>,
+}
+
+impl<'a> IndexList<CurrencyRecord> for CurrencyIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<CurrencyRecord>> + '_> {
+        let v: Vec<&dyn Index<CurrencyRecord>> = vec![&self.code_id];
+        Box::new(v.into_iter())
+    }
+}
+
+pub struct BondTokenIndexes<'a> {
+    pub issuer: MultiIndex<'a, Addr, BondTokenRecord, Addr>,
+    pub currency: MultiIndex<'a, Addr, BondTokenRecord, Addr>,
+    pub code_id: MultiIndex<'a, u64, BondTokenRecord, Addr>,
+}
+
+impl<'a> IndexList<BondTokenRecord> for BondTokenIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<BondTokenRecord>> + '_> {
+        let v: Vec<&dyn Index<BondTokenRecord>> = vec![&self.issuer, &self.currency, &self.code_id];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn currencies<'a>() -> IndexedMap<'a, Addr, CurrencyRecord, CurrencyIndexes<'a>> {
+    let indexes = CurrencyIndexes {
+        code_id: MultiIndex::new(
+            |_pk, record: &CurrencyRecord| record.code_id,
+            "currencies",
+            "currencies__code_id",
+        ),
+    };
+    IndexedMap::new("currencies", indexes)
+}
+
+pub fn bond_tokens<'a>() -> IndexedMap<'a, Addr, BondTokenRecord, BondTokenIndexes<'a>> {
+    let indexes = BondTokenIndexes {
+        issuer: MultiIndex::new(
+            |_pk, record: &BondTokenRecord| record.issuer.clone(),
+            "bond_tokens",
+            "bond_tokens__issuer",
+        ),
+        currency: MultiIndex::new(
+            |_pk, record: &BondTokenRecord| record.currency.clone(),
+            "bond_tokens",
+            "bond_tokens__currency",
+        ),
+        code_id: MultiIndex::new(
+            |_pk, record: &BondTokenRecord| record.code_id,
+            "bond_tokens",
+            "bond_tokens__code_id",
+        ),
+    };
+    IndexedMap::new("bond_tokens", indexes)
+}
+
 pub const FACTORY_PLATFORM: Item<FactoryPlatform> = Item::new("factory_platform");
 pub const OPERATORS: Map<Addr, bool> = Map::new("operators");
+pub const PENDING_CONTRACT: Item<PendingContract> = Item::new("pending_contract");
@@ -0,0 +1,216 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+};
+use cw2::set_contract_version;
+
+use crate::{
+    error::IdentityRegistryErr,
+    helpers::{
+        ClaimResponse, ConfigResponse, IdentityClaim, IdentityRequirements, OperatorsResponse,
+        VerificationResponse,
+    },
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{IdentityRegistry, CLAIMS, IDENTITY_REGISTRY, OPERATORS},
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:identity-registry";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, IdentityRegistryErr> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    IDENTITY_REGISTRY.save(
+        deps.storage,
+        &IdentityRegistry {
+            admin: info.sender.clone(),
+        },
+    )?;
+    for operator in msg.operators.iter() {
+        OPERATORS.save(
+            deps.storage,
+            deps.api.addr_validate(operator.as_str())?,
+            &true,
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("admin", info.sender))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, IdentityRegistryErr> {
+    match msg {
+        ExecuteMsg::SetOperators {
+            operators,
+            is_operators,
+        } => execute::set_operators(deps, info, operators, is_operators),
+        ExecuteMsg::SetClaim {
+            investor,
+            kyc_level,
+            jurisdiction,
+            accredited,
+            expiry,
+        } => {
+            let claim = IdentityClaim {
+                kyc_level,
+                jurisdiction,
+                accredited,
+                expiry,
+                operator: info.sender.clone(),
+            };
+            execute::set_claim(deps, env, info, investor, claim)
+        }
+        ExecuteMsg::RevokeClaim { investor } => execute::revoke_claim(deps, info, investor),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query::get_config(deps)?),
+        QueryMsg::Operators {} => to_binary(&query::get_operators(deps)?),
+        QueryMsg::Claim { investor } => to_binary(&query::get_claim(deps, investor)?),
+        QueryMsg::Verify {
+            investor,
+            requirements,
+        } => to_binary(&query::verify(deps, env, investor, requirements)?),
+    }
+}
+
+pub mod execute {
+    use super::*;
+
+    pub fn set_operators(
+        deps: DepsMut,
+        info: MessageInfo,
+        operators: Vec<String>,
+        is_operators: Vec<bool>,
+    ) -> Result<Response, IdentityRegistryErr> {
+        if info.sender != IDENTITY_REGISTRY.load(deps.storage)?.admin {
+            return Err(IdentityRegistryErr::NotAdmin {
+                account: info.sender.into(),
+            });
+        }
+        if operators.len() != is_operators.len() {
+            return Err(IdentityRegistryErr::LengthMismatch {});
+        }
+        for (i, new_operator) in operators.iter().enumerate() {
+            OPERATORS.save(
+                deps.storage,
+                deps.api.addr_validate(new_operator.as_str())?,
+                is_operators.get(i).unwrap(),
+            )?;
+        }
+        Ok(Response::new().add_attribute("action", "set_operators"))
+    }
+
+    pub fn set_claim(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        investor: String,
+        claim: IdentityClaim,
+    ) -> Result<Response, IdentityRegistryErr> {
+        check_operator(deps.as_ref(), &info)?;
+        if claim.expiry <= env.block.time {
+            return Err(IdentityRegistryErr::InvalidExpiry {
+                expiry: claim.expiry,
+            });
+        }
+
+        let investor_addr = deps.api.addr_validate(investor.as_str())?;
+        CLAIMS.save(deps.storage, &investor_addr, &claim)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_claim")
+            .add_attribute("investor", investor))
+    }
+
+    pub fn revoke_claim(
+        deps: DepsMut,
+        info: MessageInfo,
+        investor: String,
+    ) -> Result<Response, IdentityRegistryErr> {
+        check_operator(deps.as_ref(), &info)?;
+
+        let investor_addr = deps.api.addr_validate(investor.as_str())?;
+        if !CLAIMS.has(deps.storage, &investor_addr) {
+            return Err(IdentityRegistryErr::ClaimNotFound { investor });
+        }
+        CLAIMS.remove(deps.storage, &investor_addr);
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_claim")
+            .add_attribute("investor", investor))
+    }
+
+    // Only trusted operators can maintain claims
+    fn check_operator(deps: Deps, info: &MessageInfo) -> Result<(), IdentityRegistryErr> {
+        if !OPERATORS
+            .may_load(deps.storage, info.sender.clone())?
+            .unwrap_or_default()
+        {
+            return Err(IdentityRegistryErr::NotOperator {
+                account: info.sender.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+pub mod query {
+    use super::*;
+
+    pub fn get_config(deps: Deps) -> StdResult<ConfigResponse> {
+        let admin = IDENTITY_REGISTRY.load(deps.storage)?.admin.to_string();
+        Ok(ConfigResponse { admin })
+    }
+
+    pub fn get_operators(deps: Deps) -> StdResult<OperatorsResponse> {
+        let operators = OPERATORS
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter_map(|item| match item {
+                Ok((operator, true)) => Some(Ok(operator.to_string())),
+                Ok((_, false)) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(OperatorsResponse { operators })
+    }
+
+    pub fn get_claim(deps: Deps, investor: String) -> StdResult<ClaimResponse> {
+        let claim = CLAIMS.may_load(deps.storage, &deps.api.addr_validate(investor.as_str())?)?;
+        Ok(ClaimResponse { investor, claim })
+    }
+
+    pub fn verify(
+        deps: Deps,
+        env: Env,
+        investor: String,
+        requirements: IdentityRequirements,
+    ) -> StdResult<VerificationResponse> {
+        let reason =
+            match CLAIMS.may_load(deps.storage, &deps.api.addr_validate(investor.as_str())?)? {
+                Some(claim) => claim.rejection_reason(&requirements, env.block.time),
+                None => Some(String::from("no claim")),
+            };
+        Ok(VerificationResponse {
+            verified: reason.is_none(),
+            reason,
+        })
+    }
+}
@@ -0,0 +1,23 @@
+use cosmwasm_std::{StdError, Timestamp};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IdentityRegistryErr {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("IdentityRegistryErr: {account:?} is not admin")]
+    NotAdmin { account: String },
+
+    #[error("IdentityRegistryErr: caller is not operator ({account:?})")]
+    NotOperator { account: String },
+
+    #[error("IdentityRegistryErr: lengths mismatch")]
+    LengthMismatch {},
+
+    #[error("IdentityRegistryErr: claim expiry must be in the future ({expiry:?})")]
+    InvalidExpiry { expiry: Timestamp },
+
+    #[error("IdentityRegistryErr: claim not found ({investor:?})")]
+    ClaimNotFound { investor: String },
+}
@@ -0,0 +1,70 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Timestamp};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Identity of an investor as screened by an operator
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct IdentityClaim {
+    pub kyc_level: u8,
+    pub jurisdiction: String, // ISO 3166-1 alpha-2 country code, e.g. "SG"
+    pub accredited: bool,
+    pub expiry: Timestamp,
+    pub operator: Addr, // who issued this claim
+}
+
+impl IdentityClaim {
+    // Returns why this claim does not meet the requirements at `now`, if it does not
+    pub fn rejection_reason(
+        &self,
+        requirements: &IdentityRequirements,
+        now: Timestamp,
+    ) -> Option<String> {
+        if now >= self.expiry {
+            return Some(String::from("claim expired"));
+        }
+        if self.kyc_level < requirements.min_kyc_level {
+            return Some(String::from("kyc level too low"));
+        }
+        if requirements.accredited_only && !self.accredited {
+            return Some(String::from("not accredited"));
+        }
+        if !requirements.jurisdictions.is_empty()
+            && !requirements.jurisdictions.contains(&self.jurisdiction)
+        {
+            return Some(String::from("jurisdiction not allowed"));
+        }
+        None
+    }
+}
+
+// What a bond asks of its investors' claims
+#[cw_serde]
+#[derive(Eq)]
+pub struct IdentityRequirements {
+    pub min_kyc_level: u8,
+    pub accredited_only: bool,
+    pub jurisdictions: Vec<String>, // allowed jurisdictions, empty means any
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub admin: String,
+}
+
+#[cw_serde]
+pub struct OperatorsResponse {
+    pub operators: Vec<String>,
+}
+
+#[cw_serde]
+pub struct ClaimResponse {
+    pub investor: String,
+    pub claim: Option<IdentityClaim>,
+}
+
+#[cw_serde]
+pub struct VerificationResponse {
+    pub verified: bool,
+    pub reason: Option<String>, // why the investor is rejected
+}
@@ -0,0 +1,50 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Timestamp;
+
+use crate::helpers::{
+    ClaimResponse, ConfigResponse, IdentityRequirements, OperatorsResponse, VerificationResponse,
+};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub operators: Vec<String>,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    SetOperators {
+        operators: Vec<String>,
+        is_operators: Vec<bool>,
+    },
+    /// Creates or replaces the claim of an investor
+    SetClaim {
+        investor: String,
+        kyc_level: u8,
+        jurisdiction: String,
+        accredited: bool,
+        expiry: Timestamp,
+    },
+    RevokeClaim {
+        investor: String,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(ConfigResponse)]
+    Config {},
+
+    #[returns(OperatorsResponse)]
+    Operators {},
+
+    #[returns(ClaimResponse)]
+    Claim { investor: String },
+
+    /// Checks the investor's claim against a bond's requirements at the current block time
+    #[returns(VerificationResponse)]
+    Verify {
+        investor: String,
+        requirements: IdentityRequirements,
+    },
+}
@@ -0,0 +1,15 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::IdentityClaim;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct IdentityRegistry {
+    pub admin: Addr,
+}
+
+pub const IDENTITY_REGISTRY: Item<IdentityRegistry> = Item::new("identity_registry");
+pub const OPERATORS: Map<Addr, bool> = Map::new("operators");
+pub const CLAIMS: Map<&Addr, IdentityClaim> = Map::new("claims"); // maps from an investor to its identity claim
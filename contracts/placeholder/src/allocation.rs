@@ -0,0 +1,139 @@
+use cosmwasm_std::Uint128;
+
+use crate::{
+    error::PlaceholderErr,
+    helpers::{AllocationCursor, AllocationPolicy, Subscription},
+};
+
+/// Shares the policy's total, at most `hard_cap`, among a bond's subscriptions one at a time
+/// in arrival order, so they can be allocated a page at a time. What the earlier subscriptions
+/// took is carried by an `AllocationCursor`
+pub struct Allocator<'a> {
+    policy: &'a AllocationPolicy,
+    total: Option<Uint128>, // None when every subscription is allocated in full
+    subscribed: Uint128,
+    anchors: Vec<(String, Uint128)>, // anchors' allocations, once per anchor
+    anchored: Uint128,               // subscribed by the anchors
+    to_anchors: Uint128,             // allocated to the anchors
+}
+
+impl<'a> Allocator<'a> {
+    /// `anchor_demands` are the anchors' subscriptions, in the policy's order
+    pub fn new(
+        policy: &'a AllocationPolicy,
+        hard_cap: Option<Uint128>,
+        subscribed: Uint128,
+        anchor_demands: Vec<(String, Uint128)>,
+    ) -> Self {
+        // Without a total or a hard cap every subscription is allocated in full
+        let total = [policy.total(), hard_cap]
+            .into_iter()
+            .flatten()
+            .min()
+            .filter(|total| subscribed > *total);
+
+        let mut anchors: Vec<(String, Uint128)> = vec![];
+        let mut anchored = Uint128::zero();
+        let mut remaining = total.unwrap_or(subscribed);
+        for (anchor, demand) in anchor_demands {
+            if anchors.iter().any(|(investor, _)| *investor == anchor) {
+                continue;
+            }
+            let allocation = demand.min(remaining);
+            remaining -= allocation;
+            anchored += demand;
+            anchors.push((anchor, allocation));
+        }
+
+        Allocator {
+            policy,
+            total,
+            subscribed,
+            anchors,
+            anchored,
+            to_anchors: total.unwrap_or(subscribed) - remaining,
+        }
+    }
+
+    // Minimum tickets must all be known before the first one is allocated
+    pub fn counts_tickets(&self) -> bool {
+        self.total.is_some() && matches!(self.policy, AllocationPolicy::MinimumTicket { .. })
+    }
+
+    pub fn count(&self, cursor: &mut AllocationCursor, subscription: &Subscription) {
+        if let AllocationPolicy::MinimumTicket { minimum_ticket, .. } = self.policy {
+            cursor.guaranteed += subscription.currency_amount.min(*minimum_ticket);
+        }
+    }
+
+    // Allocation starts over from the first subscription once every ticket was counted
+    pub fn finish_count(&self, cursor: &mut AllocationCursor) -> Result<(), PlaceholderErr> {
+        let total = self.total.unwrap_or(self.subscribed);
+        if cursor.guaranteed > total {
+            return Err(PlaceholderErr::AllocationInfeasible {
+                guaranteed: cursor.guaranteed,
+                total,
+            });
+        }
+        cursor.counting = false;
+        cursor.start_after = 0;
+        Ok(())
+    }
+
+    /// Allocates the next subscription in arrival order, never more than subscribed
+    pub fn allocate(&self, cursor: &mut AllocationCursor, subscription: &Subscription) -> Uint128 {
+        let demand = subscription.currency_amount;
+        let Some(total) = self.total else {
+            cursor.allocated += demand;
+            return demand;
+        };
+
+        let allocation = match self.policy {
+            AllocationPolicy::ProRata { .. } => pro_rata(cursor, demand, total, self.subscribed),
+            AllocationPolicy::FirstComeFirstServed { .. } => demand.min(total - cursor.allocated),
+            AllocationPolicy::MinimumTicket { minimum_ticket, .. } => {
+                let guaranteed = demand.min(*minimum_ticket);
+                guaranteed
+                    + pro_rata(
+                        cursor,
+                        demand - guaranteed,
+                        total - cursor.guaranteed,
+                        self.subscribed - cursor.guaranteed,
+                    )
+            }
+            AllocationPolicy::AnchorsFirst { .. } => {
+                match self
+                    .anchors
+                    .iter()
+                    .find(|(anchor, _)| subscription.investor == *anchor)
+                {
+                    Some((_, allocation)) => *allocation,
+                    None => pro_rata(
+                        cursor,
+                        demand,
+                        total - self.to_anchors,
+                        self.subscribed - self.anchored,
+                    ),
+                }
+            }
+        };
+        cursor.allocated += allocation;
+        allocation
+    }
+}
+
+// Share of `amount` for `demand` out of `total_demand`, rounded down on the running total
+// of demands so the whole amount is allocated by the last subscription
+fn pro_rata(
+    cursor: &mut AllocationCursor,
+    demand: Uint128,
+    amount: Uint128,
+    total_demand: Uint128,
+) -> Uint128 {
+    if total_demand <= amount {
+        return demand;
+    }
+    let before = cursor.weight.multiply_ratio(amount, total_demand);
+    cursor.weight += demand;
+    cursor.weight.multiply_ratio(amount, total_demand) - before
+}
@@ -1,20 +1,33 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, SubMsg,
-    Uint128, WasmMsg,
+    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+    Storage, SubMsg, Timestamp, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw20::Cw20ExecuteMsg::Transfer;
+use cw20::{BalanceResponse, Cw20ExecuteMsg::Transfer, Cw20QueryMsg};
+use cw_storage_plus::{Bound, PrefixBound};
 
 use crate::{
+    allocation::Allocator,
     error::PlaceholderErr,
     helpers::{
-        BondValidationResponse, InvesmentRule, Subscription, SubscriptionResponse,
-        SubscriptionsResponse,
+        AccruedFeesResponse, AllocationCursor, AllocationFeePolicy, AllocationPolicy,
+        AllocationResponse, BondCurrencyResponse, BondPhase, BondScheduleResponse,
+        BondSubscriptionLimitsResponse, BondTokenAdditionalQueryMsg, BondTokenQueryMsg,
+        BondValidationResponse, EscrowLedgerResponse, EscrowResponse, EscrowState, FeeRecipient,
+        FeeShare, FeeSplitResponse, FeeWithdrawalResponse, FeeWithdrawalsResponse, InvesmentRule,
+        InvestorSubscriptionResponse, InvestorSubscriptionsResponse, ReconcileResponse,
+        Subscription, SubscriptionResponse, SubscriptionTotalResponse, SubscriptionsResponse,
+        SystemFeeResponse, SystemFeesResponse, DEFAULT_LIMIT, MAX_FEE_WEIGHT, MAX_LIMIT,
+    },
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
+    state::{
+        subscriptions, BondEscrow, EscrowLedger, FeeShareEntry, FeeWithdrawal, PlaceholderPlatform,
+        ACCRUED_FEES, ALLOCATION_CLAIMS, BOND_ESCROWS, BOND_FEE_SPLITS, ESCROW_LEDGERS, FEE_SPLIT,
+        FEE_WITHDRAWALS, FEE_WITHDRAWAL_COUNT, LEGACY_SUBSCRIPTIONS, OPERATORS,
+        PLACEHOLDER_PLATFORM, SUBSCRIPTION_SEQUENCE, SUBSCRIPTION_TOTALS, SYSTEM_FEE,
     },
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
-    state::{PlaceholderPlatform, OPERATORS, PLACEHOLDER_PLATFORM, SUBSCRIPTIONS, SYSTEM_FEE},
 };
 
 // version info for migration info
@@ -49,7 +62,7 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, PlaceholderErr> {
@@ -62,46 +75,232 @@ pub fn execute(
         // ExecuteMsg::RegisterCurrencies { currencies, states } => {
         //     execute::register_currency(deps, info, currencies, states)
         // }
-        ExecuteMsg::RegisterBondToken { bond_token } => {
-            execute::register_bond_token(deps, info, bond_token)
-        }
+        ExecuteMsg::RegisterBondToken {
+            bond_token,
+            currency,
+            distribution_deadline,
+            allocation_fee_policy,
+        } => execute::register_bond_token(
+            deps,
+            info,
+            bond_token,
+            currency,
+            distribution_deadline,
+            allocation_fee_policy,
+        ),
         ExecuteMsg::RegisterSubscription {
             investor,
-            currency,
             subscription_amount,
             fee_amount,
-        } => execute::register_subcription(
+        } => execute::register_subcription(deps, info, investor, subscription_amount, fee_amount),
+        ExecuteMsg::RefundSubscription {
+            investor,
+            currency_amount,
+            refund_fee,
+        } => execute::refund_subscription(deps, info, investor, currency_amount, refund_fee),
+        ExecuteMsg::RefundSubscriptions {
+            bond_token,
+            refund_fees,
+            limit,
+        } => execute::refund_subscriptions(deps, info, bond_token, refund_fees, limit),
+        ExecuteMsg::ClaimRefund { bond_token } => {
+            execute::claim_refund(deps, env, info, bond_token)
+        }
+        ExecuteMsg::SetFeeSplit { bond_token, shares } => {
+            execute::set_fee_split(deps, info, bond_token, shares)
+        }
+        ExecuteMsg::ClaimFees {} => execute::claim_fees(deps, info),
+        ExecuteMsg::OpenAllocationClaims {
+            bond_token,
+            investors,
+        } => execute::open_allocation_claims(deps, info, bond_token, investors),
+        ExecuteMsg::ReleaseAllocation {
+            bond_token,
+            investor,
+            currency_amount,
+            refund_fee,
+        } => execute::release_allocation(
             deps,
             info,
+            bond_token,
             investor,
-            currency,
-            subscription_amount,
-            fee_amount,
+            currency_amount,
+            refund_fee,
         ),
-        ExecuteMsg::ReleaseCurrency {
-            issuer,
-            bond_token,
-            currency,
-            investment_rules,
-        } => execute::release_currency(deps, info, issuer, bond_token, currency, investment_rules),
-        ExecuteMsg::WithdrawSystemFee { recipient } => {
-            execute::withdraw_system_fee(deps, info, recipient)
+        ExecuteMsg::CollectProceeds { bond_token, issuer } => {
+            execute::collect_proceeds(deps, info, bond_token, issuer, false)
         }
+        ExecuteMsg::CloseAllocationClaims { bond_token, issuer } => {
+            execute::collect_proceeds(deps, info, bond_token, issuer, true)
+        }
+        ExecuteMsg::WithdrawSystemFee {
+            currency,
+            recipients,
+        } => execute::withdraw_system_fee(deps, env, info, currency, recipients),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::SubscriptionsOf { bond_token } => {
-            to_binary(&query::get_subscriptions(deps, bond_token)?)
+        QueryMsg::SubscriptionsOf {
+            bond_token,
+            start_after,
+            limit,
+        } => to_binary(&query::get_subscriptions(
+            deps,
+            bond_token,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::SubscriptionOf {
+            bond_token,
+            investor,
+        } => to_binary(&query::get_subscription(deps, bond_token, investor)?),
+        QueryMsg::SubscriptionsByInvestor {
+            investor,
+            start_after,
+            limit,
+        } => to_binary(&query::get_subscriptions_by_investor(
+            deps,
+            investor,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::SubscriptionTotal { bond_token } => {
+            to_binary(&query::get_subscription_total(deps, bond_token)?)
+        }
+        QueryMsg::Escrow { bond_token } => to_binary(&query::get_escrow(deps, bond_token)?),
+        QueryMsg::EscrowLedger { bond_token } => {
+            to_binary(&query::get_escrow_ledger(deps, bond_token)?)
+        }
+        QueryMsg::Reconcile { currency } => to_binary(&query::reconcile(deps, env, currency)?),
+        QueryMsg::SystemFees {} => to_binary(&query::get_system_fees(deps)?),
+        QueryMsg::FeeSplit { bond_token } => to_binary(&query::get_fee_split(deps, bond_token)?),
+        QueryMsg::AccruedFees { recipient } => {
+            to_binary(&query::get_accrued_fees(deps, recipient)?)
         }
+        QueryMsg::FeeWithdrawals { start_after, limit } => {
+            to_binary(&query::get_fee_withdrawals(deps, start_after, limit)?)
+        }
+        QueryMsg::PreviewAllocation {
+            bond_token,
+            policy,
+            cursor,
+            limit,
+        } => to_binary(&query::preview_allocation(
+            deps, bond_token, policy, cursor, limit,
+        )?),
         QueryMsg::ValidateBondToken { bond_token } => {
             to_binary(&query::validate_bond_token(deps, bond_token)?)
         }
     }
 }
 
+// Moves subscriptions stored as one vector per bond token into the indexed map, each vector
+// was kept in arrival order so sequences follow it
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, PlaceholderErr> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let legacy_subscriptions = LEGACY_SUBSCRIPTIONS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut sequence = SUBSCRIPTION_SEQUENCE
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let mut migrated = 0_u32;
+    for (bond_token, bond_subscriptions) in legacy_subscriptions {
+        let mut total = Uint128::zero();
+        for mut subscription in bond_subscriptions {
+            sequence += 1;
+            subscription.sequence = sequence;
+            total += subscription.currency_amount;
+            subscriptions().save(
+                deps.storage,
+                (bond_token.clone(), subscription.investor.clone()),
+                &subscription,
+            )?;
+            migrated += 1;
+        }
+        SUBSCRIPTION_TOTALS.save(deps.storage, bond_token.clone(), &total)?;
+        LEGACY_SUBSCRIPTIONS.remove(deps.storage, bond_token);
+    }
+    SUBSCRIPTION_SEQUENCE.save(deps.storage, &sequence)?;
+
+    // Bonds registered before escrows were tracked get one built from their subscriptions
+    let mut backfilled = 0_u32;
+    for bond_token in PLACEHOLDER_PLATFORM.load(deps.storage)?.bond_tokens {
+        if !BOND_ESCROWS.has(deps.storage, bond_token.clone()) {
+            backfill_escrow(deps.branch(), &bond_token)?;
+            backfilled += 1;
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("migrated_subscriptions", migrated.to_string())
+        .add_attribute("backfilled_escrows", backfilled.to_string()))
+}
+
+// Fees of a bond still subscribing were added to the system fee when collected, they are pending
+// again until settled. A bond past subscription was already released to its issuer
+fn backfill_escrow(deps: DepsMut, bond_token: &Addr) -> StdResult<()> {
+    let currency_response: BondCurrencyResponse = deps.querier.query_wasm_smart(
+        bond_token.to_string(),
+        &BondTokenQueryMsg::AdditionalQueryMsg(BondTokenAdditionalQueryMsg::GetCurrency {}),
+    )?;
+    let schedule_response: BondScheduleResponse = deps.querier.query_wasm_smart(
+        bond_token.to_string(),
+        &BondTokenQueryMsg::AdditionalQueryMsg(BondTokenAdditionalQueryMsg::BondSchedule {}),
+    )?;
+    let currency = deps.api.addr_validate(&currency_response.currency)?;
+
+    let mut ledger = EscrowLedger::default();
+    for item in
+        subscriptions()
+            .prefix(bond_token.clone())
+            .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, subscription) = item?;
+        ledger.subscribed += subscription.currency_amount;
+        ledger.fees += subscription.fee_amount;
+    }
+    let state = if schedule_response.current_phase == BondPhase::Subscription {
+        SYSTEM_FEE.update(deps.storage, currency.clone(), |fee| -> StdResult<_> {
+            Ok(fee.unwrap_or_default().saturating_sub(ledger.fees))
+        })?;
+        EscrowState::Open
+    } else {
+        ledger.released = ledger.subscribed;
+        ledger.fees_settled = ledger.fees;
+        EscrowState::Released
+    };
+    ESCROW_LEDGERS.save(
+        deps.storage,
+        (currency.clone(), bond_token.clone()),
+        &ledger,
+    )?;
+    BOND_ESCROWS.save(
+        deps.storage,
+        bond_token.clone(),
+        &BondEscrow {
+            currency,
+            distribution_deadline: schedule_response.distribution_deadline,
+            state,
+            allocation_fee_policy: AllocationFeePolicy::default(),
+            uncollected_proceeds: Uint128::zero(),
+        },
+    )
+}
+
+// The bond's own fee split if it has one, otherwise the global split
+fn fee_split(storage: &dyn Storage, bond_token: &Addr) -> StdResult<Vec<FeeShareEntry>> {
+    match BOND_FEE_SPLITS.may_load(storage, bond_token.clone())? {
+        Some(shares) => Ok(shares),
+        None => Ok(FEE_SPLIT.may_load(storage)?.unwrap_or_default()),
+    }
+}
+
 pub mod execute {
     use super::*;
 
@@ -184,20 +383,43 @@ pub mod execute {
         deps: DepsMut,
         info: MessageInfo,
         bond_token: String,
+        currency: String,
+        distribution_deadline: Timestamp,
+        allocation_fee_policy: AllocationFeePolicy,
     ) -> Result<Response, PlaceholderErr> {
-        // Only System Addresses can register bond tokens
-        if !OPERATORS.load(deps.storage, info.sender.clone())? {
+        // Only System Addresses or factory can register bond tokens
+        let platform = PLACEHOLDER_PLATFORM.load(deps.storage)?;
+        if platform.factory != Some(info.sender.clone())
+            && !OPERATORS
+                .may_load(deps.storage, info.sender.clone())?
+                .unwrap_or_default()
+        {
             return Err(PlaceholderErr::NotOperator {
                 account: info.sender.to_string(),
             });
         }
 
-        PLACEHOLDER_PLATFORM.update(deps.storage, |mut platform| -> Result<_, PlaceholderErr> {
-            platform
-                .bond_tokens
-                .push(deps.api.addr_validate(bond_token.as_str())?);
-            Ok(platform)
-        })?;
+        let bond_token_addr = deps.api.addr_validate(bond_token.as_str())?;
+        if !platform.bond_tokens.contains(&bond_token_addr) {
+            BOND_ESCROWS.save(
+                deps.storage,
+                bond_token_addr.clone(),
+                &BondEscrow {
+                    currency: deps.api.addr_validate(currency.as_str())?,
+                    distribution_deadline,
+                    state: EscrowState::Open,
+                    allocation_fee_policy,
+                    uncollected_proceeds: Uint128::zero(),
+                },
+            )?;
+            PLACEHOLDER_PLATFORM.update(
+                deps.storage,
+                |mut platform| -> Result<_, PlaceholderErr> {
+                    platform.bond_tokens.push(bond_token_addr);
+                    Ok(platform)
+                },
+            )?;
+        }
 
         Ok(Response::new().add_attribute("action", "register_bond_token"))
     }
@@ -206,7 +428,6 @@ pub mod execute {
         deps: DepsMut,
         info: MessageInfo,
         investor: String,
-        currency: String,
         subscription_amount: Uint128,
         fee_amount: Uint128,
     ) -> Result<Response, PlaceholderErr> {
@@ -222,65 +443,527 @@ pub mod execute {
             });
         }
 
-        // Update fee
-        let currency_address = deps.api.addr_validate(currency.as_str())?;
-        SYSTEM_FEE.update(deps.storage, currency_address, |fee| -> StdResult<_> {
-            Ok(fee.unwrap_or_default() + fee_amount)
+        // Subscriptions are taken in the bond's own currency while its escrow is open
+        let currency_address = load_open_escrow(deps.storage, &info.sender)?.currency;
+
+        // Register investor's subscription, a new one is queued after every existing one
+        let investor_address = deps.api.addr_validate(&investor)?;
+        let key = (info.sender.clone(), investor_address.clone());
+        let mut subscription = match subscriptions().may_load(deps.storage, key.clone())? {
+            Some(subscription) => subscription,
+            None => {
+                let sequence = SUBSCRIPTION_SEQUENCE
+                    .may_load(deps.storage)?
+                    .unwrap_or_default()
+                    + 1;
+                SUBSCRIPTION_SEQUENCE.save(deps.storage, &sequence)?;
+                Subscription {
+                    investor: investor_address,
+                    currency_amount: Uint128::zero(),
+                    fee_amount: Uint128::zero(),
+                    refunded: false,
+                    sequence,
+                }
+            }
+        };
+        subscription.currency_amount += subscription_amount;
+        subscription.fee_amount += fee_amount;
+        subscriptions().save(deps.storage, key, &subscription)?;
+        add_to_total(deps.storage, &info.sender, subscription_amount)?;
+        update_ledger(deps.storage, &currency_address, &info.sender, |ledger| {
+            ledger.subscribed += subscription_amount;
+            ledger.fees += fee_amount;
+        })?;
+
+        Ok(Response::new().add_attribute("action", "register_subcription"))
+    }
+
+    pub fn refund_subscription(
+        deps: DepsMut,
+        info: MessageInfo,
+        investor: String,
+        currency_amount: Uint128,
+        refund_fee: bool,
+    ) -> Result<Response, PlaceholderErr> {
+        // Only bond token can refund its investors' subscriptions
+        if !PLACEHOLDER_PLATFORM
+            .load(deps.storage)?
+            .bond_tokens
+            .into_iter()
+            .any(|bond_token| bond_token == info.sender)
+        {
+            return Err(PlaceholderErr::NotBondToken {
+                account: info.sender.to_string(),
+            });
+        }
+
+        // Reduce investor's subscription and its fee proportionally, dropping it once nothing is left
+        let currency_address = load_open_escrow(deps.storage, &info.sender)?.currency;
+        let investor_address = deps.api.addr_validate(investor.as_str())?;
+        let key = (info.sender.clone(), investor_address);
+        let mut subscription = subscriptions()
+            .may_load(deps.storage, key.clone())?
+            .filter(|subscription| subscription.currency_amount >= currency_amount)
+            .ok_or(PlaceholderErr::InsufficientSubscription {
+                investor: investor.clone(),
+                currency_amount,
+            })?;
+        let fee_amount = subscription
+            .fee_amount
+            .multiply_ratio(currency_amount, subscription.currency_amount);
+        subscription.currency_amount -= currency_amount;
+        subscription.fee_amount -= fee_amount;
+        if subscription.currency_amount.is_zero() && !subscription.refunded {
+            subscriptions().remove(deps.storage, key)?;
+        } else {
+            subscriptions().save(deps.storage, key, &subscription)?;
+        }
+        subtract_from_total(deps.storage, &info.sender, currency_amount)?;
+
+        let refunded_fee = if refund_fee {
+            fee_amount
+        } else {
+            Uint128::zero()
+        };
+        update_ledger(deps.storage, &currency_address, &info.sender, |ledger| {
+            ledger.refunded += currency_amount;
+            ledger.fees_refunded += refunded_fee;
+        })?;
+        settle_fee(
+            deps.storage,
+            &info.sender,
+            &currency_address,
+            fee_amount - refunded_fee,
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "refund_subscription")
+            .add_attribute("investor", investor.clone())
+            .add_attribute("currency_amount", currency_amount)
+            .add_attribute("fee_amount", refunded_fee)
+            .add_submessage(SubMsg::new(WasmMsg::Execute {
+                contract_addr: currency_address.to_string(),
+                msg: to_binary(&Transfer {
+                    recipient: investor,
+                    amount: currency_amount + refunded_fee,
+                })?,
+                funds: vec![],
+            })))
+    }
+
+    // Refunds subscriptions of a cancelled bond page by page, refunded ones are dropped
+    // so each call picks up the next page
+    pub fn refund_subscriptions(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        refund_fees: bool,
+        limit: Option<u32>,
+    ) -> Result<Response, PlaceholderErr> {
+        // Only router can call this function to refund subscriptions
+        if PLACEHOLDER_PLATFORM
+            .load(deps.storage)?
+            .router
+            .ok_or(PlaceholderErr::ContractNotSetup {})?
+            != info.sender
+        {
+            return Err(PlaceholderErr::NotRouter {
+                account: info.sender.to_string(),
+            });
+        }
+
+        let bond_token_address = deps.api.addr_validate(bond_token.as_str())?;
+        let mut escrow = load_open_escrow(deps.storage, &bond_token_address)?;
+        let currency_address = escrow.currency.clone();
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let page = subscriptions()
+            .prefix(bond_token_address.clone())
+            .range(deps.storage, None, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut messages: Vec<SubMsg> = vec![];
+        for (investor, subscription) in page {
+            subscriptions().remove(deps.storage, (bond_token_address.clone(), investor))?;
+            // Already refunded after the distribution deadline
+            if subscription.refunded {
+                continue;
+            }
+            subtract_from_total(
+                deps.storage,
+                &bond_token_address,
+                subscription.currency_amount,
+            )?;
+            let refunded_fee = if refund_fees {
+                subscription.fee_amount
+            } else {
+                Uint128::zero()
+            };
+            update_ledger(
+                deps.storage,
+                &currency_address,
+                &bond_token_address,
+                |ledger| {
+                    ledger.refunded += subscription.currency_amount;
+                    ledger.fees_refunded += refunded_fee;
+                },
+            )?;
+            settle_fee(
+                deps.storage,
+                &bond_token_address,
+                &currency_address,
+                subscription.fee_amount - refunded_fee,
+            )?;
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: currency_address.to_string(),
+                msg: to_binary(&Transfer {
+                    recipient: subscription.investor.to_string(),
+                    amount: subscription.currency_amount + refunded_fee,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        let completed = subscriptions()
+            .prefix(bond_token_address.clone())
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_none();
+        if completed {
+            escrow.state = EscrowState::Refunded;
+            BOND_ESCROWS.save(deps.storage, bond_token_address, &escrow)?;
+        }
+        Ok(Response::new()
+            .add_attribute("action", "refund_subscriptions")
+            .add_attribute("refunded", messages.len().to_string())
+            .add_attribute("completed", completed.to_string())
+            .add_submessages(messages))
+    }
+
+    // Gives back the fee charged on an excess when the bond charges fees on allocations only
+    // or when the fee is refunded anyway
+    fn return_excess_fee(
+        storage: &mut dyn Storage,
+        bond_token: &Addr,
+        escrow: &BondEscrow,
+        subscription: &mut Subscription,
+        excess: Uint128,
+        refund_fee: bool,
+    ) -> StdResult<Uint128> {
+        let returned_fee = match (refund_fee, &escrow.allocation_fee_policy) {
+            (false, AllocationFeePolicy::Subscribed) => Uint128::zero(),
+            _ => subscription
+                .fee_amount
+                .multiply_ratio(excess, subscription.currency_amount),
+        };
+        if !returned_fee.is_zero() {
+            update_ledger(storage, &escrow.currency, bond_token, |ledger| {
+                ledger.fees_refunded += returned_fee
+            })?;
+            subscription.fee_amount -= returned_fee;
+            subscriptions().save(
+                storage,
+                (bond_token.clone(), subscription.investor.clone()),
+                subscription,
+            )?;
+        }
+        Ok(returned_fee)
+    }
+
+    // Loads a bond's escrow, failing unless its currency is still held
+    fn load_open_escrow(
+        storage: &dyn Storage,
+        bond_token: &Addr,
+    ) -> Result<BondEscrow, PlaceholderErr> {
+        let escrow = BOND_ESCROWS.may_load(storage, bond_token.clone())?.ok_or(
+            PlaceholderErr::BondTokenNotRegistered {
+                bond_token: bond_token.to_string(),
+            },
+        )?;
+        match escrow.state {
+            EscrowState::Open => Ok(escrow),
+            EscrowState::Released => Err(PlaceholderErr::CurrencyReleased {
+                bond_token: bond_token.to_string(),
+            }),
+            EscrowState::Refunded => Err(PlaceholderErr::EscrowRefunded {
+                bond_token: bond_token.to_string(),
+            }),
+            EscrowState::Claiming => Err(PlaceholderErr::AllocationsCommitted {
+                bond_token: bond_token.to_string(),
+            }),
+        }
+    }
+
+    // Pays a fee that can no longer be refunded out to the bond's recipients, the system fee
+    // keeps the rest
+    fn settle_fee(
+        storage: &mut dyn Storage,
+        bond_token: &Addr,
+        currency: &Addr,
+        fee_amount: Uint128,
+    ) -> StdResult<()> {
+        if fee_amount.is_zero() {
+            return Ok(());
+        }
+        let mut system_fee = fee_amount;
+        for share in fee_split(storage, bond_token)? {
+            let amount = fee_amount.multiply_ratio(share.weight, MAX_FEE_WEIGHT);
+            if amount.is_zero() {
+                continue;
+            }
+            system_fee -= amount;
+            ACCRUED_FEES.update(
+                storage,
+                (share.recipient, currency.clone()),
+                |accrued| -> StdResult<_> { Ok(accrued.unwrap_or_default() + amount) },
+            )?;
+        }
+        SYSTEM_FEE.update(storage, currency.clone(), |fee| -> StdResult<_> {
+            Ok(fee.unwrap_or_default() + system_fee)
+        })?;
+        update_ledger(storage, currency, bond_token, |ledger| {
+            ledger.fees_settled += fee_amount
+        })
+    }
+
+    fn update_ledger(
+        storage: &mut dyn Storage,
+        currency: &Addr,
+        bond_token: &Addr,
+        record: impl FnOnce(&mut EscrowLedger),
+    ) -> StdResult<()> {
+        let key = (currency.clone(), bond_token.clone());
+        let mut ledger = ESCROW_LEDGERS
+            .may_load(storage, key.clone())?
+            .unwrap_or_default();
+        record(&mut ledger);
+        ESCROW_LEDGERS.save(storage, key, &ledger)
+    }
+
+    fn add_to_total(
+        storage: &mut dyn Storage,
+        bond_token: &Addr,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        SUBSCRIPTION_TOTALS.update(storage, bond_token.clone(), |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default() + amount)
+        })?;
+        Ok(())
+    }
+
+    fn subtract_from_total(
+        storage: &mut dyn Storage,
+        bond_token: &Addr,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        SUBSCRIPTION_TOTALS.update(storage, bond_token.clone(), |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default().checked_sub(amount)?)
         })?;
+        Ok(())
+    }
+
+    // Investors' protection when the issuer never distributes, it does not depend on operators
+    pub fn claim_refund(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        bond_token: String,
+    ) -> Result<Response, PlaceholderErr> {
+        // Once claims are open only the subscriptions nobody released can still be refunded
+        let bond_token_address = deps.api.addr_validate(bond_token.as_str())?;
+        let escrow = match load_claiming_escrow(deps.storage, &bond_token_address) {
+            Ok(escrow) => escrow,
+            Err(_) => load_open_escrow(deps.storage, &bond_token_address)?,
+        };
+        if env.block.time < escrow.distribution_deadline {
+            return Err(PlaceholderErr::DistributionDeadlineNotPassed {
+                deadline: escrow.distribution_deadline,
+            });
+        }
 
-        // Register investor's subscription
-        SUBSCRIPTIONS.update(
+        // Mark investor's subscription as refunded so it can no longer be distributed
+        let key = (bond_token_address.clone(), info.sender.clone());
+        let mut subscription = subscriptions()
+            .may_load(deps.storage, key.clone())?
+            .filter(|subscription| !subscription.currency_amount.is_zero())
+            .ok_or(PlaceholderErr::NothingToRefund {
+                investor: info.sender.to_string(),
+            })?;
+        if ALLOCATION_CLAIMS.has(deps.storage, key.clone()) {
+            return Err(PlaceholderErr::AllocationClaimed {
+                investor: info.sender.to_string(),
+            });
+        }
+        let refund_amount = subscription.currency_amount + subscription.fee_amount;
+        update_ledger(
             deps.storage,
-            info.sender,
-            |subs| -> Result<_, PlaceholderErr> {
-                match subs {
-                    Some(mut subscriptions) => {
-                        // If this is the new investor
-                        if !subscriptions
-                            .clone()
-                            .into_iter()
-                            .any(|subscription| subscription.investor.to_string() == investor)
-                        {
-                            subscriptions.push(Subscription {
-                                investor: deps.api.addr_validate(&investor)?,
-                                currency_amount: subscription_amount,
-                            });
-                        } else {
-                            // This investor already invested before
-                            for subscription in subscriptions.iter_mut() {
-                                if subscription.investor.to_string() == investor {
-                                    subscription.currency_amount += subscription_amount;
-                                }
-                            }
-                        }
-                        Ok(subscriptions)
-                    }
-                    None => Ok(vec![Subscription {
-                        investor: deps.api.addr_validate(&investor)?,
-                        currency_amount: subscription_amount,
-                    }]),
-                }
+            &escrow.currency,
+            &bond_token_address,
+            |ledger| {
+                ledger.refunded += subscription.currency_amount;
+                ledger.fees_refunded += subscription.fee_amount;
             },
         )?;
+        subtract_from_total(
+            deps.storage,
+            &bond_token_address,
+            subscription.currency_amount,
+        )?;
+        subscription.currency_amount = Uint128::zero();
+        subscription.fee_amount = Uint128::zero();
+        subscription.refunded = true;
+        subscriptions().save(deps.storage, key, &subscription)?;
 
-        Ok(Response::new().add_attribute("action", "register_subcription"))
+        Ok(Response::new()
+            .add_attribute("action", "claim_refund")
+            .add_attribute("investor", info.sender.to_string())
+            .add_attribute("amount", refund_amount)
+            .add_submessage(SubMsg::new(WasmMsg::Execute {
+                contract_addr: escrow.currency.to_string(),
+                msg: to_binary(&Transfer {
+                    recipient: info.sender.to_string(),
+                    amount: refund_amount,
+                })?,
+                funds: vec![],
+            })))
     }
 
-    // Release currency from placeholder to issuer and return investors their excess
-    pub fn release_currency(
+    pub fn open_allocation_claims(
         deps: DepsMut,
         info: MessageInfo,
-        issuer: String,
         bond_token: String,
-        currency: String,
-        investment_rules: Vec<InvesmentRule>,
+        investors: Vec<String>,
+    ) -> Result<Response, PlaceholderErr> {
+        // Only router can call this function to open claims
+        if PLACEHOLDER_PLATFORM
+            .load(deps.storage)?
+            .router
+            .ok_or(PlaceholderErr::ContractNotSetup {})?
+            != info.sender
+        {
+            return Err(PlaceholderErr::NotRouter {
+                account: info.sender.to_string(),
+            });
+        }
+
+        // Rules must name subscribers, those nobody released can still claim refunds after the deadline
+        let bond_token_address = deps.api.addr_validate(bond_token.as_str())?;
+        let mut escrow = load_open_escrow(deps.storage, &bond_token_address)?;
+        for investor in investors {
+            let subscribed = subscriptions()
+                .may_load(
+                    deps.storage,
+                    (
+                        bond_token_address.clone(),
+                        deps.api.addr_validate(investor.as_str())?,
+                    ),
+                )?
+                .is_some_and(|subscription| !subscription.currency_amount.is_zero());
+            if !subscribed {
+                return Err(PlaceholderErr::NotSubscriber { investor });
+            }
+        }
+        escrow.state = EscrowState::Claiming;
+        BOND_ESCROWS.save(deps.storage, bond_token_address, &escrow)?;
+
+        Ok(Response::new().add_attribute("action", "open_allocation_claims"))
+    }
+
+    pub fn release_allocation(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        investor: String,
+        currency_amount: Uint128,
+        refund_fee: bool,
+    ) -> Result<Response, PlaceholderErr> {
+        // Only router can call this function to release an allocation
+        if PLACEHOLDER_PLATFORM
+            .load(deps.storage)?
+            .router
+            .ok_or(PlaceholderErr::ContractNotSetup {})?
+            != info.sender
+        {
+            return Err(PlaceholderErr::NotRouter {
+                account: info.sender.to_string(),
+            });
+        }
+
+        let bond_token_address = deps.api.addr_validate(bond_token.as_str())?;
+        let investor_address = deps.api.addr_validate(investor.as_str())?;
+        let mut escrow = load_claiming_escrow(deps.storage, &bond_token_address)?;
+        let key = (bond_token_address.clone(), investor_address.clone());
+        if ALLOCATION_CLAIMS.has(deps.storage, key.clone()) {
+            return Err(PlaceholderErr::AllocationClaimed { investor });
+        }
+        let mut subscription = subscriptions()
+            .may_load(deps.storage, key.clone())?
+            .filter(|subscription| !subscription.currency_amount.is_zero())
+            .ok_or(PlaceholderErr::NotSubscriber {
+                investor: investor.clone(),
+            })?;
+
+        // The allocation waits for the issuer, the excess goes back to the investor now
+        let allocation = currency_amount.min(subscription.currency_amount);
+        let excess = subscription.currency_amount - allocation;
+        let returned_fee = return_excess_fee(
+            deps.storage,
+            &bond_token_address,
+            &escrow,
+            &mut subscription,
+            excess,
+            refund_fee,
+        )?;
+        update_ledger(
+            deps.storage,
+            &escrow.currency,
+            &bond_token_address,
+            |ledger| ledger.refunded += excess,
+        )?;
+        settle_fee(
+            deps.storage,
+            &bond_token_address,
+            &escrow.currency,
+            subscription.fee_amount,
+        )?;
+        ALLOCATION_CLAIMS.save(deps.storage, key, &allocation)?;
+        escrow.uncollected_proceeds += allocation;
+        check_escrow_cap(deps.storage, &bond_token_address, &escrow)?;
+        BOND_ESCROWS.save(deps.storage, bond_token_address, &escrow)?;
+
+        let mut messages: Vec<SubMsg> = vec![];
+        if !(excess + returned_fee).is_zero() {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: escrow.currency.to_string(),
+                msg: to_binary(&Transfer {
+                    recipient: investor.clone(),
+                    amount: excess + returned_fee,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "release_allocation")
+            .add_attribute("investor", investor)
+            .add_attribute("allocation", allocation)
+            .add_attribute("returned_currency", excess)
+            .add_attribute("returned_fee", returned_fee)
+            .add_submessages(messages))
+    }
+
+    pub fn collect_proceeds(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        issuer: String,
+        close: bool,
     ) -> Result<Response, PlaceholderErr> {
-        // Only router can call this function to release currency
+        // Only router can call this function to pay the issuer
         if PLACEHOLDER_PLATFORM
             .load(deps.storage)?
             .router
-            .ok_or(PlaceholderErr::ContractNotSetup {})
-            .unwrap()
+            .ok_or(PlaceholderErr::ContractNotSetup {})?
             != info.sender
         {
             return Err(PlaceholderErr::NotRouter {
@@ -288,54 +971,197 @@ pub mod execute {
             });
         }
 
+        let bond_token_address = deps.api.addr_validate(bond_token.as_str())?;
+        let mut escrow = load_claiming_escrow(deps.storage, &bond_token_address)?;
+        let proceeds = escrow.uncollected_proceeds;
+        if proceeds.is_zero() && !close {
+            return Err(PlaceholderErr::NothingToCollect { bond_token });
+        }
+        update_ledger(
+            deps.storage,
+            &escrow.currency,
+            &bond_token_address,
+            |ledger| ledger.released += proceeds,
+        )?;
+        escrow.uncollected_proceeds = Uint128::zero();
+        check_escrow_cap(deps.storage, &bond_token_address, &escrow)?;
+        if close {
+            // Nothing would pay out a subscription still held once the escrow is released
+            let held = ESCROW_LEDGERS
+                .load(
+                    deps.storage,
+                    (escrow.currency.clone(), bond_token_address.clone()),
+                )?
+                .held();
+            if !held.is_zero() {
+                return Err(PlaceholderErr::SubscriptionsUnreleased { bond_token, held });
+            }
+            escrow.state = EscrowState::Released;
+        }
+        BOND_ESCROWS.save(deps.storage, bond_token_address, &escrow)?;
+
         let mut messages: Vec<SubMsg> = vec![];
-        let mut invested_currency = Uint128::zero();
-        let subscriptions =
-            SUBSCRIPTIONS.load(deps.storage, deps.api.addr_validate(bond_token.as_str())?)?;
-        for subscription in &subscriptions {
-            let mut max_allowed_currency_subscription = Uint128::zero();
-            for rule in &investment_rules {
-                if subscription.investor.to_string() == rule.investor {
-                    max_allowed_currency_subscription = rule.currency_amount;
-                    break;
+        if !proceeds.is_zero() {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: escrow.currency.to_string(),
+                msg: to_binary(&Transfer {
+                    recipient: deps.api.addr_validate(issuer.as_str())?.to_string(),
+                    amount: proceeds,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        Ok(Response::new()
+            .add_attribute(
+                "action",
+                if close {
+                    "close_allocation_claims"
+                } else {
+                    "collect_proceeds"
+                },
+            )
+            .add_attribute("amount", proceeds)
+            .add_submessages(messages))
+    }
+
+    // Loads a bond's escrow, failing unless its allocations are being released
+    fn load_claiming_escrow(
+        storage: &dyn Storage,
+        bond_token: &Addr,
+    ) -> Result<BondEscrow, PlaceholderErr> {
+        let escrow = BOND_ESCROWS.may_load(storage, bond_token.clone())?.ok_or(
+            PlaceholderErr::ClaimsNotOpen {
+                bond_token: bond_token.to_string(),
+            },
+        )?;
+        match escrow.state {
+            EscrowState::Claiming => Ok(escrow),
+            EscrowState::Released => Err(PlaceholderErr::CurrencyReleased {
+                bond_token: bond_token.to_string(),
+            }),
+            EscrowState::Refunded => Err(PlaceholderErr::EscrowRefunded {
+                bond_token: bond_token.to_string(),
+            }),
+            EscrowState::Open => Err(PlaceholderErr::ClaimsNotOpen {
+                bond_token: bond_token.to_string(),
+            }),
+        }
+    }
+
+    // Never pays out more principal for a bond than was escrowed for it
+    fn check_escrow_cap(
+        storage: &dyn Storage,
+        bond_token: &Addr,
+        escrow: &BondEscrow,
+    ) -> Result<(), PlaceholderErr> {
+        let ledger = ESCROW_LEDGERS
+            .may_load(storage, (escrow.currency.clone(), bond_token.clone()))?
+            .unwrap_or_default();
+        let amount = ledger.refunded + ledger.released + escrow.uncollected_proceeds;
+        if amount > ledger.subscribed {
+            return Err(PlaceholderErr::EscrowExceeded {
+                amount,
+                escrowed: ledger.subscribed,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn set_fee_split(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: Option<String>,
+        shares: Option<Vec<FeeShare>>,
+    ) -> Result<Response, PlaceholderErr> {
+        // Only admin can decide how fees are shared
+        if PLACEHOLDER_PLATFORM.load(deps.storage)?.admin != info.sender {
+            return Err(PlaceholderErr::NotAdmin {
+                account: info.sender.to_string(),
+            });
+        }
+
+        let shares = match shares {
+            Some(shares) => {
+                let mut total_weight = Uint128::zero();
+                let mut entries: Vec<FeeShareEntry> = vec![];
+                for share in shares {
+                    if share.weight.is_zero() {
+                        return Err(PlaceholderErr::InvalidFeeSplit {});
+                    }
+                    total_weight += share.weight;
+                    entries.push(FeeShareEntry {
+                        recipient: deps.api.addr_validate(share.recipient.as_str())?,
+                        weight: share.weight,
+                    });
+                }
+                if total_weight.u128() > MAX_FEE_WEIGHT {
+                    return Err(PlaceholderErr::InvalidFeeSplit {});
                 }
+                Some(entries)
             }
-            if subscription.currency_amount > max_allowed_currency_subscription {
-                invested_currency += max_allowed_currency_subscription;
-
-                // Return excess to investors
-                messages.push(SubMsg::new(WasmMsg::Execute {
-                    contract_addr: currency.clone(),
-                    msg: to_binary(&Transfer {
-                        recipient: subscription.investor.to_string(),
-                        amount: subscription.currency_amount - max_allowed_currency_subscription,
-                    })?,
-                    funds: vec![],
-                }));
-            } else {
-                invested_currency += subscription.currency_amount;
+            None => None,
+        };
+        match bond_token {
+            Some(bond_token) => {
+                let bond_token_address = deps.api.addr_validate(bond_token.as_str())?;
+                if !BOND_ESCROWS.has(deps.storage, bond_token_address.clone()) {
+                    return Err(PlaceholderErr::BondTokenNotRegistered { bond_token });
+                }
+                match shares {
+                    Some(shares) => {
+                        BOND_FEE_SPLITS.save(deps.storage, bond_token_address, &shares)?
+                    }
+                    None => BOND_FEE_SPLITS.remove(deps.storage, bond_token_address),
+                }
             }
+            None => match shares {
+                Some(shares) => FEE_SPLIT.save(deps.storage, &shares)?,
+                None => FEE_SPLIT.remove(deps.storage),
+            },
         }
 
-        // Transfer invested currency to issuer
-        messages.push(SubMsg::new(WasmMsg::Execute {
-            contract_addr: currency,
-            msg: to_binary(&Transfer {
-                recipient: issuer,
-                amount: invested_currency,
-            })?,
-            funds: vec![],
-        }));
+        Ok(Response::new().add_attribute("action", "set_fee_split"))
+    }
+
+    pub fn claim_fees(deps: DepsMut, info: MessageInfo) -> Result<Response, PlaceholderErr> {
+        let accrued_fees = ACCRUED_FEES
+            .prefix(info.sender.clone())
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut messages: Vec<SubMsg> = vec![];
+        for (currency, amount) in accrued_fees {
+            ACCRUED_FEES.remove(deps.storage, (info.sender.clone(), currency.clone()));
+            if amount.is_zero() {
+                continue;
+            }
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: currency.to_string(),
+                msg: to_binary(&Transfer {
+                    recipient: info.sender.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+        if messages.is_empty() {
+            return Err(PlaceholderErr::NothingToClaim {
+                recipient: info.sender.to_string(),
+            });
+        }
 
         Ok(Response::new()
-            .add_attribute("action", "release_currency")
+            .add_attribute("action", "claim_fees")
+            .add_attribute("recipient", info.sender)
             .add_submessages(messages))
     }
 
     pub fn withdraw_system_fee(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
-        recipient: String,
+        currency: String,
+        recipients: Vec<FeeRecipient>,
     ) -> Result<Response, PlaceholderErr> {
         // Only admin can withdraw system fee
         if PLACEHOLDER_PLATFORM.load(deps.storage)?.admin != info.sender {
@@ -343,27 +1169,63 @@ pub mod execute {
                 account: info.sender.to_string(),
             });
         }
+        if recipients.is_empty() {
+            return Err(PlaceholderErr::NoFeeRecipients {});
+        }
 
-        // Transfer all fees to the recipient
-        let currencies: Vec<String> = SYSTEM_FEE
-            .keys(deps.storage, None, None, Order::Ascending)
-            .map(|item| item.map(Into::into))
-            .collect::<StdResult<_>>()?;
+        // Each payout comes off the stored fee before the next one is computed
+        let currency_address = deps.api.addr_validate(currency.as_str())?;
+        let mut withdrawal_id = FEE_WITHDRAWAL_COUNT
+            .may_load(deps.storage)?
+            .unwrap_or_default();
         let mut withdraw_messages: Vec<SubMsg> = vec![];
-        for currency in currencies {
+        for fee_recipient in recipients {
+            let recipient = deps.api.addr_validate(fee_recipient.recipient.as_str())?;
+            let available = SYSTEM_FEE
+                .may_load(deps.storage, currency_address.clone())?
+                .unwrap_or_default();
+            let amount = fee_recipient.amount.unwrap_or(available);
+            if amount.is_zero() {
+                return Err(PlaceholderErr::NothingToWithdraw { currency });
+            }
+            if amount > available {
+                return Err(PlaceholderErr::InsufficientSystemFee {
+                    currency,
+                    amount,
+                    available,
+                });
+            }
+            SYSTEM_FEE.save(
+                deps.storage,
+                currency_address.clone(),
+                &(available - amount),
+            )?;
+
+            withdrawal_id += 1;
+            FEE_WITHDRAWALS.save(
+                deps.storage,
+                withdrawal_id,
+                &FeeWithdrawal {
+                    currency: currency_address.clone(),
+                    recipient: recipient.clone(),
+                    amount,
+                    time: env.block.time,
+                },
+            )?;
             withdraw_messages.push(SubMsg::new(WasmMsg::Execute {
                 contract_addr: currency.clone(),
                 msg: to_binary(&Transfer {
-                    recipient: recipient.clone(),
-                    amount: SYSTEM_FEE
-                        .load(deps.storage, deps.api.addr_validate(currency.as_str())?)?,
+                    recipient: recipient.to_string(),
+                    amount,
                 })?,
                 funds: vec![],
             }));
         }
+        FEE_WITHDRAWAL_COUNT.save(deps.storage, &withdrawal_id)?;
 
         Ok(Response::new()
             .add_attribute("action", "withdraw_system_fee")
+            .add_attribute("currency", currency)
             .add_submessages(withdraw_messages))
     }
 }
@@ -371,18 +1233,369 @@ pub mod execute {
 pub mod query {
     use super::*;
 
-    pub fn get_subscriptions(deps: Deps, bond_token: String) -> StdResult<SubscriptionsResponse> {
-        let subscriptions = SUBSCRIPTIONS
-            .load(deps.storage, deps.api.addr_validate(bond_token.as_str())?)?
-            .iter()
-            .map(|s| SubscriptionResponse {
-                investor: s.investor.to_string(),
-                currency_amount: s.currency_amount,
+    fn subscription_response(subscription: Subscription) -> SubscriptionResponse {
+        SubscriptionResponse {
+            investor: subscription.investor.to_string(),
+            currency_amount: subscription.currency_amount,
+            fee_amount: subscription.fee_amount,
+            refunded: subscription.refunded,
+            sequence: subscription.sequence,
+        }
+    }
+
+    pub fn get_escrow(deps: Deps, bond_token: String) -> StdResult<EscrowResponse> {
+        let escrow =
+            BOND_ESCROWS.load(deps.storage, deps.api.addr_validate(bond_token.as_str())?)?;
+        Ok(EscrowResponse {
+            currency: escrow.currency.to_string(),
+            distribution_deadline: escrow.distribution_deadline,
+            state: escrow.state,
+            allocation_fee_policy: escrow.allocation_fee_policy,
+        })
+    }
+
+    pub fn get_escrow_ledger(deps: Deps, bond_token: String) -> StdResult<EscrowLedgerResponse> {
+        let bond_token = deps.api.addr_validate(bond_token.as_str())?;
+        let currency = BOND_ESCROWS
+            .load(deps.storage, bond_token.clone())?
+            .currency;
+        let ledger = ESCROW_LEDGERS
+            .may_load(deps.storage, (currency.clone(), bond_token))?
+            .unwrap_or_default();
+        Ok(EscrowLedgerResponse {
+            currency: currency.to_string(),
+            subscribed: ledger.subscribed,
+            fees: ledger.fees,
+            refunded: ledger.refunded,
+            fees_refunded: ledger.fees_refunded,
+            fees_settled: ledger.fees_settled,
+            released: ledger.released,
+            held: ledger.held(),
+        })
+    }
+
+    pub fn reconcile(deps: Deps, env: Env, currency: String) -> StdResult<ReconcileResponse> {
+        let currency = deps.api.addr_validate(currency.as_str())?;
+        let mut escrowed = Uint128::zero();
+        let mut pending_fees = Uint128::zero();
+        for item in ESCROW_LEDGERS.prefix(currency.clone()).range(
+            deps.storage,
+            None,
+            None,
+            Order::Ascending,
+        ) {
+            let ledger = item?.1;
+            escrowed += ledger.held();
+            pending_fees += ledger.pending_fees();
+        }
+        let system_fee = SYSTEM_FEE
+            .may_load(deps.storage, currency.clone())?
+            .unwrap_or_default();
+        let mut accrued_fees = Uint128::zero();
+        for item in ACCRUED_FEES.range(deps.storage, None, None, Order::Ascending) {
+            let ((_, accrued_currency), amount) = item?;
+            if accrued_currency == currency {
+                accrued_fees += amount;
+            }
+        }
+        let ledger_total = escrowed + pending_fees + system_fee + accrued_fees;
+        let balance: BalanceResponse = deps.querier.query_wasm_smart(
+            currency.to_string(),
+            &Cw20QueryMsg::Balance {
+                address: env.contract.address.to_string(),
+            },
+        )?;
+        Ok(ReconcileResponse {
+            currency: currency.to_string(),
+            escrowed,
+            pending_fees,
+            system_fee,
+            accrued_fees,
+            ledger_total,
+            balance: balance.balance,
+            surplus: balance.balance.saturating_sub(ledger_total),
+            shortfall: ledger_total.saturating_sub(balance.balance),
+        })
+    }
+
+    pub fn get_system_fees(deps: Deps) -> StdResult<SystemFeesResponse> {
+        let fees = SYSTEM_FEE
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(currency, amount)| SystemFeeResponse {
+                    currency: currency.to_string(),
+                    amount,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(SystemFeesResponse { fees })
+    }
+
+    pub fn get_fee_split(deps: Deps, bond_token: Option<String>) -> StdResult<FeeSplitResponse> {
+        let shares = match bond_token {
+            Some(bond_token) => {
+                fee_split(deps.storage, &deps.api.addr_validate(bond_token.as_str())?)?
+            }
+            None => FEE_SPLIT.may_load(deps.storage)?.unwrap_or_default(),
+        };
+        Ok(FeeSplitResponse {
+            shares: shares
+                .into_iter()
+                .map(|share| FeeShare {
+                    recipient: share.recipient.to_string(),
+                    weight: share.weight,
+                })
+                .collect(),
+        })
+    }
+
+    pub fn get_accrued_fees(deps: Deps, recipient: String) -> StdResult<AccruedFeesResponse> {
+        let fees = ACCRUED_FEES
+            .prefix(deps.api.addr_validate(recipient.as_str())?)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(currency, amount)| SystemFeeResponse {
+                    currency: currency.to_string(),
+                    amount,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(AccruedFeesResponse { fees })
+    }
+
+    pub fn get_fee_withdrawals(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<FeeWithdrawalsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let withdrawals = FEE_WITHDRAWALS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| {
+                item.map(|(id, withdrawal)| FeeWithdrawalResponse {
+                    id,
+                    currency: withdrawal.currency.to_string(),
+                    recipient: withdrawal.recipient.to_string(),
+                    amount: withdrawal.amount,
+                    time: withdrawal.time,
+                })
             })
-            .collect();
+            .collect::<StdResult<_>>()?;
+        Ok(FeeWithdrawalsResponse { withdrawals })
+    }
+
+    pub fn preview_allocation(
+        deps: Deps,
+        bond_token: String,
+        policy: AllocationPolicy,
+        cursor: Option<AllocationCursor>,
+        limit: Option<u32>,
+    ) -> StdResult<AllocationResponse> {
+        let bond_token_address = deps.api.addr_validate(bond_token.as_str())?;
+        let mut limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let total_subscribed = SUBSCRIPTION_TOTALS
+            .may_load(deps.storage, bond_token_address.clone())?
+            .unwrap_or_default();
+        let limits_response: BondSubscriptionLimitsResponse = deps.querier.query_wasm_smart(
+            bond_token,
+            &BondTokenQueryMsg::AdditionalQueryMsg(
+                BondTokenAdditionalQueryMsg::SubscriptionLimits {},
+            ),
+        )?;
+        let mut anchor_demands = vec![];
+        if let AllocationPolicy::AnchorsFirst { anchors, .. } = &policy {
+            for anchor in anchors {
+                let demand = subscriptions()
+                    .may_load(
+                        deps.storage,
+                        (bond_token_address.clone(), deps.api.addr_validate(anchor)?),
+                    )?
+                    .map(|subscription| subscription.currency_amount)
+                    .filter(|demand| !demand.is_zero())
+                    .ok_or_else(|| {
+                        StdError::generic_err(
+                            PlaceholderErr::NotSubscriber {
+                                investor: anchor.clone(),
+                            }
+                            .to_string(),
+                        )
+                    })?;
+                anchor_demands.push((anchor.clone(), demand));
+            }
+        }
+        let allocator = Allocator::new(
+            &policy,
+            limits_response.limits.hard_cap,
+            total_subscribed,
+            anchor_demands,
+        );
+
+        // Refunded subscriptions are paged through but get nothing
+        let mut cursor = cursor.unwrap_or(AllocationCursor {
+            counting: allocator.counts_tickets(),
+            ..AllocationCursor::default()
+        });
+        let mut allocations = vec![];
+        loop {
+            let page = subscriptions()
+                .idx
+                .arrival
+                .prefix_range_raw(
+                    deps.storage,
+                    Some(PrefixBound::exclusive((
+                        bond_token_address.clone(),
+                        cursor.start_after,
+                    ))),
+                    Some(PrefixBound::inclusive((
+                        bond_token_address.clone(),
+                        u64::MAX,
+                    ))),
+                    Order::Ascending,
+                )
+                .take(limit)
+                .map(|item| item.map(|(_, subscription)| subscription))
+                .collect::<StdResult<Vec<_>>>()?;
+            for subscription in &page {
+                cursor.start_after = subscription.sequence;
+                if subscription.currency_amount.is_zero() {
+                    continue;
+                }
+                if cursor.counting {
+                    allocator.count(&mut cursor, subscription);
+                } else {
+                    allocations.push(InvesmentRule {
+                        investor: subscription.investor.to_string(),
+                        currency_amount: allocator.allocate(&mut cursor, subscription),
+                    });
+                }
+            }
+
+            // A short page ends the count, allocations start over with what is left of the page
+            if page.len() == limit {
+                break;
+            }
+            limit -= page.len();
+            if !cursor.counting {
+                return Ok(AllocationResponse {
+                    allocations,
+                    total_subscribed,
+                    total_allocated: cursor.allocated,
+                    next: None,
+                });
+            }
+            allocator
+                .finish_count(&mut cursor)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+        }
+
+        Ok(AllocationResponse {
+            allocations,
+            total_subscribed,
+            total_allocated: cursor.allocated,
+            next: Some(cursor),
+        })
+    }
+
+    pub fn get_subscriptions(
+        deps: Deps,
+        bond_token: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<SubscriptionsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|investor| deps.api.addr_validate(investor.as_str()))
+            .transpose()?;
+        let subscriptions = subscriptions()
+            .prefix(deps.api.addr_validate(bond_token.as_str())?)
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| item.map(|(_, subscription)| subscription_response(subscription)))
+            .collect::<StdResult<_>>()?;
         Ok(SubscriptionsResponse { subscriptions })
     }
 
+    pub fn get_subscription(
+        deps: Deps,
+        bond_token: String,
+        investor: String,
+    ) -> StdResult<SubscriptionResponse> {
+        let investor = deps.api.addr_validate(investor.as_str())?;
+        let subscription = subscriptions()
+            .may_load(
+                deps.storage,
+                (
+                    deps.api.addr_validate(bond_token.as_str())?,
+                    investor.clone(),
+                ),
+            )?
+            .unwrap_or(Subscription {
+                investor,
+                currency_amount: Uint128::zero(),
+                fee_amount: Uint128::zero(),
+                refunded: false,
+                sequence: 0,
+            });
+        Ok(subscription_response(subscription))
+    }
+
+    pub fn get_subscriptions_by_investor(
+        deps: Deps,
+        investor: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<InvestorSubscriptionsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let investor = deps.api.addr_validate(investor.as_str())?;
+        let start = start_after
+            .map(|bond_token| -> StdResult<_> {
+                Ok(Bound::exclusive((
+                    deps.api.addr_validate(bond_token.as_str())?,
+                    investor.clone(),
+                )))
+            })
+            .transpose()?;
+        let subscriptions = subscriptions()
+            .idx
+            .investor
+            .prefix(investor)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(
+                    |((bond_token, _), subscription)| InvestorSubscriptionResponse {
+                        bond_token: bond_token.to_string(),
+                        currency_amount: subscription.currency_amount,
+                        fee_amount: subscription.fee_amount,
+                        refunded: subscription.refunded,
+                    },
+                )
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(InvestorSubscriptionsResponse { subscriptions })
+    }
+
+    pub fn get_subscription_total(
+        deps: Deps,
+        bond_token: String,
+    ) -> StdResult<SubscriptionTotalResponse> {
+        let currency_amount = SUBSCRIPTION_TOTALS
+            .may_load(deps.storage, deps.api.addr_validate(bond_token.as_str())?)?
+            .unwrap_or_default();
+        Ok(SubscriptionTotalResponse { currency_amount })
+    }
+
     pub fn validate_bond_token(
         deps: Deps,
         bond_token: String,
@@ -391,7 +1604,7 @@ pub mod query {
             .load(deps.storage)?
             .bond_tokens
             .iter()
-            .any(|token| token.to_string() == bond_token);
+            .any(|token| *token == bond_token);
         Ok(BondValidationResponse { validity })
     }
 }
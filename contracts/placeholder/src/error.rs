@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Timestamp, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -21,6 +21,76 @@ pub enum PlaceholderErr {
     #[error("PlaceholderErr: caller is not router ({account:?}")]
     NotRouter { account: String },
 
+    #[error("PlaceholderErr: insufficient subscription ({investor:?} {currency_amount:?})")]
+    InsufficientSubscription {
+        investor: String,
+        currency_amount: Uint128,
+    },
+
+    #[error("PlaceholderErr: bond token not registered ({bond_token:?})")]
+    BondTokenNotRegistered { bond_token: String },
+
+    #[error("PlaceholderErr: currency already released ({bond_token:?})")]
+    CurrencyReleased { bond_token: String },
+
+    #[error("PlaceholderErr: subscriptions already refunded ({bond_token:?})")]
+    EscrowRefunded { bond_token: String },
+
+    #[error(
+        "PlaceholderErr: investment rule names an investor without subscription ({investor:?})"
+    )]
+    NotSubscriber { investor: String },
+
+    #[error("PlaceholderErr: release exceeds escrow ({amount:?} > {escrowed:?})")]
+    EscrowExceeded { amount: Uint128, escrowed: Uint128 },
+
+    #[error(
+        "PlaceholderErr: refunds are not allowed before the distribution deadline ({deadline:?})"
+    )]
+    DistributionDeadlineNotPassed { deadline: Timestamp },
+
+    #[error("PlaceholderErr: nothing to refund ({investor:?})")]
+    NothingToRefund { investor: String },
+
+    #[error("PlaceholderErr: no fee recipients")]
+    NoFeeRecipients {},
+
+    #[error("PlaceholderErr: insufficient system fee ({currency:?} {amount:?} > {available:?})")]
+    InsufficientSystemFee {
+        currency: String,
+        amount: Uint128,
+        available: Uint128,
+    },
+
+    #[error("PlaceholderErr: nothing to withdraw ({currency:?})")]
+    NothingToWithdraw { currency: String },
+
+    #[error("PlaceholderErr: fee weights must be positive and add up to at most 10000")]
+    InvalidFeeSplit {},
+
+    #[error("PlaceholderErr: nothing to claim ({recipient:?})")]
+    NothingToClaim { recipient: String },
+
+    #[error("PlaceholderErr: allocations are being claimed ({bond_token:?})")]
+    AllocationsCommitted { bond_token: String },
+
+    #[error("PlaceholderErr: allocations are not open for claims ({bond_token:?})")]
+    ClaimsNotOpen { bond_token: String },
+
+    #[error("PlaceholderErr: allocation already claimed ({investor:?})")]
+    AllocationClaimed { investor: String },
+
+    #[error("PlaceholderErr: nothing to collect ({bond_token:?})")]
+    NothingToCollect { bond_token: String },
+
+    #[error(
+        "PlaceholderErr: subscriptions are neither released nor refunded ({bond_token:?} {held:?})"
+    )]
+    SubscriptionsUnreleased { bond_token: String, held: Uint128 },
+
+    #[error("PlaceholderErr: minimum tickets exceed allocation ({guaranteed:?} > {total:?})")]
+    AllocationInfeasible { guaranteed: Uint128, total: Uint128 },
+
     #[error("PlaceholderErr: not setup")]
     ContractNotSetup {},
 }
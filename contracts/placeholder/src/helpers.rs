@@ -1,15 +1,94 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Timestamp, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub const NATIVE_DENOM: &str = "flavor";
 
+// Pagination
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
+
+// Fee share weights are basis points, values [0 -> 10000] map to [0% -> 100%]
+pub const MAX_FEE_WEIGHT: u128 = 10000;
+
 // Which investor has subscribed how much currency
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Subscription {
     pub investor: Addr,
     pub currency_amount: Uint128,
+    #[serde(default)]
+    pub fee_amount: Uint128, // fee paid on top of currency_amount
+    #[serde(default)]
+    pub refunded: bool, // investor took the subscription back after the distribution deadline
+    #[serde(default)]
+    pub sequence: u64, // arrival order of the subscription, kept on top-ups
+}
+
+// Lifecycle of the currency held for a bond
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum EscrowState {
+    Open,     // subscriptions are held
+    Released, // invested currency went to the issuer and the excess back to investors
+    Refunded, // the issuance was cancelled and every subscription was refunded
+    Claiming, // allocations are released one investor at a time, by a distribution job or Merkle-root claims
+}
+
+// Who receives how much of a currency's system fee, everything left when no amount is given
+#[cw_serde]
+pub struct FeeRecipient {
+    pub recipient: String,
+    pub amount: Option<Uint128>,
+}
+
+// How an oversubscribed bond's `total` is shared among subscriptions, `total` is capped at
+// the bond's hard cap and defaults to it when None
+#[cw_serde]
+pub enum AllocationPolicy {
+    ProRata {
+        total: Option<Uint128>,
+    },
+    FirstComeFirstServed {
+        total: Option<Uint128>,
+    },
+    // Each subscription gets up to `minimum_ticket`, the rest is shared pro-rata
+    MinimumTicket {
+        total: Option<Uint128>,
+        minimum_ticket: Uint128,
+    },
+    // `anchors` are filled in the given order, the rest is shared pro-rata
+    AnchorsFirst {
+        total: Option<Uint128>,
+        anchors: Vec<String>,
+    },
+}
+
+impl AllocationPolicy {
+    pub fn total(&self) -> Option<Uint128> {
+        match self {
+            AllocationPolicy::ProRata { total }
+            | AllocationPolicy::FirstComeFirstServed { total }
+            | AllocationPolicy::MinimumTicket { total, .. }
+            | AllocationPolicy::AnchorsFirst { total, .. } => *total,
+        }
+    }
+}
+
+// What the subscription fee is charged on once allocations are known
+#[cw_serde]
+#[derive(Copy, Eq, Default)]
+pub enum AllocationFeePolicy {
+    #[default]
+    Subscribed, // the whole subscription, the fee on excess is kept
+    Allocated, // only the allocated part, the fee on excess is returned with it
+}
+
+// Part of every settled fee credited to a recipient, the platform keeps what the weights leave
+#[cw_serde]
+pub struct FeeShare {
+    pub recipient: String,
+    pub weight: Uint128,
 }
 
 // Which investor is allowed to subscribe maximum how much currency
@@ -29,8 +108,160 @@ pub struct BondValidationResponse {
     pub validity: bool,
 }
 
+#[cw_serde]
+pub struct SubscriptionTotalResponse {
+    pub currency_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct InvestorSubscriptionsResponse {
+    pub subscriptions: Vec<InvestorSubscriptionResponse>,
+}
+
+#[cw_serde]
+pub struct InvestorSubscriptionResponse {
+    pub bond_token: String,
+    pub currency_amount: Uint128,
+    pub fee_amount: Uint128,
+    pub refunded: bool,
+}
+
 #[cw_serde]
 pub struct SubscriptionResponse {
     pub investor: String,
     pub currency_amount: Uint128,
+    pub fee_amount: Uint128,
+    pub refunded: bool,
+    pub sequence: u64,
+}
+
+// Where a paged allocation stopped, handed back to get the next page
+#[cw_serde]
+#[derive(Default)]
+pub struct AllocationCursor {
+    pub start_after: u64,    // sequence of the last subscription paged through
+    pub counting: bool,      // first pass adding up minimum tickets, nothing is allocated
+    pub guaranteed: Uint128, // minimum tickets added up so far
+    pub weight: Uint128,     // pro-rata weight of the subscriptions allocated so far
+    pub allocated: Uint128,
+}
+
+#[cw_serde]
+pub struct AllocationResponse {
+    pub allocations: Vec<InvesmentRule>, // one per subscriber of the page, in arrival order
+    pub total_subscribed: Uint128,
+    pub total_allocated: Uint128,       // up to the end of the page
+    pub next: Option<AllocationCursor>, // None after the last page
+}
+
+#[cw_serde]
+pub struct EscrowResponse {
+    pub currency: String,
+    pub distribution_deadline: Timestamp,
+    pub state: EscrowState,
+    pub allocation_fee_policy: AllocationFeePolicy,
+}
+
+#[cw_serde]
+pub struct EscrowLedgerResponse {
+    pub currency: String,
+    pub subscribed: Uint128,
+    pub fees: Uint128,
+    pub refunded: Uint128,
+    pub fees_refunded: Uint128,
+    pub fees_settled: Uint128,
+    pub released: Uint128,
+    pub held: Uint128,
+}
+
+#[cw_serde]
+pub struct ReconcileResponse {
+    pub currency: String,
+    pub escrowed: Uint128,     // principal held for all bonds in this currency
+    pub pending_fees: Uint128, // fees that may still be refunded
+    pub system_fee: Uint128,   // platform's fees not yet withdrawn
+    pub accrued_fees: Uint128, // recipients' fees not yet claimed
+    pub ledger_total: Uint128, // escrowed + pending_fees + system_fee + accrued_fees
+    pub balance: Uint128,      // placeholder's actual CW20 balance
+    pub surplus: Uint128,      // balance above ledger_total
+    pub shortfall: Uint128,    // balance below ledger_total
+}
+
+#[cw_serde]
+pub struct SystemFeesResponse {
+    pub fees: Vec<SystemFeeResponse>,
+}
+
+#[cw_serde]
+pub struct SystemFeeResponse {
+    pub currency: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct FeeWithdrawalsResponse {
+    pub withdrawals: Vec<FeeWithdrawalResponse>,
+}
+
+#[cw_serde]
+pub struct FeeWithdrawalResponse {
+    pub id: u64,
+    pub currency: String,
+    pub recipient: String,
+    pub amount: Uint128,
+    pub time: Timestamp,
+}
+
+#[cw_serde]
+pub struct FeeSplitResponse {
+    pub shares: Vec<FeeShare>,
+}
+
+#[cw_serde]
+pub struct AccruedFeesResponse {
+    pub fees: Vec<SystemFeeResponse>,
+}
+
+// Bond token queries the placeholder reads, mirrored here since the bond token depends on this crate
+#[cw_serde]
+pub enum BondTokenQueryMsg {
+    AdditionalQueryMsg(BondTokenAdditionalQueryMsg),
+}
+
+#[cw_serde]
+pub enum BondTokenAdditionalQueryMsg {
+    GetCurrency {},
+    BondSchedule {},
+    SubscriptionLimits {},
+}
+
+// Only the fields read here, the rest of the bond token's response is ignored
+#[derive(Deserialize)]
+pub struct BondCurrencyResponse {
+    pub currency: String,
+}
+
+#[derive(Deserialize)]
+pub struct BondScheduleResponse {
+    pub distribution_deadline: Timestamp,
+    pub current_phase: BondPhase,
+}
+
+#[derive(Deserialize)]
+pub struct BondSubscriptionLimitsResponse {
+    pub limits: BondSubscriptionLimits,
+}
+
+#[derive(Deserialize)]
+pub struct BondSubscriptionLimits {
+    pub hard_cap: Option<Uint128>,
+}
+
+#[derive(Deserialize, PartialEq, Eq)]
+pub enum BondPhase {
+    Subscription,
+    Distribution,
+    Coupon,
+    Redemption,
+    Cancelled,
 }
@@ -1,3 +1,4 @@
+pub mod allocation;
 pub mod contract;
 mod error;
 pub mod helpers;
@@ -1,10 +1,19 @@
-use crate::helpers::{BondValidationResponse, InvesmentRule, SubscriptionsResponse};
+use crate::helpers::{
+    AccruedFeesResponse, AllocationCursor, AllocationFeePolicy, AllocationPolicy,
+    AllocationResponse, BondValidationResponse, EscrowLedgerResponse, EscrowResponse, FeeRecipient,
+    FeeShare, FeeSplitResponse, FeeWithdrawalsResponse, InvestorSubscriptionsResponse,
+    ReconcileResponse, SubscriptionResponse, SubscriptionTotalResponse, SubscriptionsResponse,
+    SystemFeesResponse,
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Timestamp, Uint128};
 
 #[cw_serde]
 pub struct InstantiateMsg {}
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     Setup {
@@ -21,21 +30,65 @@ pub enum ExecuteMsg {
     // },
     RegisterBondToken {
         bond_token: String,
+        currency: String,
+        distribution_deadline: Timestamp,
+        allocation_fee_policy: AllocationFeePolicy,
     },
+    // only callable by bond token
     RegisterSubscription {
         investor: String,
-        currency: String,
         subscription_amount: Uint128,
         fee_amount: Uint128,
     },
-    ReleaseCurrency {
-        issuer: String,
+    // only callable by bond token, fee on the returned part too when refund_fee
+    RefundSubscription {
+        investor: String,
+        currency_amount: Uint128,
+        refund_fee: bool,
+    },
+    // next page of a cancelled bond, only callable by router
+    RefundSubscriptions {
         bond_token: String,
-        currency: String,
-        investment_rules: Vec<InvesmentRule>,
+        refund_fees: bool,
+        limit: Option<u32>,
+    },
+    // after the distribution deadline if nothing was released to the caller
+    ClaimRefund {
+        bond_token: String,
+    },
+    // global split when bond_token is None, shares None removes it, only callable by admin
+    SetFeeSplit {
+        bond_token: Option<String>,
+        shares: Option<Vec<FeeShare>>,
     },
+    ClaimFees {},
+    // recipients paid in order, only callable by admin
     WithdrawSystemFee {
-        recipient: String,
+        currency: String,
+        recipients: Vec<FeeRecipient>,
+    },
+    // allocations are claimed one by one from now on, only callable by router
+    OpenAllocationClaims {
+        bond_token: String,
+        investors: Vec<String>,
+    },
+    // refunds the excess over the allocation, once per investor, only callable by router,
+    // fee on the excess too when refund_fee whatever the bond's allocation fee policy
+    ReleaseAllocation {
+        bond_token: String,
+        investor: String,
+        currency_amount: Uint128,
+        refund_fee: bool,
+    },
+    // only callable by router
+    CollectProceeds {
+        bond_token: String,
+        issuer: String,
+    },
+    // once every subscription was released or refunded, only callable by router
+    CloseAllocationClaims {
+        bond_token: String,
+        issuer: String,
     },
 }
 
@@ -45,7 +98,65 @@ pub enum QueryMsg {
     /// Returns the current balance of the given address, 0 if unset.
     /// Return type: BalanceResponse.
     #[returns(SubscriptionsResponse)]
-    SubscriptionsOf { bond_token: String },
+    SubscriptionsOf {
+        bond_token: String,
+        start_after: Option<String>, // investor
+        limit: Option<u32>,
+    },
+
+    // zero amounts when not subscribed
+    #[returns(SubscriptionResponse)]
+    SubscriptionOf {
+        bond_token: String,
+        investor: String,
+    },
+
+    #[returns(InvestorSubscriptionsResponse)]
+    SubscriptionsByInvestor {
+        investor: String,
+        start_after: Option<String>, // bond token
+        limit: Option<u32>,
+    },
+
+    // net of fees
+    #[returns(SubscriptionTotalResponse)]
+    SubscriptionTotal { bond_token: String },
+
+    #[returns(EscrowResponse)]
+    Escrow { bond_token: String },
+
+    #[returns(EscrowLedgerResponse)]
+    EscrowLedger { bond_token: String },
+
+    // ledgers against the CW20 balance
+    #[returns(ReconcileResponse)]
+    Reconcile { currency: String },
+
+    // settled and not yet withdrawn
+    #[returns(SystemFeesResponse)]
+    SystemFees {},
+
+    // global split when the bond has none
+    #[returns(FeeSplitResponse)]
+    FeeSplit { bond_token: Option<String> },
+
+    #[returns(AccruedFeesResponse)]
+    AccruedFees { recipient: String },
+
+    #[returns(FeeWithdrawalsResponse)]
+    FeeWithdrawals {
+        start_after: Option<u64>, // withdrawal id
+        limit: Option<u32>,
+    },
+
+    // paged in arrival order, the cursor of a page gets the next one
+    #[returns(AllocationResponse)]
+    PreviewAllocation {
+        bond_token: String,
+        policy: AllocationPolicy,
+        cursor: Option<AllocationCursor>,
+        limit: Option<u32>,
+    },
 
     #[returns(BondValidationResponse)]
     ValidateBondToken { bond_token: String },
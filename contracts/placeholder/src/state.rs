@@ -1,9 +1,9 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::helpers::Subscription;
+use crate::helpers::{AllocationFeePolicy, EscrowState, Subscription};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct PlaceholderPlatform {
@@ -14,7 +14,102 @@ pub struct PlaceholderPlatform {
     pub bond_tokens: Vec<Addr>,
 }
 
+// Currency held for a bond's subscriptions until it is released to the issuer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BondEscrow {
+    pub currency: Addr,
+    pub distribution_deadline: Timestamp, // investors can claim refunds after this if nothing was released
+    pub state: EscrowState,
+    #[serde(default)]
+    pub allocation_fee_policy: AllocationFeePolicy,
+    #[serde(default)]
+    pub uncollected_proceeds: Uint128, // claimed allocations not yet paid to the issuer
+}
+
+// Currency that went in and out of the placeholder for one bond
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct EscrowLedger {
+    pub subscribed: Uint128,    // principal received from investors
+    pub fees: Uint128,          // fees received on top of the principal
+    pub refunded: Uint128,      // principal returned to investors, including excess at release
+    pub fees_refunded: Uint128, // fees returned to investors
+    pub released: Uint128,      // principal paid to the issuer
+    #[serde(default)]
+    pub fees_settled: Uint128, // fees that can no longer be refunded, paid to recipients and the system fee
+}
+
+impl EscrowLedger {
+    // Principal still held for the bond
+    pub fn held(&self) -> Uint128 {
+        self.subscribed - self.refunded - self.released
+    }
+
+    // Fees still refundable to investors, not yet part of the system fee
+    pub fn pending_fees(&self) -> Uint128 {
+        self.fees - self.fees_refunded - self.fees_settled
+    }
+}
+
+// One transfer of system fee to a recipient
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FeeWithdrawal {
+    pub currency: Addr,
+    pub recipient: Addr,
+    pub amount: Uint128,
+    pub time: Timestamp,
+}
+
+// Stored form of a fee share
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FeeShareEntry {
+    pub recipient: Addr,
+    pub weight: Uint128,
+}
+
+pub struct SubscriptionIndexes<'a> {
+    pub investor: MultiIndex<'a, Addr, Subscription, (Addr, Addr)>,
+    pub arrival: MultiIndex<'a, (Addr, u64), Subscription, (Addr, Addr)>, // (bond token, sequence)
+}
+
+impl<'a> IndexList<Subscription> for SubscriptionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Subscription>> + '_> {
+        let v: Vec<&dyn Index<Subscription>> = vec![&self.investor, &self.arrival];
+        Box::new(v.into_iter())
+    }
+}
+
+// Maps from (bond token, investor) to the investor's subscription
+pub fn subscriptions<'a>() -> IndexedMap<'a, (Addr, Addr), Subscription, SubscriptionIndexes<'a>> {
+    let indexes = SubscriptionIndexes {
+        investor: MultiIndex::new(
+            |_pk, subscription: &Subscription| subscription.investor.clone(),
+            "bond_subscriptions",
+            "bond_subscriptions__investor",
+        ),
+        arrival: MultiIndex::new(
+            |pk, subscription: &Subscription| {
+                // Primary keys are always (bond token, investor)
+                let (bond_token, _) = <(Addr, Addr)>::from_slice(pk).unwrap();
+                (bond_token, subscription.sequence)
+            },
+            "bond_subscriptions",
+            "bond_subscriptions__arrival",
+        ),
+    };
+    IndexedMap::new("bond_subscriptions", indexes)
+}
+
 pub const PLACEHOLDER_PLATFORM: Item<PlaceholderPlatform> = Item::new("placeholder_platform");
 pub const OPERATORS: Map<Addr, bool> = Map::new("operators");
-pub const SUBSCRIPTIONS: Map<Addr, Vec<Subscription>> = Map::new("subscriptions"); // maps from a bond token to its investors' subcriptions
+pub const LEGACY_SUBSCRIPTIONS: Map<Addr, Vec<Subscription>> = Map::new("subscriptions"); // layout before subscriptions were indexed, only read by migrate
+pub const ALLOCATION_CLAIMS: Map<(Addr, Addr), Uint128> = Map::new("allocation_claims"); // maps from (bond token, investor) to the claimed allocation
+pub const SUBSCRIPTION_SEQUENCE: Item<u64> = Item::new("subscription_sequence");
+pub const SUBSCRIPTION_TOTALS: Map<Addr, Uint128> = Map::new("subscription_totals"); // maps from a bond token to its total subscribed currency
+pub const BOND_ESCROWS: Map<Addr, BondEscrow> = Map::new("bond_escrows"); // maps from a bond token to its escrow
+pub const ESCROW_LEDGERS: Map<(Addr, Addr), EscrowLedger> = Map::new("escrow_ledgers"); // maps from (currency, bond token) to its ledger
 pub const SYSTEM_FEE: Map<Addr, Uint128> = Map::new("system_fee");
+pub const FEE_WITHDRAWAL_COUNT: Item<u64> = Item::new("fee_withdrawal_count");
+pub const FEE_WITHDRAWALS: Map<u64, FeeWithdrawal> = Map::new("fee_withdrawals"); // maps from a withdrawal id to the withdrawal
+pub const FEE_SPLIT: Item<Vec<FeeShareEntry>> = Item::new("fee_split");
+pub const BOND_FEE_SPLITS: Map<Addr, Vec<FeeShareEntry>> = Map::new("bond_fee_splits"); // maps from a bond token to its own split
+pub const ACCRUED_FEES: Map<(Addr, Addr), Uint128> = Map::new("accrued_fees"); // maps from (recipient, currency) to fees left to claim
@@ -1,34 +1,59 @@
 #[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response,
-    StdResult, SubMsg, Uint128, WasmMsg, WasmQuery,
+    to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, QueryRequest, Reply,
+    Response, StdResult, SubMsg, Timestamp, Uint128, WasmMsg, WasmQuery,
 };
 use cw2::set_contract_version;
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw20_base::msg::ExecuteMsg as Cw20BaseExecuteMsg;
+use cw_storage_plus::Map;
 
 use bond_token::{
-    helpers::{CurrencyResponse, HoldersResponse, IssuerResponse, Phase},
+    helpers::{
+        BondScheduleResponse, CouponEntitlementResponse, CouponScheduleResponse, CurrencyResponse,
+        HoldersResponse, IssuerResponse, Phase, TotalSupplyAtResponse,
+    },
     msg::{
-        AdditionalExecuteMsg::{BurnFromHolder, MintToInvestor, UpdatePhase},
-        AdditionalQueryMsg::{GetCurrency, GetHolders, GetIssuer},
-        ExecuteMsg::AdditionalExecuteMsg,
+        AdditionalExecuteMsg::{
+            BurnFromHolder, ClaimCouponEntitlement, MintToInvestor, RecordCouponEntitlements,
+            RecordCouponPayment, UpdatePhase,
+        },
+        AdditionalQueryMsg::{
+            BondSchedule, CouponEntitlement, CouponSchedule, GetCurrency, GetIssuer, Holders,
+            HoldersAt, TotalSupplyAt,
+        },
+        ExecuteMsg::{AdditionalExecuteMsg, Cw20ExecuteMsg as BondTokenCw20ExecuteMsg},
         QueryMsg::AdditionalQueryMsg,
     },
 };
 use placeholder::{
-    helpers::{BondValidationResponse, InvesmentRule, SubscriptionsResponse},
+    helpers::{
+        AllocationPolicy, AllocationResponse, BondValidationResponse, InvesmentRule,
+        SubscriptionResponse, SubscriptionsResponse,
+    },
     msg::{
-        ExecuteMsg as PlaceholderExecuteMsg, QueryMsg::SubscriptionsOf, QueryMsg::ValidateBondToken,
+        ExecuteMsg as PlaceholderExecuteMsg, QueryMsg::PreviewAllocation, QueryMsg::SubscriptionOf,
+        QueryMsg::SubscriptionsOf, QueryMsg::ValidateBondToken,
     },
 };
 
 use crate::{
     error::RouterErr,
     helpers::{
-        Coupon, Cw20BatchBalanceQuery, Cw20BatchBalanceResponse, Cw20MintItem, Cw20TransferItem,
+        verify_merkle_proof, AllocationClaimResponse, AllocationLeaf, Coupon, CouponPoolResponse,
+        Cw20BatchBalanceQuery, Cw20BatchBalanceResponse, Cw20MintItem, Cw20TransferItem, JobKind,
+        JobProgressResponse, MintPayload, MintRequest, RequestProcessedResponse, DEFAULT_LIMIT,
+        MAX_LIMIT,
     },
     msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
-    state::{RouterPlatform, OPERATORS, ROUTER_PLATFORM},
+    state::{
+        BatchJob, CouponPayment, CouponPool, PendingRelease, RouterPlatform, ALLOCATION_CLAIMS,
+        ALLOCATION_CURSORS, ALLOCATION_JOBS, ALLOCATION_POLICIES, ALLOCATION_ROOTS, COUPON_JOBS,
+        COUPON_PAYMENTS, COUPON_POOLS, DISTRIBUTION_JOBS, DISTRIBUTION_RULES, OPERATORS,
+        OPERATOR_PUBLIC_KEYS, PENDING_RELEASES, PENDING_RELEASE_COUNT, PROCESSED_REQUESTS,
+        REDEMPTION_JOBS, ROUTER_PLATFORM,
+    },
 };
 
 // version info for migration info
@@ -61,7 +86,7 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, RouterErr> {
@@ -74,21 +99,121 @@ pub fn execute(
             operators,
             is_operators,
         } => execute::set_operators(deps, info, operators, is_operators),
+        ExecuteMsg::SetOperatorPublicKeys {
+            operators,
+            public_keys,
+        } => execute::set_operator_public_keys(deps, info, operators, public_keys),
         ExecuteMsg::Cw20MintBatch { cw20_mint_items } => {
             execute::cw20_mint_batch(deps, info, cw20_mint_items)
         }
         ExecuteMsg::Cw20TransferBatch {
             cw20_transfer_items,
         } => execute::cw20_transfer_batch(deps, info, cw20_transfer_items),
+        ExecuteMsg::AddDistributionRules {
+            bond_token,
+            investment_rules,
+        } => execute::add_distribution_rules(deps, info, bond_token, investment_rules),
         ExecuteMsg::Distribute {
             bond_token,
             investment_rules,
         } => execute::distribute(deps, info, bond_token, investment_rules),
+        ExecuteMsg::ContinueDistribute { bond_token, limit } => {
+            execute::continue_distribute(deps, info, bond_token, limit)
+        }
+        ExecuteMsg::DistributeByPolicy { bond_token, policy } => {
+            execute::distribute_by_policy(deps, info, bond_token, policy)
+        }
+        ExecuteMsg::CommitAllocationRoot {
+            bond_token,
+            merkle_root,
+        } => execute::commit_allocation_root(deps, info, bond_token, merkle_root),
+        ExecuteMsg::ClaimAllocation {
+            bond_token,
+            amount,
+            proof,
+        } => execute::claim_allocation(deps, info, bond_token, amount, proof),
+        ExecuteMsg::CollectProceeds { bond_token } => {
+            execute::collect_proceeds(deps, info, bond_token)
+        }
+        ExecuteMsg::CloseAllocationRoot { bond_token } => {
+            execute::close_allocation_root(deps, env, info, bond_token)
+        }
+        ExecuteMsg::CancelIssuance {
+            bond_token,
+            refund_fees,
+            limit,
+        } => execute::cancel_issuance(deps, info, bond_token, refund_fees, limit),
         ExecuteMsg::SendCoupon {
             bond_token,
             coupons,
         } => execute::send_coupon(deps, info, bond_token, coupons),
+        ExecuteMsg::PayCoupon { bond_token, period } => {
+            execute::pay_coupon(deps, env, info, bond_token, period)
+        }
+        ExecuteMsg::ContinuePayCoupon { bond_token, limit } => {
+            execute::continue_pay_coupon(deps, info, bond_token, limit)
+        }
+        ExecuteMsg::FundCoupon {
+            bond_token,
+            period,
+            claim_expiry,
+        } => execute::fund_coupon(deps, env, info, bond_token, period, claim_expiry),
+        ExecuteMsg::ClaimCoupon { bond_token, period } => {
+            execute::claim_coupon(deps, env, info, bond_token, period)
+        }
+        ExecuteMsg::SweepCoupon { bond_token, period } => {
+            execute::sweep_coupon(deps, env, info, bond_token, period)
+        }
         ExecuteMsg::Redeem { bond_token } => execute::redeem(deps, info, bond_token),
+        ExecuteMsg::ContinueRedeem { bond_token, limit } => {
+            execute::continue_redeem(deps, info, bond_token, limit)
+        }
+        ExecuteMsg::MintToken {
+            request_id,
+            bond_address,
+            receivers,
+            amounts,
+            operator,
+            signature,
+        } => execute::mint_token(
+            deps,
+            env,
+            MintRequest {
+                request_id,
+                token: bond_address,
+                receivers,
+                amounts,
+                operator,
+                signature,
+            },
+        ),
+        ExecuteMsg::MintCurrency {
+            request_id,
+            currency_address,
+            receivers,
+            amounts,
+            operator,
+            signature,
+        } => execute::mint_currency(
+            deps,
+            env,
+            MintRequest {
+                request_id,
+                token: currency_address,
+                receivers,
+                amounts,
+                operator,
+                signature,
+            },
+        ),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, RouterErr> {
+    match PENDING_RELEASES.may_load(deps.storage, msg.id)? {
+        Some(release) => reply::release_minted_allocation(deps, msg, release),
+        None => Err(RouterErr::UnknownReplyId { id: msg.id }),
     }
 }
 
@@ -98,6 +223,19 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Cw20QueryBalanceBatch {
             cw20_batch_balance_queries,
         } => to_binary(&query::balance_of_batch(deps, cw20_batch_balance_queries)?),
+        QueryMsg::RequestProcessed { request_id } => {
+            to_binary(&query::request_processed(deps, request_id)?)
+        }
+        QueryMsg::CouponPool { bond_token, period } => {
+            to_binary(&query::get_coupon_pool(deps, bond_token, period)?)
+        }
+        QueryMsg::JobProgress { bond_token, kind } => {
+            to_binary(&query::job_progress(deps, bond_token, kind)?)
+        }
+        QueryMsg::AllocationClaim {
+            bond_token,
+            investor,
+        } => to_binary(&query::allocation_claim(deps, bond_token, investor)?),
     }
 }
 
@@ -147,6 +285,30 @@ pub mod execute {
         Ok(Response::new().add_attribute("action", "set_operators"))
     }
 
+    pub fn set_operator_public_keys(
+        deps: DepsMut,
+        info: MessageInfo,
+        operators: Vec<String>,
+        public_keys: Vec<Binary>,
+    ) -> Result<Response, RouterErr> {
+        if info.sender != ROUTER_PLATFORM.load(deps.storage)?.admin {
+            return Err(RouterErr::NotAdmin {
+                account: info.sender.into(),
+            });
+        }
+        if operators.len() != public_keys.len() {
+            return Err(RouterErr::LengthMismatch {});
+        }
+        for (operator, public_key) in operators.iter().zip(public_keys.iter()) {
+            OPERATOR_PUBLIC_KEYS.save(
+                deps.storage,
+                deps.api.addr_validate(operator.as_str())?,
+                public_key,
+            )?;
+        }
+        Ok(Response::new().add_attribute("action", "set_operator_public_keys"))
+    }
+
     pub fn cw20_mint_batch(
         deps: DepsMut,
         info: MessageInfo,
@@ -204,115 +366,293 @@ pub mod execute {
             .add_submessages(messages))
     }
 
+    pub fn add_distribution_rules(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        investment_rules: Vec<InvesmentRule>,
+    ) -> Result<Response, RouterErr> {
+        let (placeholder_addr, bond_addr) = validate_issuer(deps.as_ref(), &info, bond_token)?;
+        check_distribution_not_started(deps.as_ref(), &bond_addr)?;
+        stage_distribution_rules(deps.branch(), &bond_addr, &investment_rules)?;
+
+        // The placeholder only checks the rules handed over with `Distribute`
+        for rule in &investment_rules {
+            let subscription_response: SubscriptionResponse = deps.querier.query_wasm_smart(
+                placeholder_addr.to_string(),
+                &SubscriptionOf {
+                    bond_token: bond_addr.to_string(),
+                    investor: rule.investor.clone(),
+                },
+            )?;
+            if subscription_response.currency_amount.is_zero() {
+                return Err(RouterErr::NotSubscriber {
+                    investor: rule.investor.clone(),
+                });
+            }
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "add_distribution_rules")
+            .add_attribute("rules", investment_rules.len().to_string()))
+    }
+
     pub fn distribute(
-        deps: DepsMut,
+        mut deps: DepsMut,
         info: MessageInfo,
         bond_token: String,
         investment_rules: Vec<InvesmentRule>,
     ) -> Result<Response, RouterErr> {
-        // Query placeholder to validate this bond token
+        let (placeholder_addr, bond_addr) = validate_issuer(deps.as_ref(), &info, bond_token)?;
+
+        // Rules added beforehand are kept with these until every subscription has been paged through
+        check_distribution_not_started(deps.as_ref(), &bond_addr)?;
+        stage_distribution_rules(deps.branch(), &bond_addr, &investment_rules)?;
+        let messages = start_distribution(
+            deps,
+            &placeholder_addr,
+            &bond_addr,
+            info.sender,
+            investment_rules
+                .into_iter()
+                .map(|rule| rule.investor)
+                .collect(),
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "distribute")
+            .add_submessages(messages))
+    }
+
+    // Starts the distribution job once every rule is staged, `investors` are checked by the placeholder
+    fn start_distribution(
+        deps: DepsMut,
+        placeholder_addr: &Addr,
+        bond_token: &Addr,
+        issuer: Addr,
+        investors: Vec<String>,
+    ) -> Result<Vec<SubMsg>, RouterErr> {
+        DISTRIBUTION_JOBS.save(
+            deps.storage,
+            bond_token,
+            &BatchJob {
+                issuer,
+                last_account: None,
+                processed: 0,
+                amount: Uint128::zero(),
+                done: false,
+            },
+        )?;
+
+        // Call bond token to update Distribution phase and lock the escrow for per-investor releases
+        let mut messages: Vec<SubMsg> = vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token.to_string(),
+                msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
+                    phase: Phase::Distribution,
+                }))?,
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: placeholder_addr.to_string(),
+                msg: to_binary(&PlaceholderExecuteMsg::OpenAllocationClaims {
+                    bond_token: bond_token.to_string(),
+                    investors,
+                })?,
+                funds: vec![],
+            }),
+        ];
+        messages.extend(distribute_page(deps, placeholder_addr, bond_token, None)?);
+        Ok(messages)
+    }
+
+    pub fn continue_distribute(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        limit: Option<u32>,
+    ) -> Result<Response, RouterErr> {
         let placeholder_addr = ROUTER_PLATFORM
             .load(deps.storage)?
             .placeholder
-            .ok_or(RouterErr::ContractNotSetup {})
-            .unwrap();
-        let validation_response: BondValidationResponse = deps.querier.query_wasm_smart(
-            placeholder_addr.to_string(),
-            &ValidateBondToken {
-                bond_token: bond_token.clone(),
-            },
-        )?;
-        if !validation_response.validity {
-            return Err(RouterErr::InvalidBondToken { bond_token });
-        }
+            .ok_or(RouterErr::ContractNotSetup {})?;
+        let bond_addr = deps.api.addr_validate(&bond_token)?;
 
-        // Query bond token to validate issuer
-        let issuer_response: IssuerResponse = deps
-            .querier
-            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
-        if issuer_response.issuer != info.sender.to_string() {
-            return Err(RouterErr::NotIssuer {
-                caller: info.sender.to_string(),
-                bond_token,
-            });
-        }
+        // A policy's allocations are staged before its distribution job starts
+        let staging = ALLOCATION_JOBS
+            .may_load(deps.storage, &bond_addr)?
+            .is_some_and(|job| !job.done);
+        let messages = if staging {
+            load_running_job(deps.as_ref(), &ALLOCATION_JOBS, &info.sender, &bond_addr)?;
+            allocation_page(deps, &placeholder_addr, &bond_addr, limit)?
+        } else {
+            load_running_job(deps.as_ref(), &DISTRIBUTION_JOBS, &info.sender, &bond_addr)?;
+            distribute_page(deps, &placeholder_addr, &bond_addr, limit)?
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "continue_distribute")
+            .add_submessages(messages))
+    }
 
-        // Query placeholder to get subscriptions info
-        let response: SubscriptionsResponse = deps.querier.query_wasm_smart(
+    // Mints for and releases the next page of subscriptions, the escrow is closed after the last one
+    fn distribute_page(
+        deps: DepsMut,
+        placeholder_addr: &Addr,
+        bond_token: &Addr,
+        limit: Option<u32>,
+    ) -> Result<Vec<SubMsg>, RouterErr> {
+        let mut job = DISTRIBUTION_JOBS.load(deps.storage, bond_token)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let subscriptions_response: SubscriptionsResponse = deps.querier.query_wasm_smart(
             placeholder_addr.to_string(),
             &SubscriptionsOf {
-                bond_token: bond_token.clone(),
+                bond_token: bond_token.to_string(),
+                start_after: job.last_account.as_ref().map(Addr::to_string),
+                limit: Some(limit),
             },
         )?;
 
-        // Call bond token to update Distribution phase
+        // Leaves nobody claimed before a Merkle root is closed are refunded with their fee,
+        // like investors who claim refunds themselves
+        let refund_fees = ALLOCATION_ROOTS.has(deps.storage, bond_token);
         let mut messages: Vec<SubMsg> = vec![];
-        messages.push(SubMsg::new(WasmMsg::Execute {
-            contract_addr: bond_token.clone(),
-            msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
-                phase: Phase::Distribution,
-            }))?,
-            funds: vec![],
-        }));
+        for subscription in &subscriptions_response.subscriptions {
+            let investor = deps.api.addr_validate(&subscription.investor)?;
+            job.processed += 1;
+            job.last_account = Some(investor.clone());
 
-        // Mint bond tokens to investors based on investment rules
-        for rule in &investment_rules {
-            for subscription in &response.subscriptions {
-                if rule.investor == subscription.investor {
-                    let invested_currency = if subscription.currency_amount > rule.currency_amount {
-                        rule.currency_amount
-                    } else {
-                        subscription.currency_amount
-                    };
-                    if invested_currency > Uint128::zero() {
-                        messages.push(SubMsg::new(WasmMsg::Execute {
-                            contract_addr: bond_token.clone(),
-                            msg: to_binary(&AdditionalExecuteMsg(MintToInvestor {
-                                issuer: info.sender.to_string(),
-                                recipient: rule.investor.to_string(),
-                                currency_amount: invested_currency,
-                            }))?,
-                            funds: vec![],
-                        }));
-                    }
-                }
+            // Refunded subscriptions have nothing left to release, claimed leaves were released
+            // with their claim
+            if subscription.currency_amount.is_zero()
+                || ALLOCATION_CLAIMS.has(deps.storage, (bond_token, &investor))
+            {
+                continue;
             }
+            let allocation = DISTRIBUTION_RULES
+                .may_load(deps.storage, (bond_token, &investor))?
+                .unwrap_or_default();
+            DISTRIBUTION_RULES.remove(deps.storage, (bond_token, &investor));
+            let invested_currency = allocation.min(subscription.currency_amount);
+            if invested_currency.is_zero() {
+                messages.push(release_allocation_msg(
+                    placeholder_addr,
+                    bond_token,
+                    &investor,
+                    allocation,
+                    refund_fees,
+                )?);
+                continue;
+            }
+
+            // The allocation is released in the mint's reply, an investor who can no longer
+            // receive the bond is refunded instead of stalling the page
+            job.amount += invested_currency;
+            let reply_id = PENDING_RELEASE_COUNT
+                .may_load(deps.storage)?
+                .unwrap_or_default()
+                + 1;
+            PENDING_RELEASE_COUNT.save(deps.storage, &reply_id)?;
+            PENDING_RELEASES.save(
+                deps.storage,
+                reply_id,
+                &PendingRelease {
+                    bond_token: bond_token.clone(),
+                    investor: investor.clone(),
+                    allocation,
+                    invested_currency,
+                },
+            )?;
+            messages.push(SubMsg::reply_always(
+                WasmMsg::Execute {
+                    contract_addr: bond_token.to_string(),
+                    msg: to_binary(&AdditionalExecuteMsg(MintToInvestor {
+                        issuer: job.issuer.to_string(),
+                        recipient: investor.to_string(),
+                        currency_amount: invested_currency,
+                    }))?,
+                    funds: vec![],
+                },
+                reply_id,
+            ));
         }
 
-        // Query bond token to get currency
-        let currency_response: CurrencyResponse = deps
-            .querier
-            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetCurrency {}))?;
+        // A short page means every subscription has been released
+        if subscriptions_response.subscriptions.len() < limit as usize {
+            job.done = true;
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: placeholder_addr.to_string(),
+                msg: to_binary(&PlaceholderExecuteMsg::CloseAllocationClaims {
+                    bond_token: bond_token.to_string(),
+                    issuer: job.issuer.to_string(),
+                })?,
+                funds: vec![],
+            }));
+        }
+        DISTRIBUTION_JOBS.save(deps.storage, bond_token, &job)?;
+        Ok(messages)
+    }
 
-        // Call placeholder contract to release currency tokens
-        messages.push(SubMsg::new(WasmMsg::Execute {
+    pub fn release_allocation_msg(
+        placeholder_addr: &Addr,
+        bond_token: &Addr,
+        investor: &Addr,
+        allocation: Uint128,
+        refund_fee: bool,
+    ) -> StdResult<SubMsg> {
+        Ok(SubMsg::new(WasmMsg::Execute {
             contract_addr: placeholder_addr.to_string(),
-            msg: to_binary(&PlaceholderExecuteMsg::ReleaseCurrency {
-                issuer: info.sender.to_string(),
-                bond_token,
-                currency: currency_response.currency,
-                investment_rules,
+            msg: to_binary(&PlaceholderExecuteMsg::ReleaseAllocation {
+                bond_token: bond_token.to_string(),
+                investor: investor.to_string(),
+                currency_amount: allocation,
+                refund_fee,
             })?,
             funds: vec![],
-        }));
+        }))
+    }
 
-        Ok(Response::new()
-            .add_attribute("action", "distribute")
-            .add_submessages(messages))
+    // Only the issuer or operators move a job forward, until it is done
+    fn load_running_job(
+        deps: Deps,
+        jobs: &Map<&Addr, BatchJob>,
+        caller: &Addr,
+        bond_token: &Addr,
+    ) -> Result<BatchJob, RouterErr> {
+        let job = jobs
+            .may_load(deps.storage, bond_token)?
+            .ok_or(RouterErr::JobNotFound {
+                bond_token: bond_token.to_string(),
+            })?;
+        if job.issuer != *caller
+            && !OPERATORS
+                .may_load(deps.storage, caller.clone())?
+                .unwrap_or_default()
+        {
+            return Err(RouterErr::NotIssuer {
+                caller: caller.to_string(),
+                bond_token: bond_token.to_string(),
+            });
+        }
+        if job.done {
+            return Err(RouterErr::JobFinished {
+                bond_token: bond_token.to_string(),
+            });
+        }
+        Ok(job)
     }
 
-    pub fn send_coupon(
-        deps: DepsMut,
-        info: MessageInfo,
+    // Bond must be registered and the caller its issuer, returns the placeholder and the bond
+    fn validate_issuer(
+        deps: Deps,
+        info: &MessageInfo,
         bond_token: String,
-        coupons: Vec<Coupon>,
-    ) -> Result<Response, RouterErr> {
+    ) -> Result<(Addr, Addr), RouterErr> {
         // Query placeholder to validate this bond token
         let placeholder_addr = ROUTER_PLATFORM
             .load(deps.storage)?
             .placeholder
-            .ok_or(RouterErr::ContractNotSetup {})
-            .unwrap();
+            .ok_or(RouterErr::ContractNotSetup {})?;
         let validation_response: BondValidationResponse = deps.querier.query_wasm_smart(
             placeholder_addr.to_string(),
             &ValidateBondToken {
@@ -327,57 +667,143 @@ pub mod execute {
         let issuer_response: IssuerResponse = deps
             .querier
             .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
-        if issuer_response.issuer != info.sender.to_string() {
+        if issuer_response.issuer != info.sender {
             return Err(RouterErr::NotIssuer {
                 caller: info.sender.to_string(),
                 bond_token,
             });
         }
+        Ok((placeholder_addr, deps.api.addr_validate(&bond_token)?))
+    }
 
-        // Query bond token to get currency
-        let currency_response: CurrencyResponse = deps
-            .querier
-            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetCurrency {}))?;
+    // Rules come from the issuer or from a policy, before the distribution job starts
+    fn check_distribution_not_started(deps: Deps, bond_token: &Addr) -> Result<(), RouterErr> {
+        if DISTRIBUTION_JOBS.has(deps.storage, bond_token)
+            || ALLOCATION_JOBS.has(deps.storage, bond_token)
+        {
+            return Err(RouterErr::JobStarted {
+                bond_token: bond_token.to_string(),
+            });
+        }
+        Ok(())
+    }
 
-        // Call bond token to update Coupon phase
-        let mut messages: Vec<SubMsg> = vec![];
-        messages.push(SubMsg::new(WasmMsg::Execute {
-            contract_addr: bond_token,
-            msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
-                phase: Phase::Coupon,
-            }))?,
-            funds: vec![],
-        }));
+    // One rule per investor
+    fn stage_distribution_rules(
+        deps: DepsMut,
+        bond_token: &Addr,
+        investment_rules: &[InvesmentRule],
+    ) -> Result<(), RouterErr> {
+        for rule in investment_rules {
+            let investor = deps.api.addr_validate(&rule.investor)?;
+            if DISTRIBUTION_RULES.has(deps.storage, (bond_token, &investor)) {
+                return Err(RouterErr::DuplicateInvestmentRule {
+                    investor: rule.investor.clone(),
+                });
+            }
+            DISTRIBUTION_RULES.save(
+                deps.storage,
+                (bond_token, &investor),
+                &rule.currency_amount,
+            )?;
+        }
+        Ok(())
+    }
 
-        // Start sending
-        for coupon in coupons {
-            messages.push(SubMsg::new(WasmMsg::Execute {
-                contract_addr: currency_response.clone().currency,
-                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                    owner: info.sender.to_string(),
-                    recipient: coupon.investor,
-                    amount: coupon.currency_amount,
-                })?,
-                funds: vec![],
-            }));
+    pub fn distribute_by_policy(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        policy: AllocationPolicy,
+    ) -> Result<Response, RouterErr> {
+        let (placeholder_addr, bond_addr) =
+            validate_issuer(deps.as_ref(), &info, bond_token.clone())?;
+        check_distribution_not_started(deps.as_ref(), &bond_addr)?;
+
+        // The policy allocates every subscriber, rules added by the issuer would be overridden
+        if DISTRIBUTION_RULES
+            .prefix(&bond_addr)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some()
+        {
+            return Err(RouterErr::DistributionRulesAdded { bond_token });
         }
+        ALLOCATION_JOBS.save(
+            deps.storage,
+            &bond_addr,
+            &BatchJob {
+                issuer: info.sender,
+                last_account: None,
+                processed: 0,
+                amount: Uint128::zero(),
+                done: false,
+            },
+        )?;
+        ALLOCATION_POLICIES.save(deps.storage, &bond_addr, &policy)?;
+        let messages = allocation_page(deps, &placeholder_addr, &bond_addr, None)?;
 
         Ok(Response::new()
-            .add_attribute("action", "send_coupon")
+            .add_attribute("action", "distribute_by_policy")
             .add_submessages(messages))
     }
 
-    pub fn redeem(
+    // Stages the next page of the policy's allocations, the distribution job starts after the last one
+    fn allocation_page(
+        mut deps: DepsMut,
+        placeholder_addr: &Addr,
+        bond_token: &Addr,
+        limit: Option<u32>,
+    ) -> Result<Vec<SubMsg>, RouterErr> {
+        let mut job = ALLOCATION_JOBS.load(deps.storage, bond_token)?;
+        let allocation_response: AllocationResponse = deps.querier.query_wasm_smart(
+            placeholder_addr.to_string(),
+            &PreviewAllocation {
+                bond_token: bond_token.to_string(),
+                policy: ALLOCATION_POLICIES.load(deps.storage, bond_token)?,
+                cursor: ALLOCATION_CURSORS.may_load(deps.storage, bond_token)?,
+                limit,
+            },
+        )?;
+
+        // Subscribers left without an allocation are refunded without a rule
+        job.processed += allocation_response.allocations.len() as u32;
+        job.last_account = allocation_response
+            .allocations
+            .last()
+            .map(|rule| deps.api.addr_validate(&rule.investor))
+            .transpose()?
+            .or(job.last_account);
+        job.amount = allocation_response.total_allocated;
+        let rules: Vec<InvesmentRule> = allocation_response
+            .allocations
+            .into_iter()
+            .filter(|rule| !rule.currency_amount.is_zero())
+            .collect();
+        stage_distribution_rules(deps.branch(), bond_token, &rules)?;
+
+        let Some(cursor) = allocation_response.next else {
+            job.done = true;
+            ALLOCATION_JOBS.save(deps.storage, bond_token, &job)?;
+            ALLOCATION_CURSORS.remove(deps.storage, bond_token);
+            return start_distribution(deps, placeholder_addr, bond_token, job.issuer, vec![]);
+        };
+        ALLOCATION_JOBS.save(deps.storage, bond_token, &job)?;
+        ALLOCATION_CURSORS.save(deps.storage, bond_token, &cursor)?;
+        Ok(vec![])
+    }
+
+    pub fn commit_allocation_root(
         deps: DepsMut,
         info: MessageInfo,
         bond_token: String,
+        merkle_root: Binary,
     ) -> Result<Response, RouterErr> {
         // Query placeholder to validate this bond token
         let placeholder_addr = ROUTER_PLATFORM
             .load(deps.storage)?
             .placeholder
-            .ok_or(RouterErr::ContractNotSetup {})
-            .unwrap();
+            .ok_or(RouterErr::ContractNotSetup {})?;
         let validation_response: BondValidationResponse = deps.querier.query_wasm_smart(
             placeholder_addr.to_string(),
             &ValidateBondToken {
@@ -392,66 +818,1032 @@ pub mod execute {
         let issuer_response: IssuerResponse = deps
             .querier
             .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
-        if issuer_response.issuer != info.sender.to_string() {
+        if issuer_response.issuer != info.sender {
             return Err(RouterErr::NotIssuer {
                 caller: info.sender.to_string(),
                 bond_token,
             });
         }
 
-        // Call bond token to update Redemption phase
-        let mut messages: Vec<SubMsg> = vec![];
-        messages.push(SubMsg::new(WasmMsg::Execute {
-            contract_addr: bond_token.clone(),
-            msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
-                phase: Phase::Redemption,
-            }))?,
-            funds: vec![],
-        }));
-
-        // Query bond token to get currency
-        let currency_response: CurrencyResponse = deps
-            .querier
-            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetCurrency {}))?;
-
-        // Get all bond token holders
-        let holders_response: HoldersResponse = deps
-            .querier
-            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetHolders {}))?;
-        for holder in holders_response.holders {
-            // Return principals to investors
-            messages.push(SubMsg::new(WasmMsg::Execute {
-                contract_addr: currency_response.clone().currency,
-                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                    owner: info.sender.to_string(),
-                    recipient: holder.clone().account,
-                    amount: holder.balance_in_currency,
-                })?,
-                funds: vec![],
-            }));
+        // A root is committed once, later leaves could not be told apart from claimed ones
+        if merkle_root.len() != 32 {
+            return Err(RouterErr::InvalidMerkleRoot {});
+        }
+        let bond_addr = deps.api.addr_validate(&bond_token)?;
+        if ALLOCATION_ROOTS.has(deps.storage, &bond_addr) {
+            return Err(RouterErr::AllocationRootCommitted { bond_token });
+        }
+        ALLOCATION_ROOTS.save(deps.storage, &bond_addr, &merkle_root)?;
 
-            // Burn bond tokens from investors
-            messages.push(SubMsg::new(WasmMsg::Execute {
+        // Move the bond to Distribution and lock the escrow for claims
+        let messages = vec![
+            SubMsg::new(WasmMsg::Execute {
                 contract_addr: bond_token.clone(),
-                msg: to_binary(&AdditionalExecuteMsg(BurnFromHolder {
-                    issuer: info.sender.to_string(),
-                    holder: holder.account,
+                msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
+                    phase: Phase::Distribution,
                 }))?,
                 funds: vec![],
-            }));
-        }
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: placeholder_addr.to_string(),
+                msg: to_binary(&PlaceholderExecuteMsg::OpenAllocationClaims {
+                    bond_token,
+                    investors: vec![],
+                })?,
+                funds: vec![],
+            }),
+        ];
 
         Ok(Response::new()
-            .add_attribute("action", "redeem")
+            .add_attribute("action", "commit_allocation_root")
+            .add_attribute("merkle_root", merkle_root.to_base64())
             .add_submessages(messages))
     }
-}
 
-pub mod query {
-    use super::*;
+    pub fn claim_allocation(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        amount: Uint128,
+        proof: Vec<Binary>,
+    ) -> Result<Response, RouterErr> {
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})?;
+        let bond_addr = deps.api.addr_validate(&bond_token)?;
+        let merkle_root = ALLOCATION_ROOTS
+            .may_load(deps.storage, &bond_addr)?
+            .ok_or_else(|| RouterErr::AllocationRootNotFound {
+                bond_token: bond_token.clone(),
+            })?;
+        if ALLOCATION_CLAIMS.has(deps.storage, (&bond_addr, &info.sender)) {
+            return Err(RouterErr::AllocationClaimed {
+                investor: info.sender.to_string(),
+            });
+        }
 
-    pub fn balance_of_batch(
-        deps: Deps,
+        // The leaf must belong to the sender
+        let leaf = AllocationLeaf {
+            investor: info.sender.to_string(),
+            amount,
+        };
+        if !verify_merkle_proof(leaf.leaf_hash()?, &proof, &merkle_root) {
+            return Err(RouterErr::InvalidMerkleProof {});
+        }
+        ALLOCATION_CLAIMS.save(deps.storage, (&bond_addr, &info.sender), &amount)?;
+
+        // Mint bond tokens for the allocation, capped at what the investor subscribed
+        let mut messages: Vec<SubMsg> = vec![];
+        let subscription: SubscriptionResponse = deps.querier.query_wasm_smart(
+            placeholder_addr.to_string(),
+            &SubscriptionOf {
+                bond_token: bond_token.clone(),
+                investor: info.sender.to_string(),
+            },
+        )?;
+        let invested_currency = amount.min(subscription.currency_amount);
+        if invested_currency > Uint128::zero() {
+            let issuer_response: IssuerResponse = deps
+                .querier
+                .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token.clone(),
+                msg: to_binary(&AdditionalExecuteMsg(MintToInvestor {
+                    issuer: issuer_response.issuer.to_string(),
+                    recipient: info.sender.to_string(),
+                    currency_amount: invested_currency,
+                }))?,
+                funds: vec![],
+            }));
+        }
+
+        // Call placeholder contract to refund the excess and hold the allocation for the issuer
+        messages.push(SubMsg::new(WasmMsg::Execute {
+            contract_addr: placeholder_addr.to_string(),
+            msg: to_binary(&PlaceholderExecuteMsg::ReleaseAllocation {
+                bond_token,
+                investor: info.sender.to_string(),
+                currency_amount: amount,
+                refund_fee: false,
+            })?,
+            funds: vec![],
+        }));
+
+        Ok(Response::new()
+            .add_attribute("action", "claim_allocation")
+            .add_attribute("investor", info.sender)
+            .add_attribute("amount", invested_currency)
+            .add_submessages(messages))
+    }
+
+    pub fn collect_proceeds(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+    ) -> Result<Response, RouterErr> {
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})?;
+
+        // Query bond token to validate issuer
+        let issuer_response: IssuerResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
+        if issuer_response.issuer != info.sender {
+            return Err(RouterErr::NotIssuer {
+                caller: info.sender.to_string(),
+                bond_token,
+            });
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "collect_proceeds")
+            .add_submessage(SubMsg::new(WasmMsg::Execute {
+                contract_addr: placeholder_addr.to_string(),
+                msg: to_binary(&PlaceholderExecuteMsg::CollectProceeds {
+                    bond_token,
+                    issuer: info.sender.to_string(),
+                })?,
+                funds: vec![],
+            })))
+    }
+
+    // Runs a distribution job without rules, so every subscription left unclaimed is refunded
+    pub fn close_allocation_root(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        bond_token: String,
+    ) -> Result<Response, RouterErr> {
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})?;
+        let bond_addr = deps.api.addr_validate(&bond_token)?;
+        if !ALLOCATION_ROOTS.has(deps.storage, &bond_addr) {
+            return Err(RouterErr::AllocationRootNotFound { bond_token });
+        }
+
+        // Only issuer or operators can close the claims
+        let issuer_response: IssuerResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
+        if issuer_response.issuer != info.sender
+            && !OPERATORS
+                .may_load(deps.storage, info.sender.clone())?
+                .unwrap_or_default()
+        {
+            return Err(RouterErr::NotIssuer {
+                caller: info.sender.to_string(),
+                bond_token,
+            });
+        }
+
+        // Investors have until the distribution deadline to claim their leaves
+        let schedule_response: BondScheduleResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(BondSchedule {}))?;
+        if env.block.time < schedule_response.distribution_deadline {
+            return Err(RouterErr::AllocationClaimsOpen {
+                deadline: schedule_response.distribution_deadline,
+            });
+        }
+        if DISTRIBUTION_JOBS.has(deps.storage, &bond_addr) {
+            return Err(RouterErr::JobStarted { bond_token });
+        }
+        DISTRIBUTION_JOBS.save(
+            deps.storage,
+            &bond_addr,
+            &BatchJob {
+                issuer: deps.api.addr_validate(&issuer_response.issuer)?,
+                last_account: None,
+                processed: 0,
+                amount: Uint128::zero(),
+                done: false,
+            },
+        )?;
+        let messages = distribute_page(deps, &placeholder_addr, &bond_addr, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "close_allocation_root")
+            .add_submessages(messages))
+    }
+
+    pub fn cancel_issuance(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        refund_fees: bool,
+        limit: Option<u32>,
+    ) -> Result<Response, RouterErr> {
+        // Query placeholder to validate this bond token
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})?;
+        let validation_response: BondValidationResponse = deps.querier.query_wasm_smart(
+            placeholder_addr.to_string(),
+            &ValidateBondToken {
+                bond_token: bond_token.clone(),
+            },
+        )?;
+        if !validation_response.validity {
+            return Err(RouterErr::InvalidBondToken { bond_token });
+        }
+
+        // Only issuer or operators can cancel a bond
+        let issuer_response: IssuerResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
+        if issuer_response.issuer != info.sender
+            && !OPERATORS
+                .may_load(deps.storage, info.sender.clone())?
+                .unwrap_or_default()
+        {
+            return Err(RouterErr::NotIssuer {
+                caller: info.sender.to_string(),
+                bond_token,
+            });
+        }
+
+        // First call moves the bond to the Cancelled phase, which also blocks subscribe and mint
+        let mut messages: Vec<SubMsg> = vec![];
+        let schedule_response: BondScheduleResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(BondSchedule {}))?;
+        if schedule_response.current_phase != Phase::Cancelled {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token.clone(),
+                msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
+                    phase: Phase::Cancelled,
+                }))?,
+                funds: vec![],
+            }));
+        }
+
+        // Call placeholder contract to refund the next page of subscriptions
+        messages.push(SubMsg::new(WasmMsg::Execute {
+            contract_addr: placeholder_addr.to_string(),
+            msg: to_binary(&PlaceholderExecuteMsg::RefundSubscriptions {
+                bond_token,
+                refund_fees,
+                limit,
+            })?,
+            funds: vec![],
+        }));
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_issuance")
+            .add_submessages(messages))
+    }
+
+    pub fn send_coupon(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        coupons: Vec<Coupon>,
+    ) -> Result<Response, RouterErr> {
+        // Query placeholder to validate this bond token
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})
+            .unwrap();
+        let validation_response: BondValidationResponse = deps.querier.query_wasm_smart(
+            placeholder_addr.to_string(),
+            &ValidateBondToken {
+                bond_token: bond_token.clone(),
+            },
+        )?;
+        if !validation_response.validity {
+            return Err(RouterErr::InvalidBondToken { bond_token });
+        }
+
+        // Query bond token to validate issuer
+        let issuer_response: IssuerResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
+        if issuer_response.issuer != info.sender {
+            return Err(RouterErr::NotIssuer {
+                caller: info.sender.to_string(),
+                bond_token,
+            });
+        }
+
+        // Query bond token to get currency
+        let currency_response: CurrencyResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetCurrency {}))?;
+
+        // Call bond token to update Coupon phase
+        let mut messages: Vec<SubMsg> = vec![];
+        messages.push(SubMsg::new(WasmMsg::Execute {
+            contract_addr: bond_token,
+            msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
+                phase: Phase::Coupon,
+            }))?,
+            funds: vec![],
+        }));
+
+        // Start sending
+        for coupon in coupons {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: currency_response.clone().currency,
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: coupon.investor,
+                    amount: coupon.currency_amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "send_coupon")
+            .add_submessages(messages))
+    }
+
+    pub fn pay_coupon(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        bond_token: String,
+        period: u32,
+    ) -> Result<Response, RouterErr> {
+        let (period_rate, currency) =
+            validate_coupon_period(deps.as_ref(), &info, &bond_token, period)?;
+
+        // One coupon job runs at a time, holders are read at the start of this block
+        let bond_addr = deps.api.addr_validate(&bond_token)?;
+        if COUPON_JOBS
+            .may_load(deps.storage, &bond_addr)?
+            .is_some_and(|job| !job.done)
+        {
+            return Err(RouterErr::JobStarted { bond_token });
+        }
+        COUPON_JOBS.save(
+            deps.storage,
+            &bond_addr,
+            &BatchJob {
+                issuer: info.sender,
+                last_account: None,
+                processed: 0,
+                amount: Uint128::zero(),
+                done: false,
+            },
+        )?;
+        COUPON_PAYMENTS.save(
+            deps.storage,
+            &bond_addr,
+            &CouponPayment {
+                period,
+                record_height: env.block.height,
+                period_rate,
+                currency: deps.api.addr_validate(&currency)?,
+            },
+        )?;
+
+        // Call bond token to update Coupon phase and mark this period as paid
+        let mut messages: Vec<SubMsg> = vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token.clone(),
+                msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
+                    phase: Phase::Coupon,
+                }))?,
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token,
+                msg: to_binary(&AdditionalExecuteMsg(RecordCouponPayment { period }))?,
+                funds: vec![],
+            }),
+        ];
+        messages.extend(coupon_page(deps, &bond_addr, None)?);
+
+        Ok(Response::new()
+            .add_attribute("action", "pay_coupon")
+            .add_attribute("period", period.to_string())
+            .add_submessages(messages))
+    }
+
+    pub fn continue_pay_coupon(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        limit: Option<u32>,
+    ) -> Result<Response, RouterErr> {
+        let bond_addr = deps.api.addr_validate(&bond_token)?;
+        load_running_job(deps.as_ref(), &COUPON_JOBS, &info.sender, &bond_addr)?;
+        let messages = coupon_page(deps, &bond_addr, limit)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "continue_pay_coupon")
+            .add_submessages(messages))
+    }
+
+    // Pays the coupon from issuer's wallet to the next page of holders at the record height
+    fn coupon_page(
+        deps: DepsMut,
+        bond_token: &Addr,
+        limit: Option<u32>,
+    ) -> Result<Vec<SubMsg>, RouterErr> {
+        let mut job = COUPON_JOBS.load(deps.storage, bond_token)?;
+        let payment = COUPON_PAYMENTS.load(deps.storage, bond_token)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+        let holders_response: HoldersResponse = deps.querier.query_wasm_smart(
+            bond_token.to_string(),
+            &AdditionalQueryMsg(HoldersAt {
+                height: payment.record_height,
+                start_after: job.last_account.as_ref().map(Addr::to_string),
+                limit: Some(limit),
+            }),
+        )?;
+
+        let mut messages: Vec<SubMsg> = vec![];
+        for holder in &holders_response.holders {
+            job.processed += 1;
+            job.last_account = Some(deps.api.addr_validate(&holder.account)?);
+
+            let coupon_amount = holder.balance_in_currency * payment.period_rate;
+            if coupon_amount.is_zero() {
+                continue;
+            }
+            job.amount += coupon_amount;
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: payment.currency.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: job.issuer.to_string(),
+                    recipient: holder.account.clone(),
+                    amount: coupon_amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        // A short page means every holder has been paid
+        if holders_response.holders.len() < limit as usize {
+            job.done = true;
+        }
+        COUPON_JOBS.save(deps.storage, bond_token, &job)?;
+        Ok(messages)
+    }
+
+    pub fn fund_coupon(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        bond_token: String,
+        period: u32,
+        claim_expiry: Timestamp,
+    ) -> Result<Response, RouterErr> {
+        let (period_rate, currency) =
+            validate_coupon_period(deps.as_ref(), &info, &bond_token, period)?;
+        if claim_expiry <= env.block.time {
+            return Err(RouterErr::InvalidClaimExpiry { claim_expiry });
+        }
+
+        // Bond token reads entitlements at the start of this block, the coupon on the whole
+        // supply then covers every holder's rounded-down share and the dust is swept back
+        let supply_response: TotalSupplyAtResponse = deps.querier.query_wasm_smart(
+            bond_token.clone(),
+            &AdditionalQueryMsg(TotalSupplyAt {
+                height: env.block.height,
+            }),
+        )?;
+        let total_amount = supply_response.total_supply_in_currency * period_rate;
+        let bond_token_addr = deps.api.addr_validate(bond_token.as_str())?;
+        COUPON_POOLS.save(
+            deps.storage,
+            (&bond_token_addr, period),
+            &CouponPool {
+                issuer: info.sender.clone(),
+                currency: deps.api.addr_validate(currency.as_str())?,
+                total_amount,
+                claimed_amount: Uint128::zero(),
+                claim_expiry,
+                swept: false,
+            },
+        )?;
+
+        // Call bond token to update Coupon phase and record entitlements, then pull the funds from issuer
+        let mut messages: Vec<SubMsg> = vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token.clone(),
+                msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
+                    phase: Phase::Coupon,
+                }))?,
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token,
+                msg: to_binary(&AdditionalExecuteMsg(RecordCouponEntitlements { period }))?,
+                funds: vec![],
+            }),
+        ];
+        if !total_amount.is_zero() {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: currency,
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: total_amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "fund_coupon")
+            .add_attribute("period", period.to_string())
+            .add_attribute("total_amount", total_amount)
+            .add_submessages(messages))
+    }
+
+    pub fn claim_coupon(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        bond_token: String,
+        period: u32,
+    ) -> Result<Response, RouterErr> {
+        let bond_token_addr = deps.api.addr_validate(bond_token.as_str())?;
+        let mut pool = COUPON_POOLS
+            .may_load(deps.storage, (&bond_token_addr, period))?
+            .ok_or(RouterErr::CouponPoolNotFound {
+                bond_token: bond_token.clone(),
+                period,
+            })?;
+        if pool.swept || env.block.time > pool.claim_expiry {
+            return Err(RouterErr::CouponClaimExpired { bond_token, period });
+        }
+
+        // Query bond token to get what the caller is owed
+        let entitlement_response: CouponEntitlementResponse = deps.querier.query_wasm_smart(
+            bond_token.clone(),
+            &AdditionalQueryMsg(CouponEntitlement {
+                period,
+                investor: info.sender.to_string(),
+            }),
+        )?;
+        if entitlement_response.claimed || entitlement_response.amount.is_zero() {
+            return Err(RouterErr::NothingToClaim {
+                investor: info.sender.to_string(),
+                period,
+            });
+        }
+        pool.claimed_amount += entitlement_response.amount;
+        COUPON_POOLS.save(deps.storage, (&bond_token_addr, period), &pool)?;
+
+        // Call bond token to mark the entitlement as claimed, then pay it from the pool
+        let messages: Vec<SubMsg> = vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token,
+                msg: to_binary(&AdditionalExecuteMsg(ClaimCouponEntitlement {
+                    period,
+                    investor: info.sender.to_string(),
+                }))?,
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: pool.currency.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: info.sender.to_string(),
+                    amount: entitlement_response.amount,
+                })?,
+                funds: vec![],
+            }),
+        ];
+
+        Ok(Response::new()
+            .add_attribute("action", "claim_coupon")
+            .add_attribute("period", period.to_string())
+            .add_attribute("amount", entitlement_response.amount)
+            .add_submessages(messages))
+    }
+
+    pub fn sweep_coupon(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        bond_token: String,
+        period: u32,
+    ) -> Result<Response, RouterErr> {
+        let bond_token_addr = deps.api.addr_validate(bond_token.as_str())?;
+        let mut pool = COUPON_POOLS
+            .may_load(deps.storage, (&bond_token_addr, period))?
+            .ok_or(RouterErr::CouponPoolNotFound {
+                bond_token: bond_token.clone(),
+                period,
+            })?;
+        if pool.issuer != info.sender {
+            return Err(RouterErr::NotIssuer {
+                caller: info.sender.to_string(),
+                bond_token,
+            });
+        }
+        if env.block.time <= pool.claim_expiry {
+            return Err(RouterErr::CouponClaimNotExpired {
+                claim_expiry: pool.claim_expiry,
+            });
+        }
+        if pool.swept {
+            return Err(RouterErr::CouponPoolSwept { bond_token, period });
+        }
+        pool.swept = true;
+        COUPON_POOLS.save(deps.storage, (&bond_token_addr, period), &pool)?;
+
+        // Return whatever has not been claimed to issuer
+        let unclaimed_amount = pool.total_amount - pool.claimed_amount;
+        let mut messages: Vec<SubMsg> = vec![];
+        if !unclaimed_amount.is_zero() {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: pool.currency.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: pool.issuer.to_string(),
+                    amount: unclaimed_amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "sweep_coupon")
+            .add_attribute("period", period.to_string())
+            .add_attribute("amount", unclaimed_amount)
+            .add_submessages(messages))
+    }
+
+    pub fn redeem(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+    ) -> Result<Response, RouterErr> {
+        // Query placeholder to validate this bond token
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})
+            .unwrap();
+        let validation_response: BondValidationResponse = deps.querier.query_wasm_smart(
+            placeholder_addr.to_string(),
+            &ValidateBondToken {
+                bond_token: bond_token.clone(),
+            },
+        )?;
+        if !validation_response.validity {
+            return Err(RouterErr::InvalidBondToken { bond_token });
+        }
+
+        // Query bond token to validate issuer
+        let issuer_response: IssuerResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.clone(), &AdditionalQueryMsg(GetIssuer {}))?;
+        if issuer_response.issuer != info.sender {
+            return Err(RouterErr::NotIssuer {
+                caller: info.sender.to_string(),
+                bond_token,
+            });
+        }
+
+        let bond_addr = deps.api.addr_validate(&bond_token)?;
+        if REDEMPTION_JOBS.has(deps.storage, &bond_addr) {
+            return Err(RouterErr::JobStarted { bond_token });
+        }
+        REDEMPTION_JOBS.save(
+            deps.storage,
+            &bond_addr,
+            &BatchJob {
+                issuer: info.sender,
+                last_account: None,
+                processed: 0,
+                amount: Uint128::zero(),
+                done: false,
+            },
+        )?;
+
+        // Call bond token to update Redemption phase before the first page rather than after the
+        // last, so no bond is transferred or minted between pages and every holder is redeemed
+        // once on a fixed balance. The job, not the phase, tells when redemption is done
+        let mut messages: Vec<SubMsg> = vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: bond_token,
+            msg: to_binary(&AdditionalExecuteMsg(UpdatePhase {
+                phase: Phase::Redemption,
+            }))?,
+            funds: vec![],
+        })];
+        messages.extend(redeem_page(deps, &bond_addr, None)?);
+
+        Ok(Response::new()
+            .add_attribute("action", "redeem")
+            .add_submessages(messages))
+    }
+
+    pub fn continue_redeem(
+        deps: DepsMut,
+        info: MessageInfo,
+        bond_token: String,
+        limit: Option<u32>,
+    ) -> Result<Response, RouterErr> {
+        let bond_addr = deps.api.addr_validate(&bond_token)?;
+        load_running_job(deps.as_ref(), &REDEMPTION_JOBS, &info.sender, &bond_addr)?;
+        let messages = redeem_page(deps, &bond_addr, limit)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "continue_redeem")
+            .add_submessages(messages))
+    }
+
+    // Pays principals to and burns the bonds of the next page of holders
+    fn redeem_page(
+        deps: DepsMut,
+        bond_token: &Addr,
+        limit: Option<u32>,
+    ) -> Result<Vec<SubMsg>, RouterErr> {
+        let mut job = REDEMPTION_JOBS.load(deps.storage, bond_token)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+        // Query bond token to get currency and the next page of holders
+        let currency_response: CurrencyResponse = deps
+            .querier
+            .query_wasm_smart(bond_token.to_string(), &AdditionalQueryMsg(GetCurrency {}))?;
+        let holders_response: HoldersResponse = deps.querier.query_wasm_smart(
+            bond_token.to_string(),
+            &AdditionalQueryMsg(Holders {
+                start_after: job.last_account.as_ref().map(Addr::to_string),
+                limit: Some(limit),
+            }),
+        )?;
+
+        let mut messages: Vec<SubMsg> = vec![];
+        for holder in &holders_response.holders {
+            job.processed += 1;
+            job.last_account = Some(deps.api.addr_validate(&holder.account)?);
+
+            // Accounts that hold nothing anymore are skipped
+            if holder.balance_in_currency.is_zero() {
+                continue;
+            }
+            job.amount += holder.balance_in_currency;
+
+            // Return principals to investors
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: currency_response.currency.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: job.issuer.to_string(),
+                    recipient: holder.account.clone(),
+                    amount: holder.balance_in_currency,
+                })?,
+                funds: vec![],
+            }));
+
+            // Burn bond tokens from investors
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: bond_token.to_string(),
+                msg: to_binary(&AdditionalExecuteMsg(BurnFromHolder {
+                    issuer: job.issuer.to_string(),
+                    holder: holder.account.clone(),
+                }))?,
+                funds: vec![],
+            }));
+        }
+
+        // A short page means every holder has been redeemed
+        if holders_response.holders.len() < limit as usize {
+            job.done = true;
+        }
+        REDEMPTION_JOBS.save(deps.storage, bond_token, &job)?;
+        Ok(messages)
+    }
+
+    pub fn mint_token(
+        deps: DepsMut,
+        env: Env,
+        request: MintRequest,
+    ) -> Result<Response, RouterErr> {
+        // Query placeholder to validate this bond token
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})?;
+        let validation_response: BondValidationResponse = deps.querier.query_wasm_smart(
+            placeholder_addr.to_string(),
+            &ValidateBondToken {
+                bond_token: request.token.clone(),
+            },
+        )?;
+        if !validation_response.validity {
+            return Err(RouterErr::InvalidBondToken {
+                bond_token: request.token,
+            });
+        }
+
+        consume_signed_request(deps, &env, "mint_token", &request)?;
+
+        // Bond token wraps its CW20 messages, router is its minter
+        let mut messages: Vec<SubMsg> = vec![];
+        for (receiver, amount) in request.receivers.into_iter().zip(request.amounts) {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: request.token.clone(),
+                msg: to_binary(&BondTokenCw20ExecuteMsg(Cw20BaseExecuteMsg::Mint {
+                    recipient: receiver,
+                    amount,
+                }))?,
+                funds: vec![],
+            }));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "mint_token")
+            .add_attribute("request_id", request.request_id)
+            .add_submessages(messages))
+    }
+
+    pub fn mint_currency(
+        deps: DepsMut,
+        env: Env,
+        request: MintRequest,
+    ) -> Result<Response, RouterErr> {
+        consume_signed_request(deps, &env, "mint_currency", &request)?;
+
+        let mut messages: Vec<SubMsg> = vec![];
+        for (receiver, amount) in request.receivers.into_iter().zip(request.amounts) {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: request.token.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: receiver,
+                    amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "mint_currency")
+            .add_attribute("request_id", request.request_id)
+            .add_submessages(messages))
+    }
+
+    // Validates bond token, issuer and period, returns the period's coupon rate and the bond's currency
+    fn validate_coupon_period(
+        deps: Deps,
+        info: &MessageInfo,
+        bond_token: &str,
+        period: u32,
+    ) -> Result<(Decimal, String), RouterErr> {
+        // Query placeholder to validate this bond token
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})?;
+        let validation_response: BondValidationResponse = deps.querier.query_wasm_smart(
+            placeholder_addr.to_string(),
+            &ValidateBondToken {
+                bond_token: bond_token.to_string(),
+            },
+        )?;
+        if !validation_response.validity {
+            return Err(RouterErr::InvalidBondToken {
+                bond_token: bond_token.to_string(),
+            });
+        }
+
+        // Query bond token to validate issuer
+        let issuer_response: IssuerResponse = deps
+            .querier
+            .query_wasm_smart(bond_token, &AdditionalQueryMsg(GetIssuer {}))?;
+        if issuer_response.issuer != info.sender {
+            return Err(RouterErr::NotIssuer {
+                caller: info.sender.to_string(),
+                bond_token: bond_token.to_string(),
+            });
+        }
+
+        // Query bond token to get the coupon terms of this period
+        let schedule_response: CouponScheduleResponse = deps
+            .querier
+            .query_wasm_smart(bond_token, &AdditionalQueryMsg(CouponSchedule {}))?;
+        let coupon_period = schedule_response
+            .periods
+            .into_iter()
+            .find(|coupon_period| coupon_period.period == period)
+            .ok_or(RouterErr::CouponPeriodNotFound { period })?;
+        if coupon_period.paid {
+            return Err(RouterErr::CouponAlreadyPaid { period });
+        }
+
+        // Query bond token to get currency
+        let currency_response: CurrencyResponse = deps
+            .querier
+            .query_wasm_smart(bond_token, &AdditionalQueryMsg(GetCurrency {}))?;
+
+        Ok((
+            schedule_response.rate * coupon_period.accrual_fraction,
+            currency_response.currency,
+        ))
+    }
+
+    // Verify the operator's signature over the mint payload and mark the request as consumed
+    fn consume_signed_request(
+        deps: DepsMut,
+        env: &Env,
+        action: &str,
+        request: &MintRequest,
+    ) -> Result<(), RouterErr> {
+        if request.receivers.len() != request.amounts.len() {
+            return Err(RouterErr::LengthMismatch {});
+        }
+        if PROCESSED_REQUESTS.has(deps.storage, &request.request_id) {
+            return Err(RouterErr::RequestProcessed {
+                request_id: request.request_id.clone(),
+            });
+        }
+
+        // Only a registered operator can authorize mints
+        let operator_addr = deps.api.addr_validate(&request.operator)?;
+        if !OPERATORS
+            .may_load(deps.storage, operator_addr.clone())?
+            .unwrap_or_default()
+        {
+            return Err(RouterErr::NotOperator {
+                account: request.operator.clone(),
+            });
+        }
+        let public_key = OPERATOR_PUBLIC_KEYS
+            .may_load(deps.storage, operator_addr)?
+            .ok_or(RouterErr::PublicKeyNotRegistered {
+                operator: request.operator.clone(),
+            })?;
+
+        // Rebuild the signed bytes from the message itself
+        let message_hash = MintPayload {
+            chain_id: env.block.chain_id.clone(),
+            router: env.contract.address.to_string(),
+            action: action.to_string(),
+            request_id: request.request_id.clone(),
+            token: request.token.clone(),
+            receivers: request.receivers.clone(),
+            amounts: request.amounts.clone(),
+        }
+        .message_hash()?;
+        if !deps
+            .api
+            .secp256k1_verify(&message_hash, &request.signature, &public_key)?
+        {
+            return Err(RouterErr::InvalidSignature {});
+        }
+
+        PROCESSED_REQUESTS.save(deps.storage, &request.request_id, &true)?;
+        Ok(())
+    }
+}
+
+pub mod reply {
+    use super::*;
+
+    pub fn release_minted_allocation(
+        deps: DepsMut,
+        msg: Reply,
+        release: PendingRelease,
+    ) -> Result<Response, RouterErr> {
+        PENDING_RELEASES.remove(deps.storage, msg.id);
+        let placeholder_addr = ROUTER_PLATFORM
+            .load(deps.storage)?
+            .placeholder
+            .ok_or(RouterErr::ContractNotSetup {})?;
+
+        // A failed mint invests nothing, releasing no allocation refunds the whole subscription
+        let minted = msg.result.is_ok();
+        let allocation = if minted {
+            release.allocation
+        } else {
+            let mut job = DISTRIBUTION_JOBS.load(deps.storage, &release.bond_token)?;
+            job.amount -= release.invested_currency;
+            DISTRIBUTION_JOBS.save(deps.storage, &release.bond_token, &job)?;
+            Uint128::zero()
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "release_minted_allocation")
+            .add_attribute("investor", release.investor.to_string())
+            .add_attribute("minted", minted.to_string())
+            .add_submessage(execute::release_allocation_msg(
+                &placeholder_addr,
+                &release.bond_token,
+                &release.investor,
+                allocation,
+                false,
+            )?))
+    }
+}
+
+pub mod query {
+    use super::*;
+
+    pub fn balance_of_batch(
+        deps: Deps,
         cw20_batch_balances: Vec<Cw20BatchBalanceQuery>,
     ) -> StdResult<Cw20BatchBalanceResponse> {
         let mut balances: Vec<Uint128> = vec![];
@@ -468,4 +1860,64 @@ pub mod query {
         }
         Ok(Cw20BatchBalanceResponse { balances })
     }
+
+    pub fn request_processed(
+        deps: Deps,
+        request_id: String,
+    ) -> StdResult<RequestProcessedResponse> {
+        let processed = PROCESSED_REQUESTS.has(deps.storage, request_id.as_str());
+        Ok(RequestProcessedResponse { processed })
+    }
+
+    pub fn get_coupon_pool(
+        deps: Deps,
+        bond_token: String,
+        period: u32,
+    ) -> StdResult<CouponPoolResponse> {
+        let bond_token = deps.api.addr_validate(bond_token.as_str())?;
+        let pool = COUPON_POOLS.load(deps.storage, (&bond_token, period))?;
+        Ok(CouponPoolResponse {
+            issuer: pool.issuer.to_string(),
+            currency: pool.currency.to_string(),
+            total_amount: pool.total_amount,
+            claimed_amount: pool.claimed_amount,
+            claim_expiry: pool.claim_expiry,
+            swept: pool.swept,
+        })
+    }
+
+    pub fn job_progress(
+        deps: Deps,
+        bond_token: String,
+        kind: JobKind,
+    ) -> StdResult<JobProgressResponse> {
+        let bond_token = deps.api.addr_validate(bond_token.as_str())?;
+        let job = match kind {
+            JobKind::Allocation => ALLOCATION_JOBS.load(deps.storage, &bond_token)?,
+            JobKind::Distribution => DISTRIBUTION_JOBS.load(deps.storage, &bond_token)?,
+            JobKind::Redemption => REDEMPTION_JOBS.load(deps.storage, &bond_token)?,
+            JobKind::Coupon => COUPON_JOBS.load(deps.storage, &bond_token)?,
+        };
+        Ok(JobProgressResponse {
+            issuer: job.issuer.to_string(),
+            last_account: job.last_account.map(String::from),
+            processed: job.processed,
+            amount: job.amount,
+            done: job.done,
+        })
+    }
+
+    pub fn allocation_claim(
+        deps: Deps,
+        bond_token: String,
+        investor: String,
+    ) -> StdResult<AllocationClaimResponse> {
+        let bond_token = deps.api.addr_validate(bond_token.as_str())?;
+        let investor = deps.api.addr_validate(investor.as_str())?;
+        let claim = ALLOCATION_CLAIMS.may_load(deps.storage, (&bond_token, &investor))?;
+        Ok(AllocationClaimResponse {
+            claimed: claim.is_some(),
+            currency_amount: claim.unwrap_or_default(),
+        })
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Timestamp, VerificationError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +6,9 @@ pub enum RouterErr {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Verification(#[from] VerificationError),
+
     #[error("RouterErr: {account:?} is not admin")]
     NotAdmin { account: String },
 
@@ -15,6 +18,9 @@ pub enum RouterErr {
     #[error("RouterErr: lengths mismatch")]
     LengthMismatch {},
 
+    #[error("RouterErr: unknown reply id ({id:?})")]
+    UnknownReplyId { id: u64 },
+
     #[error("RouterErr: not setup")]
     ContractNotSetup {},
 
@@ -23,4 +29,73 @@ pub enum RouterErr {
 
     #[error("RouterErr: caller is not issuer of bond token ({caller:?} {bond_token:?})")]
     NotIssuer { caller: String, bond_token: String },
+
+    #[error("RouterErr: operator has no registered public key ({operator:?})")]
+    PublicKeyNotRegistered { operator: String },
+
+    #[error("RouterErr: invalid signature")]
+    InvalidSignature {},
+
+    #[error("RouterErr: request already processed ({request_id:?})")]
+    RequestProcessed { request_id: String },
+
+    #[error("RouterErr: coupon period not found ({period:?})")]
+    CouponPeriodNotFound { period: u32 },
+
+    #[error("RouterErr: coupon period already paid ({period:?})")]
+    CouponAlreadyPaid { period: u32 },
+
+    #[error("RouterErr: claim expiry must be in the future ({claim_expiry:?})")]
+    InvalidClaimExpiry { claim_expiry: Timestamp },
+
+    #[error("RouterErr: coupon pool not found ({bond_token:?} {period:?})")]
+    CouponPoolNotFound { bond_token: String, period: u32 },
+
+    #[error("RouterErr: coupon claims expired ({bond_token:?} {period:?})")]
+    CouponClaimExpired { bond_token: String, period: u32 },
+
+    #[error("RouterErr: coupon claims still open until {claim_expiry:?}")]
+    CouponClaimNotExpired { claim_expiry: Timestamp },
+
+    #[error("RouterErr: coupon pool already swept ({bond_token:?} {period:?})")]
+    CouponPoolSwept { bond_token: String, period: u32 },
+
+    #[error("RouterErr: job already started ({bond_token:?})")]
+    JobStarted { bond_token: String },
+
+    #[error("RouterErr: no job started ({bond_token:?})")]
+    JobNotFound { bond_token: String },
+
+    #[error("RouterErr: job already finished ({bond_token:?})")]
+    JobFinished { bond_token: String },
+
+    #[error("RouterErr: investor already has a distribution rule ({investor:?})")]
+    DuplicateInvestmentRule { investor: String },
+
+    #[error("RouterErr: distribution rules already added ({bond_token:?})")]
+    DistributionRulesAdded { bond_token: String },
+
+    #[error("RouterErr: not a subscriber of the bond token ({investor:?})")]
+    NotSubscriber { investor: String },
+
+    #[error("RouterErr: allocation root already committed ({bond_token:?})")]
+    AllocationRootCommitted { bond_token: String },
+
+    #[error("RouterErr: no allocation root committed ({bond_token:?})")]
+    AllocationRootNotFound { bond_token: String },
+
+    #[error("RouterErr: Merkle root must be 32 bytes")]
+    InvalidMerkleRoot {},
+
+    #[error("RouterErr: invalid Merkle proof")]
+    InvalidMerkleProof {},
+
+    #[error("RouterErr: allocation claims are open until {deadline:?}")]
+    AllocationClaimsOpen { deadline: Timestamp },
+
+    #[error("RouterErr: allocation already claimed ({investor:?})")]
+    AllocationClaimed { investor: String },
+
+    #[error("RouterErr: nothing to claim ({investor:?} {period:?})")]
+    NothingToClaim { investor: String, period: u32 },
 }
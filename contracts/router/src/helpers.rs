@@ -1,7 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{to_binary, Binary, StdResult, Timestamp, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
 
 pub const NATIVE_DENOM: &str = "flavor";
 
@@ -37,3 +41,106 @@ pub struct Cw20BatchBalanceQuery {
 pub struct Cw20BatchBalanceResponse {
     pub balances: Vec<Uint128>,
 }
+
+// A mint submitted on behalf of an operator, with the operator's signature over its payload
+#[cw_serde]
+pub struct MintRequest {
+    pub request_id: String,
+    pub token: String,
+    pub receivers: Vec<String>,
+    pub amounts: Vec<Uint128>,
+    pub operator: String,
+    pub signature: Binary,
+}
+
+// What an operator signs to authorize a mint submitted by anyone else.
+// The signed hash is sha256 over the JSON encoding of this payload, so the
+// field order here is part of the signing format.
+#[cw_serde]
+pub struct MintPayload {
+    pub chain_id: String,
+    pub router: String,
+    pub action: String, // "mint_token" or "mint_currency"
+    pub request_id: String,
+    pub token: String,
+    pub receivers: Vec<String>,
+    pub amounts: Vec<Uint128>,
+}
+
+impl MintPayload {
+    pub fn message_hash(&self) -> StdResult<Vec<u8>> {
+        Ok(Sha256::digest(to_binary(self)?.as_slice()).to_vec())
+    }
+}
+
+// One leaf of an issuer's allocation tree, hashed as sha256 over its JSON encoding
+#[cw_serde]
+pub struct AllocationLeaf {
+    pub investor: String,
+    pub amount: Uint128,
+}
+
+impl AllocationLeaf {
+    pub fn leaf_hash(&self) -> StdResult<Vec<u8>> {
+        Ok(Sha256::digest(to_binary(self)?.as_slice()).to_vec())
+    }
+}
+
+// Folds the proof into the leaf, each pair is hashed smallest first so siblings need no position
+pub fn verify_merkle_proof(leaf_hash: Vec<u8>, proof: &[Binary], merkle_root: &Binary) -> bool {
+    let mut hash = leaf_hash;
+    for sibling in proof {
+        if sibling.len() != 32 {
+            return false;
+        }
+        let mut hasher = Sha256::new();
+        if hash.as_slice() <= sibling.as_slice() {
+            hasher.update(&hash);
+            hasher.update(sibling.as_slice());
+        } else {
+            hasher.update(sibling.as_slice());
+            hasher.update(&hash);
+        }
+        hash = hasher.finalize().to_vec();
+    }
+    hash.as_slice() == merkle_root.as_slice()
+}
+
+#[cw_serde]
+pub struct RequestProcessedResponse {
+    pub processed: bool,
+}
+
+#[cw_serde]
+pub struct CouponPoolResponse {
+    pub issuer: String,
+    pub currency: String,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+    pub claim_expiry: Timestamp,
+    pub swept: bool,
+}
+
+#[cw_serde]
+pub struct AllocationClaimResponse {
+    pub claimed: bool,
+    pub currency_amount: Uint128, // amount of the claimed leaf
+}
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum JobKind {
+    Allocation, // allocations of a policy, staged before its distribution job starts
+    Distribution,
+    Redemption,
+    Coupon, // the latest coupon job
+}
+
+#[cw_serde]
+pub struct JobProgressResponse {
+    pub issuer: String,
+    pub last_account: Option<String>,
+    pub processed: u32,
+    pub amount: Uint128,
+    pub done: bool,
+}
@@ -1,10 +1,13 @@
 use std::vec;
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use placeholder::helpers::InvesmentRule;
+use cosmwasm_std::{Binary, Timestamp, Uint128};
+use placeholder::helpers::{AllocationPolicy, InvesmentRule};
 
 use crate::helpers::{
-    Coupon, Cw20BatchBalanceQuery, Cw20BatchBalanceResponse, Cw20MintItem, Cw20TransferItem,
+    AllocationClaimResponse, Coupon, CouponPoolResponse, Cw20BatchBalanceQuery,
+    Cw20BatchBalanceResponse, Cw20MintItem, Cw20TransferItem, JobKind, JobProgressResponse,
+    RequestProcessedResponse,
 };
 
 #[cw_serde]
@@ -20,23 +23,126 @@ pub enum ExecuteMsg {
         operators: Vec<String>,
         is_operators: Vec<bool>,
     },
+    SetOperatorPublicKeys {
+        operators: Vec<String>,
+        public_keys: Vec<Binary>,
+    },
     Cw20MintBatch {
         cw20_mint_items: Vec<Cw20MintItem>,
     },
     Cw20TransferBatch {
         cw20_transfer_items: Vec<Cw20TransferItem>,
     },
+    /// Issuer stages rules ahead of `Distribute` when they do not fit in one message,
+    /// only one rule per investor
+    AddDistributionRules {
+        bond_token: String,
+        investment_rules: Vec<InvesmentRule>,
+    },
+    /// Starts a distribution job with these and the staged rules and handles its first page of subscriptions,
+    /// the escrow is released to the issuer once the last page is done.
+    /// Investors the bond can no longer be minted to are refunded
     Distribute {
         bond_token: String,
         investment_rules: Vec<InvesmentRule>,
     },
+    /// Issuer or operator handles the next page of an allocation or distribution job
+    ContinueDistribute {
+        bond_token: String,
+        limit: Option<u32>,
+    },
+    /// Same as `Distribute` with the rules the placeholder computes from `policy`, see its
+    /// `PreviewAllocation` query. They are staged a page per call, continued with
+    /// `ContinueDistribute`, and the distribution job starts after the last page
+    DistributeByPolicy {
+        bond_token: String,
+        policy: AllocationPolicy,
+    },
+    /// Issuer commits the sha256 Merkle root of `AllocationLeaf`s instead of listing every rule,
+    /// which moves the bond to the Distribution phase
+    CommitAllocationRoot {
+        bond_token: String,
+        merkle_root: Binary,
+    },
+    /// Investor mints bond tokens for a leaf of the committed tree, the excess is refunded
+    ClaimAllocation {
+        bond_token: String,
+        amount: Uint128,
+        proof: Vec<Binary>,
+    },
+    /// Issuer collects the currency of every allocation claimed so far
+    CollectProceeds {
+        bond_token: String,
+    },
+    /// Issuer or operator refunds the subscriptions left unclaimed once the distribution deadline
+    /// has passed, continued with `ContinueDistribute`, the escrow is closed after the last page
+    CloseAllocationRoot {
+        bond_token: String,
+    },
+    /// Issuer or operator abandons a bond before distribution, refunding `limit` subscriptions
+    /// per call until none is left, fees are refunded too when `refund_fees` is set
+    CancelIssuance {
+        bond_token: String,
+        refund_fees: bool,
+        limit: Option<u32>,
+    },
     SendCoupon {
         bond_token: String,
         coupons: Vec<Coupon>,
     },
+    /// Starts a coupon job that pays every holder `balance_in_currency * rate * accrual_fraction`
+    /// on its balance at the start of this block, and handles its first page of holders
+    PayCoupon {
+        bond_token: String,
+        period: u32,
+    },
+    /// Issuer or operator handles the next page of a coupon job
+    ContinuePayCoupon {
+        bond_token: String,
+        limit: Option<u32>,
+    },
+    /// Issuer deposits the whole coupon of a period into the router, holders then claim their share
+    FundCoupon {
+        bond_token: String,
+        period: u32,
+        claim_expiry: Timestamp,
+    },
+    ClaimCoupon {
+        bond_token: String,
+        period: u32,
+    },
+    /// Issuer takes back unclaimed coupons once the claim expiry has passed
+    SweepCoupon {
+        bond_token: String,
+        period: u32,
+    },
+    /// Starts a redemption job and handles its first page of holders, the bond enters the
+    /// Redemption phase at once so holders are frozen until the job is done
     Redeem {
         bond_token: String,
     },
+    /// Issuer or operator handles the next page of a redemption job
+    ContinueRedeem {
+        bond_token: String,
+        limit: Option<u32>,
+    },
+    /// Anyone can submit, the operator's signature over `MintPayload` is what authorizes it
+    MintToken {
+        request_id: String,
+        bond_address: String,
+        receivers: Vec<String>,
+        amounts: Vec<Uint128>,
+        operator: String,
+        signature: Binary,
+    },
+    MintCurrency {
+        request_id: String,
+        currency_address: String,
+        receivers: Vec<String>,
+        amounts: Vec<Uint128>,
+        operator: String,
+        signature: Binary,
+    },
 }
 
 #[cw_serde]
@@ -46,4 +152,19 @@ pub enum QueryMsg {
     Cw20QueryBalanceBatch {
         cw20_batch_balance_queries: Vec<Cw20BatchBalanceQuery>,
     },
+
+    #[returns(RequestProcessedResponse)]
+    RequestProcessed { request_id: String },
+
+    #[returns(CouponPoolResponse)]
+    CouponPool { bond_token: String, period: u32 },
+
+    #[returns(JobProgressResponse)]
+    JobProgress { bond_token: String, kind: JobKind },
+
+    #[returns(AllocationClaimResponse)]
+    AllocationClaim {
+        bond_token: String,
+        investor: String,
+    },
 }
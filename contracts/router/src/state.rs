@@ -1,5 +1,6 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary, Decimal, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
+use placeholder::helpers::{AllocationCursor, AllocationPolicy};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,5 +11,59 @@ pub struct RouterPlatform {
     pub factory: Option<Addr>,
 }
 
+// Coupon funds deposited by the issuer for one period, claimed by holders
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CouponPool {
+    pub issuer: Addr,
+    pub currency: Addr,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+    pub claim_expiry: Timestamp, // issuer can sweep what is left after this
+    pub swept: bool,
+}
+
+// A distribution or redemption handled one page of accounts per call, in account order
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BatchJob {
+    pub issuer: Addr,
+    pub last_account: Option<Addr>, // next page starts after this account
+    pub processed: u32,
+    pub amount: Uint128, // currency invested in or paid to the processed accounts
+    pub done: bool,
+}
+
+// Coupon period a coupon job pays, on the holders' balances at the record height
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CouponPayment {
+    pub period: u32,
+    pub record_height: u64,
+    pub period_rate: Decimal,
+    pub currency: Addr,
+}
+
+// Allocation released once the mint for it replies, nothing is invested when the mint failed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingRelease {
+    pub bond_token: Addr,
+    pub investor: Addr,
+    pub allocation: Uint128,
+    pub invested_currency: Uint128, // minted for, taken off the job's amount if the mint failed
+}
+
 pub const ROUTER_PLATFORM: Item<RouterPlatform> = Item::new("router_platform");
 pub const OPERATORS: Map<Addr, bool> = Map::new("operators");
+pub const OPERATOR_PUBLIC_KEYS: Map<Addr, Binary> = Map::new("operator_public_keys"); // secp256k1 keys used to verify operators' signatures
+pub const PROCESSED_REQUESTS: Map<&str, bool> = Map::new("processed_requests");
+pub const ALLOCATION_ROOTS: Map<&Addr, Binary> = Map::new("allocation_roots"); // maps from a bond token to the Merkle root of its allocations
+pub const ALLOCATION_CLAIMS: Map<(&Addr, &Addr), Uint128> = Map::new("allocation_claims"); // maps from (bond token, investor) to the claimed leaf amount
+pub const COUPON_POOLS: Map<(&Addr, u32), CouponPool> = Map::new("coupon_pools"); // maps from (bond token, coupon period) to its pool
+pub const ALLOCATION_JOBS: Map<&Addr, BatchJob> = Map::new("allocation_jobs"); // maps from a bond token to the job staging its policy's allocations as distribution rules
+pub const ALLOCATION_POLICIES: Map<&Addr, AllocationPolicy> = Map::new("allocation_policies"); // maps from a bond token to the policy its allocation job applies
+pub const ALLOCATION_CURSORS: Map<&Addr, AllocationCursor> = Map::new("allocation_cursors"); // maps from a bond token to where its allocation job stopped
+pub const DISTRIBUTION_JOBS: Map<&Addr, BatchJob> = Map::new("distribution_jobs");
+pub const DISTRIBUTION_RULES: Map<(&Addr, &Addr), Uint128> = Map::new("distribution_rules"); // maps from (bond token, investor) to the allocated currency
+pub const REDEMPTION_JOBS: Map<&Addr, BatchJob> = Map::new("redemption_jobs");
+pub const COUPON_JOBS: Map<&Addr, BatchJob> = Map::new("coupon_jobs"); // maps from a bond token to its latest coupon job
+pub const COUPON_PAYMENTS: Map<&Addr, CouponPayment> = Map::new("coupon_payments"); // maps from a bond token to what its latest coupon job pays
+pub const PENDING_RELEASE_COUNT: Item<u64> = Item::new("pending_release_count");
+pub const PENDING_RELEASES: Map<u64, PendingRelease> = Map::new("pending_releases"); // maps from a mint's reply id to its release
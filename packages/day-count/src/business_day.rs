@@ -0,0 +1,136 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Timestamp;
+
+use crate::date::{Date, SECONDS_PER_DAY};
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum BusinessDayConvention {
+    // Keep the date as it is
+    Unadjusted,
+    // Roll forward to the next business day
+    Following,
+    // Roll forward, unless that crosses into the next month, then roll backward
+    ModifiedFollowing,
+}
+
+impl BusinessDayConvention {
+    // Adjust `date` so that it falls on a business day.
+    // Weekends and any day listed in `holidays` (compared by UTC day) are not business days.
+    // The time of day of `date` is kept.
+    pub fn adjust(&self, date: Timestamp, holidays: &[Timestamp]) -> Timestamp {
+        let day = date.seconds() / SECONDS_PER_DAY;
+        let adjusted_day = match self {
+            BusinessDayConvention::Unadjusted => day,
+            BusinessDayConvention::Following => roll_forward(day, holidays),
+            BusinessDayConvention::ModifiedFollowing => {
+                let following = roll_forward(day, holidays);
+                if Date::from_days_since_epoch(following).month
+                    == Date::from_days_since_epoch(day).month
+                {
+                    following
+                } else {
+                    roll_backward(day, holidays)
+                }
+            }
+        };
+        if adjusted_day >= day {
+            date.plus_seconds((adjusted_day - day) * SECONDS_PER_DAY)
+        } else {
+            date.minus_seconds((day - adjusted_day) * SECONDS_PER_DAY)
+        }
+    }
+}
+
+pub fn is_business_day(date: Timestamp, holidays: &[Timestamp]) -> bool {
+    is_business_day_number(date.seconds() / SECONDS_PER_DAY, holidays)
+}
+
+fn is_business_day_number(day: u64, holidays: &[Timestamp]) -> bool {
+    !Date::from_days_since_epoch(day).is_weekend()
+        && !holidays
+            .iter()
+            .any(|holiday| holiday.seconds() / SECONDS_PER_DAY == day)
+}
+
+fn roll_forward(mut day: u64, holidays: &[Timestamp]) -> u64 {
+    while !is_business_day_number(day, holidays) {
+        day += 1;
+    }
+    day
+}
+
+fn roll_backward(mut day: u64, holidays: &[Timestamp]) -> u64 {
+    while !is_business_day_number(day, holidays) {
+        day -= 1;
+    }
+    day
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::date_to_timestamp;
+
+    fn ts(year: u32, month: u32, day: u32) -> Timestamp {
+        date_to_timestamp(Date::new(year, month, day))
+    }
+
+    #[test]
+    fn unadjusted() {
+        assert_eq!(
+            BusinessDayConvention::Unadjusted.adjust(ts(2023, 12, 30), &[]),
+            ts(2023, 12, 30)
+        );
+    }
+
+    #[test]
+    fn following() {
+        let convention = BusinessDayConvention::Following;
+        // Business days are kept
+        assert_eq!(convention.adjust(ts(2023, 12, 29), &[]), ts(2023, 12, 29));
+        // Saturday -> Monday
+        assert_eq!(convention.adjust(ts(2023, 12, 30), &[]), ts(2024, 1, 1));
+        // Saturday -> Tuesday when Monday is a holiday
+        assert_eq!(
+            convention.adjust(ts(2023, 12, 30), &[ts(2024, 1, 1)]),
+            ts(2024, 1, 2)
+        );
+        // Holidays match by day, whatever their time of day
+        assert_eq!(
+            convention.adjust(ts(2024, 1, 1), &[ts(2024, 1, 1).plus_seconds(3600)]),
+            ts(2024, 1, 2)
+        );
+        // Time of day is kept
+        assert_eq!(
+            convention.adjust(ts(2023, 12, 30).plus_seconds(100), &[]),
+            ts(2024, 1, 1).plus_seconds(100)
+        );
+    }
+
+    #[test]
+    fn modified_following() {
+        let convention = BusinessDayConvention::ModifiedFollowing;
+        // Saturday -> Monday in the same month
+        assert_eq!(convention.adjust(ts(2023, 9, 23), &[]), ts(2023, 9, 25));
+        // Saturday 30th -> Friday 29th rather than Monday 2nd of the next month
+        assert_eq!(convention.adjust(ts(2023, 9, 30), &[]), ts(2023, 9, 29));
+        // Holiday on Friday 29th as well -> Thursday 28th
+        assert_eq!(
+            convention.adjust(ts(2023, 9, 30), &[ts(2023, 9, 29)]),
+            ts(2023, 9, 28)
+        );
+        // Following still applies when it stays within the month
+        assert_eq!(
+            convention.adjust(ts(2023, 12, 25), &[ts(2023, 12, 25)]),
+            ts(2023, 12, 26)
+        );
+    }
+
+    #[test]
+    fn business_days() {
+        assert!(is_business_day(ts(2024, 1, 2), &[]));
+        assert!(!is_business_day(ts(2024, 1, 6), &[]));
+        assert!(!is_business_day(ts(2024, 1, 2), &[ts(2024, 1, 2)]));
+    }
+}
@@ -0,0 +1,107 @@
+use cosmwasm_std::Timestamp;
+
+pub const SECONDS_PER_DAY: u64 = 24 * 3600;
+
+// Civil (proleptic Gregorian) date, UTC
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u32,
+    pub month: u32, // 1 -> 12
+    pub day: u32,   // 1 -> 31
+}
+
+impl Date {
+    pub fn new(year: u32, month: u32, day: u32) -> Self {
+        Date { year, month, day }
+    }
+
+    // Days since 1970-01-01, based on Howard Hinnant's `days_from_civil`
+    pub fn days_since_epoch(&self) -> u64 {
+        let year = u64::from(if self.month <= 2 {
+            self.year - 1
+        } else {
+            self.year
+        });
+        let era = year / 400;
+        let year_of_era = year - era * 400;
+        let shifted_month = u64::from((self.month + 9) % 12); // March = 0
+        let day_of_year = (153 * shifted_month + 2) / 5 + u64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    // Inverse of `days_since_epoch`, based on Howard Hinnant's `civil_from_days`
+    pub fn from_days_since_epoch(days: u64) -> Self {
+        let days = days + 719468;
+        let era = days / 146097;
+        let day_of_era = days - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+        Date {
+            year: year as u32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    // 0 = Monday -> 6 = Sunday, 1970-01-01 was a Thursday
+    pub fn weekday(&self) -> u32 {
+        ((self.days_since_epoch() + 3) % 7) as u32
+    }
+
+    pub fn is_weekend(&self) -> bool {
+        self.weekday() >= 5
+    }
+}
+
+pub fn timestamp_to_date(timestamp: Timestamp) -> Date {
+    Date::from_days_since_epoch(timestamp.seconds() / SECONDS_PER_DAY)
+}
+
+// Midnight UTC of the given date
+pub fn date_to_timestamp(date: Date) -> Timestamp {
+    Timestamp::from_seconds(date.days_since_epoch() * SECONDS_PER_DAY)
+}
+
+// Actual number of calendar days from `start` to `end`, 0 if `end` is before `start`
+pub fn actual_days(start: Timestamp, end: Timestamp) -> u64 {
+    (end.seconds() / SECONDS_PER_DAY).saturating_sub(start.seconds() / SECONDS_PER_DAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_dates() {
+        assert_eq!(Date::new(1970, 1, 1).days_since_epoch(), 0);
+        assert_eq!(Date::new(2000, 3, 1).days_since_epoch(), 11017);
+        assert_eq!(Date::new(2024, 2, 29).days_since_epoch(), 19782);
+        assert_eq!(Date::from_days_since_epoch(19782), Date::new(2024, 2, 29));
+        assert_eq!(
+            timestamp_to_date(Timestamp::from_seconds(1_571_797_419)),
+            Date::new(2019, 10, 23)
+        );
+        for days in 0..200_000 {
+            assert_eq!(Date::from_days_since_epoch(days).days_since_epoch(), days);
+        }
+    }
+
+    #[test]
+    fn finds_weekdays() {
+        assert_eq!(Date::new(1970, 1, 1).weekday(), 3);
+        assert_eq!(Date::new(2024, 1, 1).weekday(), 0);
+        assert!(Date::new(2023, 12, 30).is_weekend());
+        assert!(Date::new(2023, 12, 31).is_weekend());
+        assert!(!Date::new(2023, 12, 29).is_weekend());
+    }
+}
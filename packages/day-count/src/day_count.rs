@@ -0,0 +1,218 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal, Timestamp};
+
+use crate::date::{actual_days, timestamp_to_date, Date};
+
+// Regular coupon period used as the reference for ACT/ACT-ICMA
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccrualPeriod {
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub frequency: u32, // coupons per year
+}
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum DayCount {
+    // 30/360 bond basis (ISDA 2006 4.16(f))
+    Thirty360,
+    // 30E/360 eurobond basis (ISDA 2006 4.16(g))
+    ThirtyE360,
+    // Actual/360
+    Act360,
+    // Actual/365 fixed
+    Act365Fixed,
+    // Actual/Actual ICMA, relative to the surrounding regular coupon period
+    ActActIcma,
+}
+
+impl DayCount {
+    // Fraction of a year accrued from `start` to `end`.
+    // `period` is only used by ACT/ACT-ICMA and is the regular coupon period containing the accrual.
+    pub fn year_fraction(
+        &self,
+        start: Timestamp,
+        end: Timestamp,
+        period: AccrualPeriod,
+    ) -> Decimal {
+        if end <= start {
+            return Decimal::zero();
+        }
+        match self {
+            DayCount::Thirty360 => {
+                let (start, end) = (timestamp_to_date(start), timestamp_to_date(end));
+                let start_day = start.day.min(30);
+                let end_day = if end.day == 31 && start_day == 30 {
+                    30
+                } else {
+                    end.day
+                };
+                Decimal::from_ratio(thirty_360_days(start, start_day, end, end_day), 360u64)
+            }
+            DayCount::ThirtyE360 => {
+                let (start, end) = (timestamp_to_date(start), timestamp_to_date(end));
+                Decimal::from_ratio(
+                    thirty_360_days(start, start.day.min(30), end, end.day.min(30)),
+                    360u64,
+                )
+            }
+            DayCount::Act360 => Decimal::from_ratio(actual_days(start, end), 360u64),
+            DayCount::Act365Fixed => Decimal::from_ratio(actual_days(start, end), 365u64),
+            DayCount::ActActIcma => {
+                let period_days = actual_days(period.start, period.end);
+                if period_days == 0 || period.frequency == 0 {
+                    return Decimal::zero();
+                }
+                Decimal::from_ratio(
+                    actual_days(start, end),
+                    period_days * u64::from(period.frequency),
+                )
+            }
+        }
+    }
+}
+
+// 360 * (Y2 - Y1) + 30 * (M2 - M1) + (D2 - D1), with D1 and D2 already adjusted
+fn thirty_360_days(start: Date, start_day: u32, end: Date, end_day: u32) -> u64 {
+    let days = 360 * (i64::from(end.year) - i64::from(start.year))
+        + 30 * (i64::from(end.month) - i64::from(start.month))
+        + (i64::from(end_day) - i64::from(start_day));
+    days.max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::date_to_timestamp;
+
+    fn ts(year: u32, month: u32, day: u32) -> Timestamp {
+        date_to_timestamp(Date::new(year, month, day))
+    }
+
+    fn fraction(day_count: DayCount, start: Timestamp, end: Timestamp) -> Decimal {
+        day_count.year_fraction(
+            start,
+            end,
+            AccrualPeriod {
+                start,
+                end,
+                frequency: 2,
+            },
+        )
+    }
+
+    #[test]
+    fn thirty_360() {
+        let day_count = DayCount::Thirty360;
+        assert_eq!(
+            fraction(day_count, ts(2007, 1, 15), ts(2007, 7, 15)),
+            Decimal::percent(50)
+        );
+        assert_eq!(
+            fraction(day_count, ts(2019, 10, 29), ts(2020, 4, 28)),
+            Decimal::from_ratio(179u64, 360u64)
+        );
+        // D2 = 31 only rolls back when D1 >= 30
+        assert_eq!(
+            fraction(day_count, ts(2007, 9, 30), ts(2008, 3, 31)),
+            Decimal::percent(50)
+        );
+        assert_eq!(
+            fraction(day_count, ts(2008, 2, 29), ts(2008, 8, 31)),
+            Decimal::from_ratio(182u64, 360u64)
+        );
+        assert_eq!(
+            fraction(day_count, ts(2007, 1, 31), ts(2007, 2, 28)),
+            Decimal::from_ratio(28u64, 360u64)
+        );
+    }
+
+    #[test]
+    fn thirty_e_360() {
+        let day_count = DayCount::ThirtyE360;
+        assert_eq!(
+            fraction(day_count, ts(2007, 9, 30), ts(2008, 3, 31)),
+            Decimal::percent(50)
+        );
+        assert_eq!(
+            fraction(day_count, ts(2008, 2, 29), ts(2008, 8, 31)),
+            Decimal::from_ratio(181u64, 360u64)
+        );
+        assert_eq!(
+            fraction(day_count, ts(2006, 8, 31), ts(2007, 2, 28)),
+            Decimal::from_ratio(178u64, 360u64)
+        );
+    }
+
+    #[test]
+    fn actual_360_and_365_fixed() {
+        assert_eq!(
+            fraction(DayCount::Act360, ts(2007, 1, 15), ts(2007, 7, 15)),
+            Decimal::from_ratio(181u64, 360u64)
+        );
+        assert_eq!(
+            fraction(DayCount::Act360, ts(2008, 2, 1), ts(2008, 3, 1)),
+            Decimal::from_ratio(29u64, 360u64)
+        );
+        assert_eq!(
+            fraction(DayCount::Act365Fixed, ts(2007, 1, 15), ts(2007, 7, 15)),
+            Decimal::from_ratio(181u64, 365u64)
+        );
+        assert_eq!(
+            fraction(DayCount::Act365Fixed, ts(2023, 1, 1), ts(2024, 1, 1)),
+            Decimal::one()
+        );
+        assert_eq!(
+            fraction(DayCount::Act365Fixed, ts(2024, 1, 1), ts(2025, 1, 1)),
+            Decimal::from_ratio(366u64, 365u64)
+        );
+    }
+
+    #[test]
+    fn actual_actual_icma() {
+        let period = AccrualPeriod {
+            start: ts(2003, 11, 1),
+            end: ts(2004, 5, 1),
+            frequency: 2,
+        };
+        let day_count = DayCount::ActActIcma;
+        // A full regular period is always 1 / frequency
+        assert_eq!(
+            day_count.year_fraction(period.start, period.end, period),
+            Decimal::percent(50)
+        );
+        assert_eq!(
+            day_count.year_fraction(period.start, ts(2004, 2, 1), period),
+            Decimal::from_ratio(92u64, 364u64)
+        );
+        let annual = AccrualPeriod {
+            start: ts(1999, 2, 1),
+            end: ts(2000, 2, 1),
+            frequency: 1,
+        };
+        assert_eq!(
+            day_count.year_fraction(ts(1999, 2, 1), ts(1999, 7, 1), annual),
+            Decimal::from_ratio(150u64, 365u64)
+        );
+    }
+
+    #[test]
+    fn empty_or_reversed_range_is_zero() {
+        for day_count in [
+            DayCount::Thirty360,
+            DayCount::ThirtyE360,
+            DayCount::Act360,
+            DayCount::Act365Fixed,
+            DayCount::ActActIcma,
+        ] {
+            assert_eq!(
+                fraction(day_count, ts(2020, 1, 1), ts(2020, 1, 1)),
+                Decimal::zero()
+            );
+            assert_eq!(
+                fraction(day_count, ts(2020, 2, 1), ts(2020, 1, 1)),
+                Decimal::zero()
+            );
+        }
+    }
+}
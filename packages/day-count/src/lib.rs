@@ -0,0 +1,14 @@
+//! Day-count fractions and business-day adjustment for bond accruals.
+//!
+//! Everything works on `cosmwasm_std::Timestamp` at day precision (UTC) and
+//! returns `Decimal` fractions. The calculations only use `core` integer
+//! arithmetic (no floating point, no allocation, no clock), so they are
+//! deterministic inside the wasm VM.
+
+pub mod business_day;
+pub mod date;
+pub mod day_count;
+
+pub use crate::business_day::{is_business_day, BusinessDayConvention};
+pub use crate::date::{date_to_timestamp, timestamp_to_date, Date};
+pub use crate::day_count::{AccrualPeriod, DayCount};
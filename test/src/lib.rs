@@ -1,18 +1,31 @@
 #[cfg(test)]
 mod tests {
     use bond_token::{
-        helpers::{Denomination, FunctionSetup, RedemptionAmountResponse},
+        helpers::{
+            BondTerms, CancellationFeePolicy, CouponTerms, Denomination, FunctionSetup,
+            IdentityCheck, RedemptionAmountResponse, SubscriptionLimits,
+        },
         msg::{
             AdditionalExecuteMsg, AdditionalQueryMsg, ExecuteMsg as BondTokenExecuteMsg,
             QueryMsg as BondTokenQueryMsg,
         },
+        BondTokenErr,
     };
-    use cosmwasm_std::{coins, Addr, Uint128, Uint64};
+    use cosmwasm_std::{coins, Addr, Decimal, Timestamp, Uint128, Uint64};
     use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
     use cw20_base::msg::QueryMsg as Cw20BaseQueryMsg;
     use cw_multi_test::{App, AppBuilder, ContractWrapper, Executor};
-    use factory::msg::ExecuteMsg as FactoryExecuteMsg;
-    use placeholder::{helpers::InvesmentRule, msg::ExecuteMsg as PlaceholderExecuteMsg};
+    use day_count::{BusinessDayConvention, DayCount};
+    use factory::{
+        helpers::{BondTokensResponse, CurrenciesResponse},
+        msg::{
+            ExecuteMsg as FactoryExecuteMsg, InstantiateBondTokenMsg, QueryMsg as FactoryQueryMsg,
+        },
+    };
+    use placeholder::{
+        helpers::{AllocationFeePolicy, BondValidationResponse, FeeRecipient, InvesmentRule},
+        msg::{ExecuteMsg as PlaceholderExecuteMsg, QueryMsg as PlaceholderQueryMsg},
+    };
     use router::{helpers::Coupon, msg::ExecuteMsg as RouterExecuteMsg};
 
     const NATIVE_DENOM: &str = "flavor";
@@ -25,9 +38,14 @@ mod tests {
 
     const SUBSCRIPTION_FEE_PERCENTAGE: u128 = 5000; // 50% fee
 
+    // Bond calendar, in seconds after the bond token is created
+    const SUBSCRIPTION_PERIOD: u64 = 7 * 24 * 3600;
+    const BOND_TERM: u64 = 365 * 24 * 3600;
+    const DISTRIBUTION_WINDOW: u64 = 30 * 24 * 3600; // after subscription end, before investors can claim refunds
+
     fn mock_blockchain() -> App {
         AppBuilder::new().build(|router, _, storage| {
-            for account in vec![ADMIN, ISSUER, INVESTOR_1, INVESTOR_2, OPERATOR].iter() {
+            for account in [ADMIN, ISSUER, INVESTOR_1, INVESTOR_2, OPERATOR].iter() {
                 router
                     .bank
                     .init_balance(
@@ -52,21 +70,27 @@ mod tests {
             currency::instantiate,
             currency::query,
         )));
-        let factory_id = blockchain.store_code(Box::new(ContractWrapper::new(
-            factory::contract::execute,
-            factory::contract::instantiate,
-            factory::contract::query,
-        )));
+        let factory_id = blockchain.store_code(Box::new(
+            ContractWrapper::new(
+                factory::contract::execute,
+                factory::contract::instantiate,
+                factory::contract::query,
+            )
+            .with_reply(factory::contract::reply),
+        ));
         let placeholder_id = blockchain.store_code(Box::new(ContractWrapper::new(
             placeholder::contract::execute,
             placeholder::contract::instantiate,
             placeholder::contract::query,
         )));
-        let router_id = blockchain.store_code(Box::new(ContractWrapper::new(
-            router::contract::execute,
-            router::contract::instantiate,
-            router::contract::query,
-        )));
+        let router_id = blockchain.store_code(Box::new(
+            ContractWrapper::new(
+                router::contract::execute,
+                router::contract::instantiate,
+                router::contract::query,
+            )
+            .with_reply(router::contract::reply),
+        ));
 
         let factory_address = blockchain
             .instantiate_contract(
@@ -114,6 +138,288 @@ mod tests {
         )
     }
 
+    // Instantiates the platform, wires its contracts together and creates a USDT currency
+    fn setup_platform() -> (App, Addr, Addr, Addr, Addr) {
+        let (mut blockchain, factory_address, placeholder_address, router_address, _, _) =
+            proper_instantiate();
+        blockchain
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                factory_address.clone(),
+                &FactoryExecuteMsg::InstantiateCurrency {
+                    name: String::from("Tether USDT"),
+                    symbol: String::from("USDT"),
+                    decimals: 6,
+                    initial_balances: vec![
+                        Cw20Coin {
+                            address: Addr::unchecked(INVESTOR_1).to_string(),
+                            amount: Uint128::from(1000_u128),
+                        },
+                        Cw20Coin {
+                            address: Addr::unchecked(INVESTOR_2).to_string(),
+                            amount: Uint128::from(2000_u128),
+                        },
+                        Cw20Coin {
+                            address: Addr::unchecked(ISSUER).to_string(),
+                            amount: Uint128::from(500_u128),
+                        },
+                    ],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+            )
+            .unwrap();
+        let currencies_response: CurrenciesResponse = blockchain
+            .wrap()
+            .query_wasm_smart(
+                factory_address.to_string(),
+                &FactoryQueryMsg::Currencies {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let currency_address = Addr::unchecked(&currencies_response.currencies[0].address);
+
+        blockchain
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                factory_address.clone(),
+                &FactoryExecuteMsg::Setup {
+                    placeholder: placeholder_address.to_string(),
+                    router: router_address.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+        blockchain
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                placeholder_address.clone(),
+                &PlaceholderExecuteMsg::Setup {
+                    factory: factory_address.to_string(),
+                    router: router_address.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+        blockchain
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                router_address.clone(),
+                &RouterExecuteMsg::Setup {
+                    placeholder: placeholder_address.to_string(),
+                    factory: factory_address.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+        blockchain
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                factory_address.clone(),
+                &FactoryExecuteMsg::SetOperators {
+                    operators: vec![Addr::unchecked(OPERATOR).to_string()],
+                    is_operators: vec![true],
+                },
+                &[],
+            )
+            .unwrap();
+
+        (
+            blockchain,
+            factory_address,
+            placeholder_address,
+            router_address,
+            currency_address,
+        )
+    }
+
+    // Terms of the bond in the factory test, starting now
+    fn bond_terms(blockchain: &App, coupon_terms: CouponTerms) -> BondTerms {
+        let now = blockchain.block_info().time;
+        BondTerms {
+            denomination: Denomination {
+                currency_amount: Uint128::from(3_u128),
+                bond_amount: Uint128::from(2_u128),
+            },
+            subscription_fee_percentage: Some(Uint128::from(SUBSCRIPTION_FEE_PERCENTAGE)),
+            subscription_fee: None,
+            subscription_start: now,
+            subscription_end: now.plus_seconds(SUBSCRIPTION_PERIOD),
+            issue_date: now.plus_seconds(SUBSCRIPTION_PERIOD),
+            maturity_date: now.plus_seconds(SUBSCRIPTION_PERIOD + BOND_TERM),
+            coupon_terms,
+            identity_check: None,
+            allowlist: None,
+            subscription_limits: SubscriptionLimits::default(),
+            cancellation_fee_policy: CancellationFeePolicy::Keep,
+            distribution_deadline: now.plus_seconds(SUBSCRIPTION_PERIOD + DISTRIBUTION_WINDOW),
+        }
+    }
+
+    // Creates a bond token with the same terms as in the factory test, starting now
+    fn create_bond_token(
+        blockchain: &mut App,
+        factory_address: &Addr,
+        currency_address: &Addr,
+        coupon_terms: CouponTerms,
+    ) -> Addr {
+        let terms = bond_terms(blockchain, coupon_terms);
+        create_bond_token_with_setup(
+            blockchain,
+            factory_address,
+            currency_address,
+            FunctionSetup {
+                transfer: true,
+                burn: true,
+                mint_to_investor: true,
+                subscribe: true,
+            },
+            terms,
+        )
+    }
+
+    fn create_bond_token_with_setup(
+        blockchain: &mut App,
+        factory_address: &Addr,
+        currency_address: &Addr,
+        function_setup: FunctionSetup,
+        terms: BondTerms,
+    ) -> Addr {
+        blockchain
+            .execute_contract(
+                Addr::unchecked(OPERATOR),
+                factory_address.clone(),
+                &FactoryExecuteMsg::InstantiateBondToken(Box::new(InstantiateBondTokenMsg {
+                    issuer: String::from(ISSUER),
+                    name: String::from("Bond Token"),
+                    symbol: String::from("BOND-TOKEN"),
+                    decimals: 18,
+                    initial_balances: vec![],
+                    function_setup,
+                    additional_data: String::from("no additional data"),
+                    currency: currency_address.to_string(),
+                    terms,
+                    allocation_fee_policy: AllocationFeePolicy::Subscribed,
+                })),
+                &[],
+            )
+            .unwrap();
+        let bond_tokens_response: BondTokensResponse = blockchain
+            .wrap()
+            .query_wasm_smart(
+                factory_address.to_string(),
+                &FactoryQueryMsg::BondTokensByCurrency {
+                    currency: currency_address.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        Addr::unchecked(&bond_tokens_response.bond_tokens.last().unwrap().address)
+    }
+
+    fn subscribe(
+        blockchain: &mut App,
+        bond_token_address: &Addr,
+        currency_address: &Addr,
+        investor: &str,
+        amount: u128,
+    ) {
+        blockchain
+            .execute_contract(
+                Addr::unchecked(investor),
+                currency_address.clone(),
+                &Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: bond_token_address.to_string(),
+                    amount: Uint128::from(amount),
+                    expires: None,
+                },
+                &[],
+            )
+            .unwrap();
+        blockchain
+            .execute_contract(
+                Addr::unchecked(investor),
+                bond_token_address.clone(),
+                &BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::Subscribe {
+                    subscription_amount: Uint128::from(amount),
+                    fee_amount: Uint128::zero(),
+                }),
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn subscribe_err(
+        blockchain: &mut App,
+        bond_token_address: &Addr,
+        currency_address: &Addr,
+        investor: &str,
+        amount: u128,
+    ) -> BondTokenErr {
+        blockchain
+            .execute_contract(
+                Addr::unchecked(investor),
+                currency_address.clone(),
+                &Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: bond_token_address.to_string(),
+                    amount: Uint128::from(amount),
+                    expires: None,
+                },
+                &[],
+            )
+            .unwrap();
+        blockchain
+            .execute_contract(
+                Addr::unchecked(investor),
+                bond_token_address.clone(),
+                &BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::Subscribe {
+                    subscription_amount: Uint128::from(amount),
+                    fee_amount: Uint128::zero(),
+                }),
+                &[],
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap()
+    }
+
+    fn balance_of(blockchain: &App, token: &Addr, account: &str) -> u128 {
+        let response: BalanceResponse = blockchain
+            .wrap()
+            .query_wasm_smart(
+                token.to_string(),
+                &Cw20QueryMsg::Balance {
+                    address: account.to_string(),
+                },
+            )
+            .unwrap();
+        response.balance.u128()
+    }
+
+    fn bond_balance_of(blockchain: &App, bond_token_address: &Addr, account: &str) -> u128 {
+        let response: BalanceResponse = blockchain
+            .wrap()
+            .query_wasm_smart(
+                bond_token_address.to_string(),
+                &BondTokenQueryMsg::Cw20QueryMsg(Cw20BaseQueryMsg::Balance {
+                    address: account.to_string(),
+                }),
+            )
+            .unwrap();
+        response.balance.u128()
+    }
+
+    fn set_block_time(blockchain: &mut App, time: Timestamp) {
+        blockchain.update_block(|block| {
+            block.time = time;
+            block.height += 1;
+        });
+    }
+
     mod factory_test {
         use super::*;
 
@@ -129,8 +435,7 @@ mod tests {
             ) = proper_instantiate();
 
             /* ================= Admin creates a new currency ================= */
-            let mut currency_address = Addr::unchecked("");
-            let mut transaction = blockchain
+            blockchain
                 .execute_contract(
                     Addr::unchecked(ADMIN),
                     factory_address.clone(),
@@ -158,22 +463,23 @@ mod tests {
                     &[],
                 )
                 .unwrap();
-            // Extract currency address from the events
-            for event in transaction.events {
-                if event
-                    .attributes
-                    .iter()
-                    .any(|attr| attr.key == "code_id" && attr.value == currency_code_id.to_string())
-                {
-                    for attribute in event.attributes {
-                        if attribute.key == String::from("_contract_addr") {
-                            currency_address = Addr::unchecked(attribute.value);
-                            break;
-                        }
-                    }
-                    break;
-                }
-            }
+            // Look up the currency address in the factory's registry
+            let currencies_response: CurrenciesResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    factory_address.to_string(),
+                    &FactoryQueryMsg::Currencies {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(currencies_response.currencies.len(), 1);
+            assert_eq!(
+                currencies_response.currencies[0].code_id,
+                Uint64::from(currency_code_id)
+            );
+            let currency_address = Addr::unchecked(&currencies_response.currencies[0].address);
 
             /* ================= Set up factory, placeholder and router ================= */
             blockchain
@@ -235,12 +541,12 @@ mod tests {
                 .unwrap();
 
             /* ================= Issuer creates bond token ================= */
-            let mut bond_token_address = Addr::unchecked("");
-            transaction = blockchain
+            let now = blockchain.block_info().time;
+            blockchain
                 .execute_contract(
                     Addr::unchecked(OPERATOR),
                     factory_address.clone(),
-                    &FactoryExecuteMsg::InstantiateBondToken {
+                    &FactoryExecuteMsg::InstantiateBondToken(Box::new(InstantiateBondTokenMsg {
                         issuer: String::from(ISSUER),
                         name: String::from("Bond Token"),
                         symbol: String::from("BOND-TOKEN"),
@@ -254,44 +560,73 @@ mod tests {
                         },
                         additional_data: String::from("no additional data"),
                         currency: currency_address.to_string(),
-                        denomination: Denomination {
-                            currency_amount: Uint128::from(3_u128),
-                            bond_amount: Uint128::from(2_u128),
+                        allocation_fee_policy: AllocationFeePolicy::Allocated,
+                        terms: BondTerms {
+                            denomination: Denomination {
+                                currency_amount: Uint128::from(3_u128),
+                                bond_amount: Uint128::from(2_u128),
+                            },
+                            subscription_fee_percentage: Some(Uint128::from(
+                                SUBSCRIPTION_FEE_PERCENTAGE,
+                            )),
+                            subscription_fee: None,
+                            subscription_limits: SubscriptionLimits::default(),
+                            cancellation_fee_policy: CancellationFeePolicy::Keep,
+                            subscription_start: now,
+                            subscription_end: now.plus_seconds(SUBSCRIPTION_PERIOD),
+                            distribution_deadline: now
+                                .plus_seconds(SUBSCRIPTION_PERIOD + DISTRIBUTION_WINDOW),
+                            issue_date: now.plus_seconds(SUBSCRIPTION_PERIOD),
+                            maturity_date: now.plus_seconds(SUBSCRIPTION_PERIOD + BOND_TERM),
+                            coupon_terms: CouponTerms {
+                                rate: Decimal::zero(),
+                                frequency: 1,
+                                dates: vec![],
+                                day_count: DayCount::Act365Fixed,
+                                business_day_convention: BusinessDayConvention::Unadjusted,
+                                holidays: vec![],
+                            },
+                            identity_check: None,
+                            allowlist: None,
                         },
-                        subscription_fee_percentage: Some(Uint128::from(
-                            SUBSCRIPTION_FEE_PERCENTAGE,
-                        )),
-                        subscription_fee: None,
-                    },
+                    })),
                     &[],
                 )
                 .unwrap();
-            // Extract bond token address from the events
-            for event in transaction.events {
-                if event.attributes.iter().any(|attr| {
-                    attr.key == "code_id" && attr.value == bond_token_code_id.to_string()
-                }) {
-                    for attribute in event.attributes {
-                        if attribute.key == String::from("_contract_addr") {
-                            bond_token_address = Addr::unchecked(attribute.value);
-                            break;
-                        }
-                    }
-                    break;
-                }
-            }
+            // Look up the bond token address in the factory's registry
+            let bond_tokens_response: BondTokensResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    factory_address.to_string(),
+                    &FactoryQueryMsg::BondTokens {
+                        issuer: Some(String::from(ISSUER)),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(bond_tokens_response.bond_tokens.len(), 1);
+            assert_eq!(
+                bond_tokens_response.bond_tokens[0].code_id,
+                Uint64::from(bond_token_code_id)
+            );
+            assert_eq!(
+                bond_tokens_response.bond_tokens[0].currency,
+                currency_address.to_string()
+            );
+            let bond_token_address = Addr::unchecked(&bond_tokens_response.bond_tokens[0].address);
 
-            /* ================= Register bond token to placeholder ================= */
-            blockchain
-                .execute_contract(
-                    Addr::unchecked(OPERATOR),
-                    placeholder_address.clone(),
-                    &PlaceholderExecuteMsg::RegisterBondToken {
+            /* ================= Bond token is registered to placeholder by factory ================= */
+            let validation_response: BondValidationResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::ValidateBondToken {
                         bond_token: bond_token_address.to_string(),
                     },
-                    &[],
                 )
                 .unwrap();
+            assert!(validation_response.validity);
 
             /* ================= Investor subscribes to bond token ================= */
             blockchain
@@ -352,23 +687,49 @@ mod tests {
             assert_eq!(placeholder_balance.balance.u128(), 1734_u128);
 
             /* ================= Issuer distributes bond tokens ================= */
+            let distribute_msg = RouterExecuteMsg::Distribute {
+                bond_token: bond_token_address.to_string(),
+                investment_rules: vec![
+                    InvesmentRule {
+                        investor: Addr::unchecked(INVESTOR_1).to_string(),
+                        currency_amount: Uint128::from(270_u128),
+                    },
+                    InvesmentRule {
+                        investor: Addr::unchecked(INVESTOR_2).to_string(),
+                        currency_amount: Uint128::from(1000_u128),
+                    },
+                ],
+            };
+            // Distribution is not allowed while the subscription window is still open
             blockchain
                 .execute_contract(
                     Addr::unchecked(ISSUER),
                     router_address.clone(),
-                    &RouterExecuteMsg::Distribute {
-                        bond_token: bond_token_address.to_string(),
-                        investment_rules: vec![
-                            InvesmentRule {
-                                investor: Addr::unchecked(INVESTOR_1).to_string(),
-                                currency_amount: Uint128::from(270_u128),
-                            },
-                            InvesmentRule {
-                                investor: Addr::unchecked(INVESTOR_2).to_string(),
-                                currency_amount: Uint128::from(1000_u128),
-                            },
-                        ],
-                    },
+                    &distribute_msg,
+                    &[],
+                )
+                .unwrap_err();
+            blockchain.update_block(|block| {
+                block.time = now.plus_seconds(SUBSCRIPTION_PERIOD);
+                block.height += 1;
+            });
+            // Subscription is closed from now on
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::Subscribe {
+                        subscription_amount: Uint128::from(10_u128),
+                        fee_amount: Uint128::zero(),
+                    }),
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &distribute_msg,
                     &[],
                 )
                 .unwrap();
@@ -426,8 +787,8 @@ mod tests {
                     },
                 )
                 .unwrap();
-            assert_eq!(placeholder_balance.balance.u128(), 867_u128);
-            assert_eq!(investor1_currency_balance.balance.u128(), 430_u128);
+            assert_eq!(placeholder_balance.balance.u128(), 837_u128);
+            assert_eq!(investor1_currency_balance.balance.u128(), 460_u128);
             assert_eq!(investor2_currency_balance.balance.u128(), 866_u128);
             assert_eq!(issuer_balance.balance.u128(), 1337_u128);
             assert_eq!(investor1_bond_balance.balance.u128(), 180_u128);
@@ -493,7 +854,7 @@ mod tests {
                     },
                 )
                 .unwrap();
-            assert_eq!(investor1_currency_balance.balance.u128(), 553_u128);
+            assert_eq!(investor1_currency_balance.balance.u128(), 583_u128);
             assert_eq!(investor2_currency_balance.balance.u128(), 1187_u128);
             assert_eq!(issuer_balance.balance.u128(), 893_u128);
 
@@ -519,6 +880,21 @@ mod tests {
                     &[],
                 )
                 .unwrap();
+            // Principals cannot be redeemed before maturity
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Redeem {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            blockchain.update_block(|block| {
+                block.time = now.plus_seconds(SUBSCRIPTION_PERIOD + BOND_TERM);
+                block.height += 1;
+            });
             blockchain
                 .execute_contract(
                     Addr::unchecked(ISSUER),
@@ -584,12 +960,12 @@ mod tests {
                 )
                 .unwrap();
             assert_eq!(redemption_response.redemption_amount.u128(), 837_u128);
-            assert_eq!(investor1_currency_balance.balance.u128(), 823_u128);
+            assert_eq!(investor1_currency_balance.balance.u128(), 853_u128);
             assert_eq!(investor2_currency_balance.balance.u128(), 1754_u128);
             assert_eq!(issuer_balance.balance.u128(), 56_u128);
             assert_eq!(investor1_bond_balance.balance.u128(), 0_u128);
             assert_eq!(investor2_bond_balance.balance.u128(), 0_u128);
-            assert_eq!(placeholder_balance.balance.u128(), 867_u128);
+            assert_eq!(placeholder_balance.balance.u128(), 837_u128);
 
             /* ================= Admin withdraws system fee ================= */
             blockchain
@@ -597,7 +973,11 @@ mod tests {
                     Addr::unchecked(ADMIN),
                     placeholder_address.clone(),
                     &PlaceholderExecuteMsg::WithdrawSystemFee {
-                        recipient: Addr::unchecked(ADMIN).to_string(),
+                        currency: currency_address.to_string(),
+                        recipients: vec![FeeRecipient {
+                            recipient: Addr::unchecked(ADMIN).to_string(),
+                            amount: None,
+                        }],
                     },
                     &[],
                 )
@@ -621,7 +1001,5243 @@ mod tests {
                 )
                 .unwrap();
             assert_eq!(placeholder_balance.balance.u128(), 0_u128);
-            assert_eq!(admin_balance.balance.u128(), 867_u128);
+            assert_eq!(admin_balance.balance.u128(), 837_u128);
+        }
+    }
+
+    mod asset_vault_test {
+        use super::*;
+        use asset_vault::{
+            helpers::{OperatorsResponse, ProcessedRequestResponse},
+            msg::{ExecuteMsg as AssetVaultExecuteMsg, QueryMsg as AssetVaultQueryMsg},
+        };
+
+        #[test]
+        fn asset_vault_test() {
+            let mut blockchain = mock_blockchain();
+            let currency_id = blockchain.store_code(Box::new(ContractWrapper::new(
+                currency::execute,
+                currency::instantiate,
+                currency::query,
+            )));
+            let asset_vault_id = blockchain.store_code(Box::new(ContractWrapper::new(
+                asset_vault::contract::execute,
+                asset_vault::contract::instantiate,
+                asset_vault::contract::query,
+            )));
+
+            /* ================= Admin creates the vault with an operator ================= */
+            let asset_vault_address = blockchain
+                .instantiate_contract(
+                    asset_vault_id,
+                    Addr::unchecked(ADMIN),
+                    &asset_vault::msg::InstantiateMsg {
+                        operators: vec![Addr::unchecked(OPERATOR).to_string()],
+                    },
+                    &[],
+                    "asset_vault",
+                    Some(String::from(ADMIN)),
+                )
+                .unwrap();
+            let currency_address = blockchain
+                .instantiate_contract(
+                    currency_id,
+                    Addr::unchecked(ADMIN),
+                    &cw20_base::msg::InstantiateMsg {
+                        name: String::from("Tether USDT"),
+                        symbol: String::from("USDT"),
+                        decimals: 6,
+                        initial_balances: vec![Cw20Coin {
+                            address: asset_vault_address.to_string(),
+                            amount: Uint128::from(1000_u128),
+                        }],
+                        mint: None,
+                        marketing: None,
+                    },
+                    &[],
+                    "currency",
+                    Some(String::from(ADMIN)),
+                )
+                .unwrap();
+
+            /* ================= Only admin can set operators ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    asset_vault_address.clone(),
+                    &AssetVaultExecuteMsg::SetOperators {
+                        operators: vec![Addr::unchecked(ISSUER).to_string()],
+                        is_operators: vec![true],
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    asset_vault_address.clone(),
+                    &AssetVaultExecuteMsg::SetOperators {
+                        operators: vec![Addr::unchecked(ISSUER).to_string()],
+                        is_operators: vec![true],
+                    },
+                    &[],
+                )
+                .unwrap();
+            let operators_response: OperatorsResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    asset_vault_address.to_string(),
+                    &AssetVaultQueryMsg::Operators {},
+                )
+                .unwrap();
+            assert_eq!(
+                operators_response.operators,
+                vec![String::from(ISSUER), String::from(OPERATOR)]
+            );
+
+            /* ================= Only operators can send assets ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    asset_vault_address.clone(),
+                    &AssetVaultExecuteMsg::SendAsset {
+                        request_id: String::from("request-1"),
+                        asset_address: currency_address.to_string(),
+                        amount: Uint128::from(300_u128),
+                        recipient: Addr::unchecked(INVESTOR_1).to_string(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    asset_vault_address.clone(),
+                    &AssetVaultExecuteMsg::SendAsset {
+                        request_id: String::from("request-1"),
+                        asset_address: currency_address.to_string(),
+                        amount: Uint128::from(300_u128),
+                        recipient: Addr::unchecked(INVESTOR_1).to_string(),
+                    },
+                    &[],
+                )
+                .unwrap();
+
+            /* ================= A replayed request is rejected ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    asset_vault_address.clone(),
+                    &AssetVaultExecuteMsg::SendAsset {
+                        request_id: String::from("request-1"),
+                        asset_address: currency_address.to_string(),
+                        amount: Uint128::from(300_u128),
+                        recipient: Addr::unchecked(INVESTOR_1).to_string(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            let investor1_balance: BalanceResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    currency_address.to_string(),
+                    &Cw20QueryMsg::Balance {
+                        address: Addr::unchecked(INVESTOR_1).to_string(),
+                    },
+                )
+                .unwrap();
+            let vault_balance: BalanceResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    currency_address.to_string(),
+                    &Cw20QueryMsg::Balance {
+                        address: asset_vault_address.to_string(),
+                    },
+                )
+                .unwrap();
+            let request_response: ProcessedRequestResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    asset_vault_address.to_string(),
+                    &AssetVaultQueryMsg::ProcessedRequest {
+                        request_id: String::from("request-1"),
+                    },
+                )
+                .unwrap();
+            assert_eq!(investor1_balance.balance.u128(), 300_u128);
+            assert_eq!(vault_balance.balance.u128(), 700_u128);
+            assert!(request_response.processed);
+            assert_eq!(request_response.amount, Some(Uint128::from(300_u128)));
+            assert_eq!(
+                request_response.recipient,
+                Some(Addr::unchecked(INVESTOR_1).to_string())
+            );
+        }
+    }
+
+    mod router_mint_test {
+        use super::*;
+        use cosmwasm_std::Binary;
+        use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+        use router::helpers::{MintPayload, RequestProcessedResponse};
+
+        fn sign(signing_key: &SigningKey, payload: &MintPayload) -> Binary {
+            // The contract verifies sha256 over the JSON payload, which is what `sign` hashes
+            let signature: Signature =
+                signing_key.sign(cosmwasm_std::to_binary(payload).unwrap().as_slice());
+            Binary::from(signature.as_ref())
+        }
+
+        #[test]
+        fn router_mint_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_id,
+                _,
+            ) = proper_instantiate();
+            let signing_key = SigningKey::from_bytes(&[7_u8; 32]).unwrap();
+            let chain_id = blockchain.block_info().chain_id;
+
+            /* ================= Admin creates a currency minted by the router ================= */
+            let currency_address = blockchain
+                .instantiate_contract(
+                    currency_id,
+                    Addr::unchecked(ADMIN),
+                    &cw20_base::msg::InstantiateMsg {
+                        name: String::from("Tether USDT"),
+                        symbol: String::from("USDT"),
+                        decimals: 6,
+                        initial_balances: vec![],
+                        mint: Some(cw20::MinterResponse {
+                            minter: router_address.to_string(),
+                            cap: None,
+                        }),
+                        marketing: None,
+                    },
+                    &[],
+                    "currency",
+                    Some(String::from(ADMIN)),
+                )
+                .unwrap();
+
+            /* ================= Admin registers the operator and its public key ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Setup {
+                        placeholder: placeholder_address.to_string(),
+                        factory: factory_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    router_address.clone(),
+                    &RouterExecuteMsg::SetOperators {
+                        operators: vec![Addr::unchecked(OPERATOR).to_string()],
+                        is_operators: vec![true],
+                    },
+                    &[],
+                )
+                .unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    router_address.clone(),
+                    &RouterExecuteMsg::SetOperatorPublicKeys {
+                        operators: vec![Addr::unchecked(OPERATOR).to_string()],
+                        public_keys: vec![Binary::from(
+                            signing_key.verifying_key().to_bytes().as_slice(),
+                        )],
+                    },
+                    &[],
+                )
+                .unwrap();
+
+            /* ================= Anyone submits the operator-signed mint ================= */
+            let payload = MintPayload {
+                chain_id: chain_id.clone(),
+                router: router_address.to_string(),
+                action: String::from("mint_currency"),
+                request_id: String::from("request-1"),
+                token: currency_address.to_string(),
+                receivers: vec![
+                    Addr::unchecked(INVESTOR_1).to_string(),
+                    Addr::unchecked(INVESTOR_2).to_string(),
+                ],
+                amounts: vec![Uint128::from(100_u128), Uint128::from(200_u128)],
+            };
+            let mint_msg = RouterExecuteMsg::MintCurrency {
+                request_id: payload.request_id.clone(),
+                currency_address: currency_address.to_string(),
+                receivers: payload.receivers.clone(),
+                amounts: payload.amounts.clone(),
+                operator: Addr::unchecked(OPERATOR).to_string(),
+                signature: sign(&signing_key, &payload),
+            };
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &mint_msg,
+                    &[],
+                )
+                .unwrap();
+
+            /* ================= The same request cannot be replayed ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &mint_msg,
+                    &[],
+                )
+                .unwrap_err();
+
+            /* ================= Tampered amounts break the signature ================= */
+            let tampered_payload = MintPayload {
+                request_id: String::from("request-2"),
+                ..payload.clone()
+            };
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::MintCurrency {
+                        request_id: tampered_payload.request_id.clone(),
+                        currency_address: currency_address.to_string(),
+                        receivers: tampered_payload.receivers.clone(),
+                        amounts: vec![Uint128::from(1000_u128), Uint128::from(200_u128)],
+                        operator: Addr::unchecked(OPERATOR).to_string(),
+                        signature: sign(&signing_key, &tampered_payload),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+
+            /* ================= A key which is not registered cannot sign ================= */
+            let other_key = SigningKey::from_bytes(&[9_u8; 32]).unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::MintCurrency {
+                        request_id: tampered_payload.request_id.clone(),
+                        currency_address: currency_address.to_string(),
+                        receivers: tampered_payload.receivers.clone(),
+                        amounts: tampered_payload.amounts.clone(),
+                        operator: Addr::unchecked(OPERATOR).to_string(),
+                        signature: sign(&other_key, &tampered_payload),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+
+            let investor1_balance: BalanceResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    currency_address.to_string(),
+                    &Cw20QueryMsg::Balance {
+                        address: Addr::unchecked(INVESTOR_1).to_string(),
+                    },
+                )
+                .unwrap();
+            let investor2_balance: BalanceResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    currency_address.to_string(),
+                    &Cw20QueryMsg::Balance {
+                        address: Addr::unchecked(INVESTOR_2).to_string(),
+                    },
+                )
+                .unwrap();
+            let processed_response: RequestProcessedResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    router_address.to_string(),
+                    &router::msg::QueryMsg::RequestProcessed {
+                        request_id: String::from("request-1"),
+                    },
+                )
+                .unwrap();
+            assert_eq!(investor1_balance.balance.u128(), 100_u128);
+            assert_eq!(investor2_balance.balance.u128(), 200_u128);
+            assert!(processed_response.processed);
+        }
+    }
+
+    mod subscription_window_test {
+        use super::*;
+        use bond_token::{
+            helpers::{BondScheduleResponse, Phase},
+            AdditionalError,
+        };
+
+        fn update_phase(
+            blockchain: &mut App,
+            router_address: &Addr,
+            bond_token_address: &Addr,
+            phase: Phase,
+        ) -> Result<(), BondTokenErr> {
+            blockchain
+                .execute_contract(
+                    router_address.clone(),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::UpdatePhase {
+                        phase,
+                    }),
+                    &[],
+                )
+                .map(|_| ())
+                .map_err(|err| err.downcast().unwrap())
+        }
+
+        #[test]
+        fn subscription_window_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let now = blockchain.block_info().time;
+            let subscription_start = now.plus_seconds(3600);
+            let subscription_end = now.plus_seconds(SUBSCRIPTION_PERIOD);
+            let maturity_date = subscription_end.plus_seconds(BOND_TERM);
+            let terms = BondTerms {
+                subscription_start,
+                ..bond_terms(
+                    &blockchain,
+                    CouponTerms {
+                        rate: Decimal::zero(),
+                        frequency: 1,
+                        dates: vec![maturity_date],
+                        day_count: DayCount::Act365Fixed,
+                        business_day_convention: BusinessDayConvention::Unadjusted,
+                        holidays: vec![],
+                    },
+                )
+            };
+            let bond_token_address = create_bond_token_with_setup(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                FunctionSetup {
+                    transfer: true,
+                    burn: true,
+                    mint_to_investor: true,
+                    subscribe: true,
+                },
+                terms,
+            );
+
+            /* ================= No subscription before the window opens ================= */
+            assert_eq!(
+                subscribe_err(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    INVESTOR_1,
+                    600,
+                ),
+                BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+                    action: String::from("subscription"),
+                    time: subscription_start,
+                })
+            );
+            set_block_time(&mut blockchain, subscription_start);
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+
+            /* ================= No distribution while the window is open ================= */
+            assert_eq!(
+                update_phase(
+                    &mut blockchain,
+                    &router_address,
+                    &bond_token_address,
+                    Phase::Distribution,
+                )
+                .unwrap_err(),
+                BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+                    action: String::from("distribution"),
+                    time: subscription_end,
+                })
+            );
+
+            /* ================= No subscription once the window has closed ================= */
+            set_block_time(&mut blockchain, subscription_end);
+            assert_eq!(
+                subscribe_err(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    INVESTOR_2,
+                    600,
+                ),
+                BondTokenErr::AdditionalError(AdditionalError::TooLate {
+                    action: String::from("subscription"),
+                    time: subscription_end,
+                })
+            );
+
+            /* ================= No redemption before maturity ================= */
+            for phase in [Phase::Distribution, Phase::Coupon] {
+                update_phase(&mut blockchain, &router_address, &bond_token_address, phase).unwrap();
+            }
+            assert_eq!(
+                update_phase(
+                    &mut blockchain,
+                    &router_address,
+                    &bond_token_address,
+                    Phase::Redemption,
+                )
+                .unwrap_err(),
+                BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+                    action: String::from("redemption"),
+                    time: maturity_date,
+                })
+            );
+            set_block_time(&mut blockchain, maturity_date);
+            update_phase(
+                &mut blockchain,
+                &router_address,
+                &bond_token_address,
+                Phase::Redemption,
+            )
+            .unwrap();
+            let schedule: BondScheduleResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::BondSchedule {}),
+                )
+                .unwrap();
+            assert_eq!(schedule.current_phase, Phase::Redemption);
+        }
+    }
+
+    mod coupon_test {
+        use super::*;
+        use bond_token::helpers::{CouponScheduleResponse, UnclaimedCouponsResponse};
+        use router::{helpers::CouponPoolResponse, msg::QueryMsg as RouterQueryMsg};
+
+        const HALF_YEAR: u64 = 182 * 24 * 3600;
+        const CLAIM_PERIOD: u64 = 30 * 24 * 3600;
+
+        // Creates a bond, lets both investors subscribe, distributes it at issue date
+        // and lets router spend the issuer's currency
+        fn issue_bond(
+            blockchain: &mut App,
+            factory_address: &Addr,
+            router_address: &Addr,
+            currency_address: &Addr,
+            coupon_terms: CouponTerms,
+        ) -> Addr {
+            let issue_date = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address =
+                create_bond_token(blockchain, factory_address, currency_address, coupon_terms);
+            subscribe(
+                blockchain,
+                &bond_token_address,
+                currency_address,
+                INVESTOR_1,
+                600,
+            );
+            subscribe(
+                blockchain,
+                &bond_token_address,
+                currency_address,
+                INVESTOR_2,
+                1134,
+            );
+            set_block_time(blockchain, issue_date);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![
+                            InvesmentRule {
+                                investor: Addr::unchecked(INVESTOR_1).to_string(),
+                                currency_amount: Uint128::from(270_u128),
+                            },
+                            InvesmentRule {
+                                investor: Addr::unchecked(INVESTOR_2).to_string(),
+                                currency_amount: Uint128::from(1000_u128),
+                            },
+                        ],
+                    },
+                    &[],
+                )
+                .unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    currency_address.clone(),
+                    &Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: router_address.to_string(),
+                        amount: Uint128::from(1000_u128),
+                        expires: None,
+                    },
+                    &[],
+                )
+                .unwrap();
+            bond_token_address
+        }
+
+        #[test]
+        fn pay_coupon_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let issue_date = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+
+            /* ================= Bond pays 10% a year, semi-annually, 30/360 ================= */
+            // The first coupon date is a holiday, so it is paid on the following business day
+            let first_coupon_date = issue_date.plus_seconds(HALF_YEAR);
+            let first_payment_date = first_coupon_date.plus_seconds(24 * 3600);
+            let bond_token_address = issue_bond(
+                &mut blockchain,
+                &factory_address,
+                &router_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::percent(10),
+                    frequency: 2,
+                    dates: vec![first_coupon_date, issue_date.plus_seconds(BOND_TERM)],
+                    day_count: DayCount::Thirty360,
+                    business_day_convention: BusinessDayConvention::Following,
+                    holidays: vec![first_coupon_date],
+                },
+            );
+
+            /* ================= First coupon cannot be paid before its date ================= */
+            let pay_coupon_msg = RouterExecuteMsg::PayCoupon {
+                bond_token: bond_token_address.to_string(),
+                period: 1,
+            };
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &pay_coupon_msg,
+                    &[],
+                )
+                .unwrap_err();
+
+            set_block_time(&mut blockchain, first_coupon_date);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &pay_coupon_msg,
+                    &[],
+                )
+                .unwrap_err();
+
+            /* ================= Issuer pays the first coupon ================= */
+            set_block_time(&mut blockchain, first_payment_date);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &pay_coupon_msg,
+                    &[],
+                )
+                .unwrap();
+            // 2019-10-30 -> 2020-04-29 is 179 days in 30/360
+            // 270 * 10% * 179 / 360 = 13.42 and 567 * 10% * 179 / 360 = 28.19, rounded down
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, INVESTOR_1),
+                430 + 13
+            );
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, INVESTOR_2),
+                866 + 28
+            );
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, ISSUER),
+                500 + 837 - 13 - 28
+            );
+
+            /* ================= A period cannot be paid twice ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &pay_coupon_msg,
+                    &[],
+                )
+                .unwrap_err();
+            let schedule_response: CouponScheduleResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::CouponSchedule {}),
+                )
+                .unwrap();
+            assert_eq!(schedule_response.periods.len(), 2);
+            assert!(schedule_response.periods[0].paid);
+            assert!(!schedule_response.periods[1].paid);
+            assert_eq!(
+                schedule_response.periods[0].accrual_fraction,
+                Decimal::from_ratio(179_u128, 360_u128)
+            );
+            assert_eq!(
+                schedule_response.periods[0].payment_date,
+                first_payment_date
+            );
+            assert_eq!(schedule_response.periods[1].start, first_coupon_date);
+        }
+
+        #[test]
+        fn claim_coupon_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let issue_date = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let coupon_date = issue_date.plus_seconds(HALF_YEAR);
+            let claim_expiry = coupon_date.plus_seconds(CLAIM_PERIOD);
+            let bond_token_address = issue_bond(
+                &mut blockchain,
+                &factory_address,
+                &router_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::percent(10),
+                    frequency: 2,
+                    dates: vec![coupon_date, issue_date.plus_seconds(BOND_TERM)],
+                    day_count: DayCount::Thirty360,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+
+            /* ================= Issuer funds the first coupon into router ================= */
+            set_block_time(&mut blockchain, coupon_date);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::FundCoupon {
+                        bond_token: bond_token_address.to_string(),
+                        period: 1,
+                        claim_expiry,
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, router_address.as_str()),
+                13 + 28
+            );
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, ISSUER),
+                500 + 837 - 13 - 28
+            );
+            // The period is paid, it cannot be pushed nor funded again
+            for msg in [
+                RouterExecuteMsg::PayCoupon {
+                    bond_token: bond_token_address.to_string(),
+                    period: 1,
+                },
+                RouterExecuteMsg::FundCoupon {
+                    bond_token: bond_token_address.to_string(),
+                    period: 1,
+                    claim_expiry,
+                },
+            ] {
+                blockchain
+                    .execute_contract(Addr::unchecked(ISSUER), router_address.clone(), &msg, &[])
+                    .unwrap_err();
+            }
+
+            /* ================= Entitlements follow the balances at funding, not later transfers ================= */
+            blockchain.update_block(|block| block.height += 1);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::Cw20ExecuteMsg(cw20_base::msg::ExecuteMsg::Transfer {
+                        recipient: String::from(INVESTOR_2),
+                        amount: Uint128::from(100_u128),
+                    }),
+                    &[],
+                )
+                .unwrap();
+            let unclaimed_response: UnclaimedCouponsResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::UnclaimedCoupons {
+                        investor: String::from(INVESTOR_1),
+                    }),
+                )
+                .unwrap();
+            assert_eq!(unclaimed_response.entitlements.len(), 1);
+            assert_eq!(unclaimed_response.entitlements[0].period, 1);
+            assert_eq!(unclaimed_response.entitlements[0].amount.u128(), 13);
+
+            /* ================= Investor 1 claims its coupon, only once ================= */
+            let claim_coupon_msg = RouterExecuteMsg::ClaimCoupon {
+                bond_token: bond_token_address.to_string(),
+                period: 1,
+            };
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &claim_coupon_msg,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, INVESTOR_1),
+                430 + 13
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &claim_coupon_msg,
+                    &[],
+                )
+                .unwrap_err();
+            let unclaimed_response: UnclaimedCouponsResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::UnclaimedCoupons {
+                        investor: String::from(INVESTOR_1),
+                    }),
+                )
+                .unwrap();
+            assert!(unclaimed_response.entitlements.is_empty());
+
+            /* ================= Issuer cannot sweep before the claim expiry ================= */
+            let sweep_coupon_msg = RouterExecuteMsg::SweepCoupon {
+                bond_token: bond_token_address.to_string(),
+                period: 1,
+            };
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &sweep_coupon_msg,
+                    &[],
+                )
+                .unwrap_err();
+
+            /* ================= After expiry, investor 2 is too late and issuer sweeps ================= */
+            set_block_time(&mut blockchain, claim_expiry.plus_seconds(1));
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    router_address.clone(),
+                    &claim_coupon_msg,
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &sweep_coupon_msg,
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &sweep_coupon_msg,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, ISSUER),
+                500 + 837 - 13
+            );
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, router_address.as_str()),
+                0
+            );
+            let pool_response: CouponPoolResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    router_address.to_string(),
+                    &RouterQueryMsg::CouponPool {
+                        bond_token: bond_token_address.to_string(),
+                        period: 1,
+                    },
+                )
+                .unwrap();
+            assert_eq!(pool_response.total_amount.u128(), 13 + 28);
+            assert_eq!(pool_response.claimed_amount.u128(), 13);
+            assert!(pool_response.swept);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &sweep_coupon_msg,
+                    &[],
+                )
+                .unwrap_err();
+        }
+    }
+    mod snapshot_test {
+        use super::*;
+        use bond_token::helpers::{HoldersResponse, TotalSupplyAtResponse};
+
+        #[test]
+        fn balance_snapshot_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let issue_date = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            set_block_time(&mut blockchain, issue_date);
+            let distribution_height = blockchain.block_info().height;
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![InvesmentRule {
+                            investor: Addr::unchecked(INVESTOR_1).to_string(),
+                            currency_amount: Uint128::from(270_u128),
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap();
+            let balance_response: BalanceResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::Cw20QueryMsg(Cw20BaseQueryMsg::Balance {
+                        address: String::from(INVESTOR_1),
+                    }),
+                )
+                .unwrap();
+            let minted = balance_response.balance.u128();
+            assert!(minted > 0);
+
+            /* ================= Investor 1 transfers part of its bonds in a later block ================= */
+            blockchain.update_block(|block| block.height += 1);
+            let record_height = blockchain.block_info().height;
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::Cw20ExecuteMsg(cw20_base::msg::ExecuteMsg::Transfer {
+                        recipient: String::from(INVESTOR_2),
+                        amount: Uint128::from(50_u128),
+                    }),
+                    &[],
+                )
+                .unwrap();
+            blockchain.update_block(|block| block.height += 1);
+
+            /* ================= Balances at the record height ignore the transfer ================= */
+            let balance_at = |address: &str, height: u64| -> u128 {
+                let response: BalanceResponse = blockchain
+                    .wrap()
+                    .query_wasm_smart(
+                        bond_token_address.to_string(),
+                        &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::BalanceAt {
+                            address: String::from(address),
+                            height,
+                        }),
+                    )
+                    .unwrap();
+                response.balance.u128()
+            };
+            // Snapshots read the start of a block, so the mint only shows up after its block
+            assert_eq!(balance_at(INVESTOR_1, distribution_height), 0);
+            assert_eq!(balance_at(INVESTOR_1, record_height), minted);
+            assert_eq!(balance_at(INVESTOR_2, record_height), 0);
+            let current_height = blockchain.block_info().height;
+            assert_eq!(balance_at(INVESTOR_1, current_height), minted - 50);
+            assert_eq!(balance_at(INVESTOR_2, current_height), 50);
+
+            let holders_at = |height: u64, start_after: Option<&str>| -> Vec<(String, u128)> {
+                let response: HoldersResponse = blockchain
+                    .wrap()
+                    .query_wasm_smart(
+                        bond_token_address.to_string(),
+                        &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::HoldersAt {
+                            height,
+                            start_after: start_after.map(String::from),
+                            limit: Some(1),
+                        }),
+                    )
+                    .unwrap();
+                response
+                    .holders
+                    .into_iter()
+                    .map(|holder| (holder.account, holder.balance_in_currency.u128()))
+                    .collect()
+            };
+            // Pages cover every account that held bonds, the recipient held nothing at the record height
+            assert_eq!(
+                holders_at(record_height, None),
+                vec![(String::from(INVESTOR_1), minted * 3 / 2)]
+            );
+            assert_eq!(
+                holders_at(record_height, Some(INVESTOR_1)),
+                vec![(String::from(INVESTOR_2), 0)]
+            );
+            assert_eq!(
+                holders_at(current_height, Some(INVESTOR_1)),
+                vec![(String::from(INVESTOR_2), 75)]
+            );
+            assert_eq!(holders_at(current_height, Some(INVESTOR_2)), vec![]);
+
+            let total_supply_response: TotalSupplyAtResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::TotalSupplyAt {
+                        height: current_height,
+                    }),
+                )
+                .unwrap();
+            assert_eq!(total_supply_response.total_supply.u128(), minted);
+        }
+    }
+    mod transfer_test {
+        use super::*;
+        use bond_token::{AdditionalError, BondTokenErr};
+
+        fn transfer(
+            blockchain: &mut App,
+            bond_token_address: &Addr,
+            amount: u128,
+        ) -> Result<(), BondTokenErr> {
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::Cw20ExecuteMsg(cw20_base::msg::ExecuteMsg::Transfer {
+                        recipient: String::from(INVESTOR_2),
+                        amount: Uint128::from(amount),
+                    }),
+                    &[],
+                )
+                .map(|_| ())
+                .map_err(|err| err.downcast().unwrap())
+        }
+
+        fn not_allowed(reason: &str) -> BondTokenErr {
+            BondTokenErr::AdditionalError(AdditionalError::TransferNotAllowed {
+                reason: String::from(reason),
+            })
+        }
+
+        fn distribute(blockchain: &mut App, router_address: &Addr, bond_token_address: &Addr) {
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![InvesmentRule {
+                            investor: Addr::unchecked(INVESTOR_1).to_string(),
+                            currency_amount: Uint128::from(270_u128),
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap();
+        }
+
+        #[test]
+        fn phase_transfer_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let issue_date = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let maturity_date = issue_date.plus_seconds(BOND_TERM);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![maturity_date],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+
+            /* ================= No transfer during subscription ================= */
+            assert_eq!(
+                transfer(&mut blockchain, &bond_token_address, 1).unwrap_err(),
+                not_allowed("subscription phase")
+            );
+
+            /* ================= Transfers are open once distributed ================= */
+            set_block_time(&mut blockchain, issue_date);
+            distribute(&mut blockchain, &router_address, &bond_token_address);
+            transfer(&mut blockchain, &bond_token_address, 10).unwrap();
+            assert_eq!(
+                blockchain
+                    .wrap()
+                    .query_wasm_smart::<BalanceResponse>(
+                        bond_token_address.to_string(),
+                        &BondTokenQueryMsg::Cw20QueryMsg(Cw20BaseQueryMsg::Balance {
+                            address: String::from(INVESTOR_2),
+                        }),
+                    )
+                    .unwrap()
+                    .balance
+                    .u128(),
+                10
+            );
+
+            /* ================= No transfer after redemption has started ================= */
+            set_block_time(&mut blockchain, maturity_date);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    currency_address.clone(),
+                    &Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: router_address.to_string(),
+                        amount: Uint128::from(1000_u128),
+                        expires: None,
+                    },
+                    &[],
+                )
+                .unwrap();
+            for msg in [
+                RouterExecuteMsg::PayCoupon {
+                    bond_token: bond_token_address.to_string(),
+                    period: 1,
+                },
+                RouterExecuteMsg::Redeem {
+                    bond_token: bond_token_address.to_string(),
+                },
+            ] {
+                blockchain
+                    .execute_contract(Addr::unchecked(ISSUER), router_address.clone(), &msg, &[])
+                    .unwrap();
+            }
+            assert_eq!(
+                transfer(&mut blockchain, &bond_token_address, 1).unwrap_err(),
+                not_allowed("redemption phase")
+            );
+        }
+
+        #[test]
+        fn non_transferable_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let issue_date = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let terms = bond_terms(
+                &blockchain,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            let bond_token_address = create_bond_token_with_setup(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                FunctionSetup {
+                    transfer: false,
+                    burn: true,
+                    mint_to_investor: true,
+                    subscribe: true,
+                },
+                terms,
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            set_block_time(&mut blockchain, issue_date);
+            distribute(&mut blockchain, &router_address, &bond_token_address);
+
+            /* ================= Private placement cannot move, directly or through allowances ================= */
+            assert_eq!(
+                transfer(&mut blockchain, &bond_token_address, 10).unwrap_err(),
+                not_allowed("transfer is disabled")
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::Cw20ExecuteMsg(
+                        cw20_base::msg::ExecuteMsg::IncreaseAllowance {
+                            spender: String::from(INVESTOR_2),
+                            amount: Uint128::from(10_u128),
+                            expires: None,
+                        },
+                    ),
+                    &[],
+                )
+                .unwrap();
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::Cw20ExecuteMsg(
+                        cw20_base::msg::ExecuteMsg::TransferFrom {
+                            owner: String::from(INVESTOR_1),
+                            recipient: String::from(INVESTOR_2),
+                            amount: Uint128::from(10_u128),
+                        },
+                    ),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(err, not_allowed("transfer is disabled"));
+        }
+    }
+    mod identity_test {
+        use super::*;
+        use bond_token::{AdditionalError, BondTokenErr};
+        use identity_registry::{
+            helpers::IdentityRequirements, msg::ExecuteMsg as IdentityRegistryExecuteMsg,
+        };
+
+        const CLAIM_VALIDITY: u64 = 30 * 24 * 3600;
+
+        fn set_claim(
+            blockchain: &mut App,
+            registry_address: &Addr,
+            investor: &str,
+            jurisdiction: &str,
+            expiry: Timestamp,
+        ) {
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    registry_address.clone(),
+                    &IdentityRegistryExecuteMsg::SetClaim {
+                        investor: String::from(investor),
+                        kyc_level: 2,
+                        jurisdiction: String::from(jurisdiction),
+                        accredited: true,
+                        expiry,
+                    },
+                    &[],
+                )
+                .unwrap();
+        }
+
+        fn not_verified(account: &str, reason: &str) -> BondTokenErr {
+            BondTokenErr::AdditionalError(AdditionalError::IdentityNotVerified {
+                account: String::from(account),
+                reason: String::from(reason),
+            })
+        }
+
+        #[test]
+        fn identity_check_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let now = blockchain.block_info().time;
+            let issue_date = now.plus_seconds(SUBSCRIPTION_PERIOD);
+            let registry_id = blockchain.store_code(Box::new(ContractWrapper::new(
+                identity_registry::contract::execute,
+                identity_registry::contract::instantiate,
+                identity_registry::contract::query,
+            )));
+            let registry_address = blockchain
+                .instantiate_contract(
+                    registry_id,
+                    Addr::unchecked(ADMIN),
+                    &identity_registry::msg::InstantiateMsg {
+                        operators: vec![String::from(OPERATOR)],
+                    },
+                    &[],
+                    "identity_registry",
+                    Some(String::from(ADMIN)),
+                )
+                .unwrap();
+
+            /* ================= Only operators maintain claims ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    registry_address.clone(),
+                    &IdentityRegistryExecuteMsg::SetClaim {
+                        investor: String::from(INVESTOR_1),
+                        kyc_level: 3,
+                        jurisdiction: String::from("SG"),
+                        accredited: true,
+                        expiry: now.plus_seconds(BOND_TERM),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            set_claim(
+                &mut blockchain,
+                &registry_address,
+                INVESTOR_1,
+                "SG",
+                now.plus_seconds(BOND_TERM),
+            );
+            set_claim(
+                &mut blockchain,
+                &registry_address,
+                INVESTOR_2,
+                "US",
+                now.plus_seconds(BOND_TERM),
+            );
+
+            /* ================= Bond is only for accredited investors in Singapore ================= */
+            let terms = BondTerms {
+                identity_check: Some(IdentityCheck {
+                    registry: registry_address.to_string(),
+                    requirements: IdentityRequirements {
+                        min_kyc_level: 2,
+                        accredited_only: true,
+                        jurisdictions: vec![String::from("SG")],
+                    },
+                }),
+                ..bond_terms(
+                    &blockchain,
+                    CouponTerms {
+                        rate: Decimal::zero(),
+                        frequency: 1,
+                        dates: vec![],
+                        day_count: DayCount::Act365Fixed,
+                        business_day_convention: BusinessDayConvention::Unadjusted,
+                        holidays: vec![],
+                    },
+                )
+            };
+            let bond_token_address = create_bond_token_with_setup(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                FunctionSetup {
+                    transfer: true,
+                    burn: true,
+                    mint_to_investor: true,
+                    subscribe: true,
+                },
+                terms,
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::Subscribe {
+                        subscription_amount: Uint128::from(100_u128),
+                        fee_amount: Uint128::zero(),
+                    }),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(err, not_verified(INVESTOR_2, "jurisdiction not allowed"));
+
+            set_block_time(&mut blockchain, issue_date);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![InvesmentRule {
+                            investor: String::from(INVESTOR_1),
+                            currency_amount: Uint128::from(270_u128),
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap();
+
+            /* ================= Bonds only move to verified investors ================= */
+            let transfer_msg =
+                BondTokenExecuteMsg::Cw20ExecuteMsg(cw20_base::msg::ExecuteMsg::Transfer {
+                    recipient: String::from(INVESTOR_2),
+                    amount: Uint128::from(10_u128),
+                });
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &transfer_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(err, not_verified(INVESTOR_2, "jurisdiction not allowed"));
+            set_claim(
+                &mut blockchain,
+                &registry_address,
+                INVESTOR_2,
+                "SG",
+                issue_date.plus_seconds(CLAIM_VALIDITY),
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &transfer_msg,
+                    &[],
+                )
+                .unwrap();
+
+            /* ================= Expired claims are rejected ================= */
+            set_block_time(&mut blockchain, issue_date.plus_seconds(CLAIM_VALIDITY));
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &transfer_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(err, not_verified(INVESTOR_2, "claim expired"));
+
+            /* ================= Revoked claims are rejected ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    registry_address,
+                    &IdentityRegistryExecuteMsg::RevokeClaim {
+                        investor: String::from(INVESTOR_2),
+                    },
+                    &[],
+                )
+                .unwrap();
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address,
+                    &transfer_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(err, not_verified(INVESTOR_2, "no claim"));
+        }
+    }
+    mod allowlist_test {
+        use super::*;
+        use bond_token::{helpers::AllowlistResponse, AdditionalError, BondTokenErr};
+
+        fn allowlist(
+            blockchain: &App,
+            bond_token_address: &Addr,
+            start_after: Option<&str>,
+            limit: Option<u32>,
+        ) -> AllowlistResponse {
+            blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::Allowlist {
+                        start_after: start_after.map(String::from),
+                        limit,
+                    }),
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn allowlist_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let issue_date = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+
+            /* ================= Private placement offered to investor 1 only ================= */
+            let terms = BondTerms {
+                allowlist: Some(vec![String::from(INVESTOR_1)]),
+                ..bond_terms(
+                    &blockchain,
+                    CouponTerms {
+                        rate: Decimal::zero(),
+                        frequency: 1,
+                        dates: vec![],
+                        day_count: DayCount::Act365Fixed,
+                        business_day_convention: BusinessDayConvention::Unadjusted,
+                        holidays: vec![],
+                    },
+                )
+            };
+            let bond_token_address = create_bond_token_with_setup(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                FunctionSetup {
+                    transfer: true,
+                    burn: true,
+                    mint_to_investor: true,
+                    subscribe: true,
+                },
+                terms,
+            );
+            let response = allowlist(&blockchain, &bond_token_address, None, None);
+            assert!(response.enabled);
+            assert_eq!(response.investors, vec![String::from(INVESTOR_1)]);
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            let subscribe_msg =
+                BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::Subscribe {
+                    subscription_amount: Uint128::from(100_u128),
+                    fee_amount: Uint128::zero(),
+                });
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    bond_token_address.clone(),
+                    &subscribe_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::NotAllowlisted {
+                    account: String::from(INVESTOR_2),
+                })
+            );
+
+            /* ================= Only issuer manages the allowlist ================= */
+            let add_msg =
+                BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::AddToAllowlist {
+                    investors: vec![String::from(INVESTOR_2), String::from(OPERATOR)],
+                });
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &add_msg,
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    bond_token_address.clone(),
+                    &add_msg,
+                    &[],
+                )
+                .unwrap();
+            let first_page = allowlist(&blockchain, &bond_token_address, None, Some(2));
+            assert_eq!(
+                first_page.investors,
+                vec![String::from(INVESTOR_1), String::from(INVESTOR_2)]
+            );
+            let second_page =
+                allowlist(&blockchain, &bond_token_address, Some(INVESTOR_2), Some(2));
+            assert_eq!(second_page.investors, vec![String::from(OPERATOR)]);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(
+                        AdditionalExecuteMsg::RemoveFromAllowlist {
+                            investors: vec![String::from(OPERATOR)],
+                        },
+                    ),
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                allowlist(&blockchain, &bond_token_address, None, None).investors,
+                vec![String::from(INVESTOR_1), String::from(INVESTOR_2)]
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+
+            /* ================= Bonds only move to allowlisted investors ================= */
+            set_block_time(&mut blockchain, issue_date);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![InvesmentRule {
+                            investor: String::from(INVESTOR_1),
+                            currency_amount: Uint128::from(270_u128),
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap();
+            let transfer_msg = |recipient: &str| {
+                BondTokenExecuteMsg::Cw20ExecuteMsg(cw20_base::msg::ExecuteMsg::Transfer {
+                    recipient: String::from(recipient),
+                    amount: Uint128::from(10_u128),
+                })
+            };
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &transfer_msg(OPERATOR),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::NotAllowlisted {
+                    account: String::from(OPERATOR),
+                })
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address,
+                    &transfer_msg(INVESTOR_2),
+                    &[],
+                )
+                .unwrap();
+        }
+    }
+
+    mod subscription_limits_test {
+        use super::*;
+        use bond_token::{helpers::SubscriptionLimitsResponse, AdditionalError, BondTokenErr};
+
+        #[test]
+        fn subscription_limits_test() {
+            let (mut blockchain, factory_address, _, router_address, currency_address) =
+                setup_platform();
+            let issue_date = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+
+            /* ================= Limits are checked when they are set ================= */
+            let coupon_terms = CouponTerms {
+                rate: Decimal::zero(),
+                frequency: 1,
+                dates: vec![],
+                day_count: DayCount::Act365Fixed,
+                business_day_convention: BusinessDayConvention::Unadjusted,
+                holidays: vec![],
+            };
+            let function_setup = FunctionSetup {
+                transfer: true,
+                burn: true,
+                mint_to_investor: true,
+                subscribe: true,
+            };
+            let now = blockchain.block_info().time;
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    factory_address.clone(),
+                    &FactoryExecuteMsg::InstantiateBondToken(Box::new(InstantiateBondTokenMsg {
+                        issuer: String::from(ISSUER),
+                        name: String::from("Bond Token"),
+                        symbol: String::from("BOND-TOKEN"),
+                        decimals: 18,
+                        initial_balances: vec![],
+                        function_setup: function_setup.clone(),
+                        additional_data: String::from("no additional data"),
+                        currency: currency_address.to_string(),
+                        allocation_fee_policy: AllocationFeePolicy::Subscribed,
+                        terms: BondTerms {
+                            denomination: Denomination {
+                                currency_amount: Uint128::from(3_u128),
+                                bond_amount: Uint128::from(2_u128),
+                            },
+                            subscription_fee_percentage: None,
+                            subscription_fee: None,
+                            subscription_limits: SubscriptionLimits {
+                                hard_cap: Some(Uint128::from(500_u128)),
+                                soft_cap: Some(Uint128::from(600_u128)),
+                                min_subscription: None,
+                                max_subscription_per_investor: None,
+                            },
+                            cancellation_fee_policy: CancellationFeePolicy::Keep,
+                            subscription_start: now,
+                            subscription_end: now.plus_seconds(SUBSCRIPTION_PERIOD),
+                            distribution_deadline: now
+                                .plus_seconds(SUBSCRIPTION_PERIOD + DISTRIBUTION_WINDOW),
+                            issue_date,
+                            maturity_date: issue_date.plus_seconds(BOND_TERM),
+                            coupon_terms: coupon_terms.clone(),
+                            identity_check: None,
+                            allowlist: None,
+                        },
+                    })),
+                    &[],
+                )
+                .unwrap_err();
+
+            // Limits are in currency net of the 50% subscription fee
+            let terms = BondTerms {
+                subscription_limits: SubscriptionLimits {
+                    hard_cap: Some(Uint128::from(600_u128)),
+                    soft_cap: Some(Uint128::from(500_u128)),
+                    min_subscription: Some(Uint128::from(100_u128)),
+                    max_subscription_per_investor: Some(Uint128::from(400_u128)),
+                },
+                ..bond_terms(&blockchain, coupon_terms)
+            };
+            let bond_token_address = create_bond_token_with_setup(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                function_setup,
+                terms,
+            );
+
+            /* ================= Subscriptions stay within the limits ================= */
+            assert_eq!(
+                subscribe_err(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    INVESTOR_1,
+                    100
+                ),
+                BondTokenErr::AdditionalError(AdditionalError::SubscriptionBelowMinimum {
+                    amount: Uint128::from(50_u128),
+                    minimum: Uint128::from(100_u128),
+                })
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            assert_eq!(
+                subscribe_err(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    INVESTOR_1,
+                    400
+                ),
+                BondTokenErr::AdditionalError(AdditionalError::SubscriptionAboveMaximum {
+                    amount: Uint128::from(500_u128),
+                    maximum: Uint128::from(400_u128),
+                })
+            );
+            assert_eq!(
+                subscribe_err(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    INVESTOR_2,
+                    800
+                ),
+                BondTokenErr::AdditionalError(AdditionalError::HardCapExceeded {
+                    amount: Uint128::from(700_u128),
+                    hard_cap: Uint128::from(600_u128),
+                })
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_2,
+                300,
+            );
+            let response: SubscriptionLimitsResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::AdditionalQueryMsg(
+                        AdditionalQueryMsg::SubscriptionLimits {},
+                    ),
+                )
+                .unwrap();
+            assert_eq!(response.total_subscribed, Uint128::from(450_u128));
+
+            /* ================= Refunds open once the window closes below the soft cap ================= */
+            let claim_refund_msg =
+                BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::ClaimRefund {});
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::TooEarly {
+                    action: String::from("refund"),
+                    time: issue_date,
+                })
+            );
+            set_block_time(&mut blockchain, issue_date);
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![InvesmentRule {
+                            investor: String::from(INVESTOR_1),
+                            currency_amount: Uint128::from(300_u128),
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::SoftCapNotReached {
+                    amount: Uint128::from(450_u128),
+                    soft_cap: Uint128::from(500_u128),
+                })
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    bond_token_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap();
+            // The bond never went ahead, so the fees come back with the subscriptions
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 1000);
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_2), 2000);
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::NothingToRefund {
+                    investor: String::from(INVESTOR_1),
+                })
+            );
+        }
+    }
+
+    mod cancellation_test {
+        use super::*;
+        use bond_token::{AdditionalError, BondTokenErr};
+        use placeholder::helpers::SubscriptionsResponse;
+
+        fn create_bond_token_with_policy(
+            blockchain: &mut App,
+            factory_address: &Addr,
+            currency_address: &Addr,
+            cancellation_fee_policy: CancellationFeePolicy,
+        ) -> Addr {
+            let terms = BondTerms {
+                cancellation_fee_policy,
+                ..bond_terms(
+                    blockchain,
+                    CouponTerms {
+                        rate: Decimal::zero(),
+                        frequency: 1,
+                        dates: vec![],
+                        day_count: DayCount::Act365Fixed,
+                        business_day_convention: BusinessDayConvention::Unadjusted,
+                        holidays: vec![],
+                    },
+                )
+            };
+            create_bond_token_with_setup(
+                blockchain,
+                factory_address,
+                currency_address,
+                FunctionSetup {
+                    transfer: true,
+                    burn: true,
+                    mint_to_investor: true,
+                    subscribe: true,
+                },
+                terms,
+            )
+        }
+
+        #[test]
+        fn cancellation_test() {
+            let (mut blockchain, factory_address, placeholder_address, _, currency_address) =
+                setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let refunding_bond_address = create_bond_token_with_policy(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CancellationFeePolicy::Refund,
+            );
+            let keeping_bond_address = create_bond_token_with_policy(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CancellationFeePolicy::Keep,
+            );
+            let reduce_msg = |amount: u128| {
+                BondTokenExecuteMsg::AdditionalExecuteMsg(
+                    AdditionalExecuteMsg::ReduceSubscription {
+                        amount: Uint128::from(amount),
+                    },
+                )
+            };
+            let cancel_msg = BondTokenExecuteMsg::AdditionalExecuteMsg(
+                AdditionalExecuteMsg::CancelSubscription {},
+            );
+
+            /* ================= Fee on the cancelled part is refunded ================= */
+            // 600 subscribed is 300 principal and 300 fee
+            subscribe(
+                &mut blockchain,
+                &refunding_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    refunding_bond_address.clone(),
+                    &reduce_msg(100),
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 600);
+            let response: SubscriptionsResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SubscriptionsOf {
+                        bond_token: refunding_bond_address.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                response.subscriptions[0].currency_amount,
+                Uint128::from(200_u128)
+            );
+            assert_eq!(
+                response.subscriptions[0].fee_amount,
+                Uint128::from(200_u128)
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    refunding_bond_address.clone(),
+                    &cancel_msg,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 1000);
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    refunding_bond_address.clone(),
+                    &cancel_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::NothingToRefund {
+                    investor: String::from(INVESTOR_1),
+                })
+            );
+
+            /* ================= Fee is kept otherwise ================= */
+            subscribe(
+                &mut blockchain,
+                &keeping_bond_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    keeping_bond_address.clone(),
+                    &reduce_msg(301),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::InsufficientSubscriptionAmount {
+                    amount: Uint128::from(301_u128),
+                })
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    keeping_bond_address.clone(),
+                    &cancel_msg,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_2), 1700);
+
+            /* ================= Only bond tokens refund subscriptions ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::RefundSubscription {
+                        investor: String::from(INVESTOR_2),
+                        currency_amount: Uint128::from(100_u128),
+                        refund_fee: true,
+                    },
+                    &[],
+                )
+                .unwrap_err();
+
+            /* ================= Subscriptions are final once the window closes ================= */
+            subscribe(
+                &mut blockchain,
+                &keeping_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                200,
+            );
+            set_block_time(&mut blockchain, subscription_end);
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    keeping_bond_address,
+                    &reduce_msg(50),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::TooLate {
+                    action: String::from("cancellation"),
+                    time: subscription_end,
+                })
+            );
+
+            // Platform keeps the cancelled fee of the second bond, the open subscription's fee
+            // can still be refunded
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address,
+                    &PlaceholderExecuteMsg::WithdrawSystemFee {
+                        currency: currency_address.to_string(),
+                        recipients: vec![FeeRecipient {
+                            recipient: String::from(ADMIN),
+                            amount: None,
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, ADMIN), 300);
+        }
+    }
+
+    mod cancel_issuance_test {
+        use super::*;
+        use bond_token::{
+            helpers::{BondScheduleResponse, Phase},
+            AdditionalError, BondTokenErr,
+        };
+        use placeholder::helpers::SubscriptionsResponse;
+
+        #[test]
+        fn cancel_issuance_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            let cancel_msg = |limit: Option<u32>| RouterExecuteMsg::CancelIssuance {
+                bond_token: bond_token_address.to_string(),
+                refund_fees: true,
+                limit,
+            };
+            let subscriptions = |blockchain: &App| -> SubscriptionsResponse {
+                blockchain
+                    .wrap()
+                    .query_wasm_smart(
+                        placeholder_address.to_string(),
+                        &PlaceholderQueryMsg::SubscriptionsOf {
+                            bond_token: bond_token_address.to_string(),
+                            start_after: None,
+                            limit: None,
+                        },
+                    )
+                    .unwrap()
+            };
+
+            /* ================= Only issuer or operators cancel ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &cancel_msg(None),
+                    &[],
+                )
+                .unwrap_err();
+
+            /* ================= Subscriptions are refunded page by page ================= */
+            // Router admin is an operator
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    router_address.clone(),
+                    &cancel_msg(Some(1)),
+                    &[],
+                )
+                .unwrap();
+            let schedule: BondScheduleResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    bond_token_address.to_string(),
+                    &BondTokenQueryMsg::AdditionalQueryMsg(AdditionalQueryMsg::BondSchedule {}),
+                )
+                .unwrap();
+            assert_eq!(schedule.current_phase, Phase::Cancelled);
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 1000);
+            assert_eq!(subscriptions(&blockchain).subscriptions.len(), 1);
+
+            // No more subscriptions once cancelled
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(AdditionalExecuteMsg::Subscribe {
+                        subscription_amount: Uint128::from(100_u128),
+                        fee_amount: Uint128::zero(),
+                    }),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::ActionNotAllowed {
+                    action: String::from("subscription"),
+                })
+            );
+
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &cancel_msg(None),
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_2), 2000);
+            assert!(subscriptions(&blockchain).subscriptions.is_empty());
+
+            /* ================= Cancelled bonds are never distributed ================= */
+            set_block_time(&mut blockchain, schedule.subscription_end);
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![],
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::InvalidPhase)
+            );
+        }
+    }
+
+    mod distribution_deadline_test {
+        use super::*;
+        use placeholder::{helpers::SubscriptionsResponse, PlaceholderErr};
+
+        #[test]
+        fn distribution_deadline_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            let claim_refund_msg = PlaceholderExecuteMsg::ClaimRefund {
+                bond_token: bond_token_address.to_string(),
+            };
+
+            /* ================= Refunds open at the distribution deadline ================= */
+            set_block_time(&mut blockchain, subscription_end);
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    placeholder_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(
+                err,
+                PlaceholderErr::DistributionDeadlineNotPassed { .. }
+            ));
+            set_block_time(
+                &mut blockchain,
+                subscription_end.plus_seconds(DISTRIBUTION_WINDOW),
+            );
+
+            // Subscription and fee come back
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    placeholder_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 1000);
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    placeholder_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::NothingToRefund { .. }));
+            let response: SubscriptionsResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SubscriptionsOf {
+                        bond_token: bond_token_address.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert!(response.subscriptions[0].refunded);
+            assert!(!response.subscriptions[1].refunded);
+
+            /* ================= Refunded investors are not distributed to ================= */
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![
+                            InvesmentRule {
+                                investor: String::from(INVESTOR_1),
+                                currency_amount: Uint128::from(270_u128),
+                            },
+                            InvesmentRule {
+                                investor: String::from(INVESTOR_2),
+                                currency_amount: Uint128::from(270_u128),
+                            },
+                        ],
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::NotSubscriber { .. }));
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![InvesmentRule {
+                            investor: String::from(INVESTOR_2),
+                            currency_amount: Uint128::from(270_u128),
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap();
+            let bond_balance = |blockchain: &App, investor: &str| -> u128 {
+                let response: BalanceResponse = blockchain
+                    .wrap()
+                    .query_wasm_smart(
+                        bond_token_address.to_string(),
+                        &BondTokenQueryMsg::Cw20QueryMsg(Cw20BaseQueryMsg::Balance {
+                            address: String::from(investor),
+                        }),
+                    )
+                    .unwrap();
+                response.balance.u128()
+            };
+            assert_eq!(bond_balance(&blockchain, INVESTOR_1), 0);
+            assert_eq!(bond_balance(&blockchain, INVESTOR_2), 180);
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 770);
+
+            // Nothing is refunded once released
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    placeholder_address,
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::CurrencyReleased { .. }));
+        }
+    }
+
+    mod subscription_index_test {
+        use super::*;
+        use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+        use placeholder::{
+            helpers::{
+                InvestorSubscriptionsResponse, Subscription, SubscriptionResponse,
+                SubscriptionTotalResponse, SubscriptionsResponse,
+            },
+            msg::{InstantiateMsg as PlaceholderInstantiateMsg, MigrateMsg},
+            state::LEGACY_SUBSCRIPTIONS,
+            PlaceholderErr,
+        };
+
+        const LEGACY_BOND: &str = "legacy_bond";
+
+        // Placeholder holding subscriptions in the layout used before they were indexed
+        fn legacy_instantiate(
+            mut deps: DepsMut,
+            env: Env,
+            info: MessageInfo,
+            msg: PlaceholderInstantiateMsg,
+        ) -> Result<Response, PlaceholderErr> {
+            let response = placeholder::contract::instantiate(deps.branch(), env, info, msg)?;
+            LEGACY_SUBSCRIPTIONS.save(
+                deps.storage,
+                Addr::unchecked(LEGACY_BOND),
+                &vec![
+                    Subscription {
+                        investor: Addr::unchecked(INVESTOR_2),
+                        currency_amount: Uint128::from(300_u128),
+                        fee_amount: Uint128::from(300_u128),
+                        refunded: false,
+                        sequence: 0,
+                    },
+                    Subscription {
+                        investor: Addr::unchecked(INVESTOR_1),
+                        currency_amount: Uint128::from(100_u128),
+                        fee_amount: Uint128::from(100_u128),
+                        refunded: false,
+                        sequence: 0,
+                    },
+                ],
+            )?;
+            Ok(response)
+        }
+
+        fn subscriptions_of(
+            blockchain: &App,
+            placeholder_address: &Addr,
+            bond_token: &str,
+            start_after: Option<&str>,
+            limit: Option<u32>,
+        ) -> Vec<String> {
+            let response: SubscriptionsResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SubscriptionsOf {
+                        bond_token: String::from(bond_token),
+                        start_after: start_after.map(String::from),
+                        limit,
+                    },
+                )
+                .unwrap();
+            response
+                .subscriptions
+                .into_iter()
+                .map(|subscription| subscription.investor)
+                .collect()
+        }
+
+        fn subscription_total(
+            blockchain: &App,
+            placeholder_address: &Addr,
+            bond_token: &str,
+        ) -> u128 {
+            let response: SubscriptionTotalResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SubscriptionTotal {
+                        bond_token: String::from(bond_token),
+                    },
+                )
+                .unwrap();
+            response.currency_amount.u128()
+        }
+
+        #[test]
+        fn subscription_queries_test() {
+            let (mut blockchain, factory_address, placeholder_address, _, currency_address) =
+                setup_platform();
+            let coupon_terms = CouponTerms {
+                rate: Decimal::zero(),
+                frequency: 1,
+                dates: vec![],
+                day_count: DayCount::Act365Fixed,
+                business_day_convention: BusinessDayConvention::Unadjusted,
+                holidays: vec![],
+            };
+            let first_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                coupon_terms.clone(),
+            );
+            let second_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                coupon_terms,
+            );
+            subscribe(
+                &mut blockchain,
+                &first_bond_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &first_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                200,
+            );
+            subscribe(
+                &mut blockchain,
+                &first_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                200,
+            );
+            subscribe(
+                &mut blockchain,
+                &second_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                400,
+            );
+
+            /* ================= Subscriptions of a bond are paged by investor ================= */
+            assert_eq!(
+                subscriptions_of(
+                    &blockchain,
+                    &placeholder_address,
+                    first_bond_address.as_str(),
+                    None,
+                    Some(1)
+                ),
+                vec![String::from(INVESTOR_1)]
+            );
+            assert_eq!(
+                subscriptions_of(
+                    &blockchain,
+                    &placeholder_address,
+                    first_bond_address.as_str(),
+                    Some(INVESTOR_1),
+                    None
+                ),
+                vec![String::from(INVESTOR_2)]
+            );
+            assert_eq!(
+                subscription_total(
+                    &blockchain,
+                    &placeholder_address,
+                    first_bond_address.as_str()
+                ),
+                500
+            );
+
+            /* ================= Single subscription and subscriptions of an investor ================= */
+            let response: SubscriptionResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SubscriptionOf {
+                        bond_token: first_bond_address.to_string(),
+                        investor: String::from(INVESTOR_1),
+                    },
+                )
+                .unwrap();
+            assert_eq!(response.currency_amount, Uint128::from(200_u128));
+            assert_eq!(response.fee_amount, Uint128::from(200_u128));
+            let response: SubscriptionResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SubscriptionOf {
+                        bond_token: second_bond_address.to_string(),
+                        investor: String::from(INVESTOR_2),
+                    },
+                )
+                .unwrap();
+            assert_eq!(response.currency_amount, Uint128::zero());
+            let response: InvestorSubscriptionsResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SubscriptionsByInvestor {
+                        investor: String::from(INVESTOR_1),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            let mut bond_tokens: Vec<String> = response
+                .subscriptions
+                .iter()
+                .map(|subscription| subscription.bond_token.clone())
+                .collect();
+            bond_tokens.sort();
+            let mut expected = vec![
+                first_bond_address.to_string(),
+                second_bond_address.to_string(),
+            ];
+            expected.sort();
+            assert_eq!(bond_tokens, expected);
+        }
+
+        #[test]
+        fn migrate_subscriptions_test() {
+            let mut blockchain = mock_blockchain();
+            let legacy_id = blockchain.store_code(Box::new(ContractWrapper::new(
+                placeholder::contract::execute,
+                legacy_instantiate,
+                placeholder::contract::query,
+            )));
+            let placeholder_id = blockchain.store_code(Box::new(
+                ContractWrapper::new(
+                    placeholder::contract::execute,
+                    placeholder::contract::instantiate,
+                    placeholder::contract::query,
+                )
+                .with_migrate(placeholder::contract::migrate),
+            ));
+            let placeholder_address = blockchain
+                .instantiate_contract(
+                    legacy_id,
+                    Addr::unchecked(ADMIN),
+                    &PlaceholderInstantiateMsg {},
+                    &[],
+                    "placeholder",
+                    Some(String::from(ADMIN)),
+                )
+                .unwrap();
+
+            blockchain
+                .migrate_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &MigrateMsg {},
+                    placeholder_id,
+                )
+                .unwrap();
+            assert_eq!(
+                subscriptions_of(&blockchain, &placeholder_address, LEGACY_BOND, None, None),
+                vec![String::from(INVESTOR_1), String::from(INVESTOR_2)]
+            );
+            assert_eq!(
+                subscription_total(&blockchain, &placeholder_address, LEGACY_BOND),
+                400
+            );
+
+            // Arrival order of the legacy vector is kept
+            let sequence_of = |investor: &str| -> u64 {
+                let response: SubscriptionResponse = blockchain
+                    .wrap()
+                    .query_wasm_smart(
+                        placeholder_address.to_string(),
+                        &PlaceholderQueryMsg::SubscriptionOf {
+                            bond_token: String::from(LEGACY_BOND),
+                            investor: String::from(investor),
+                        },
+                    )
+                    .unwrap();
+                response.sequence
+            };
+            assert_eq!(sequence_of(INVESTOR_2), 1);
+            assert_eq!(sequence_of(INVESTOR_1), 2);
+        }
+    }
+
+    mod escrow_state_test {
+        use super::*;
+        use placeholder::{
+            helpers::{EscrowResponse, EscrowState, InvesmentRule},
+            PlaceholderErr,
+        };
+
+        fn escrow_state(
+            blockchain: &App,
+            placeholder_address: &Addr,
+            bond_token: &Addr,
+        ) -> EscrowState {
+            let response: EscrowResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Escrow {
+                        bond_token: bond_token.to_string(),
+                    },
+                )
+                .unwrap();
+            response.state
+        }
+
+        #[test]
+        fn escrow_state_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let coupon_terms = CouponTerms {
+                rate: Decimal::zero(),
+                frequency: 1,
+                dates: vec![],
+                day_count: DayCount::Act365Fixed,
+                business_day_convention: BusinessDayConvention::Unadjusted,
+                holidays: vec![],
+            };
+            let distributed_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                coupon_terms.clone(),
+            );
+            let cancelled_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                coupon_terms,
+            );
+            subscribe(
+                &mut blockchain,
+                &distributed_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &distributed_bond_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &cancelled_bond_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            let response: EscrowResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Escrow {
+                        bond_token: distributed_bond_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(response.currency, currency_address.to_string());
+            assert_eq!(response.state, EscrowState::Open);
+            set_block_time(&mut blockchain, subscription_end);
+            let open_claims_msg = |bond_token: &Addr| PlaceholderExecuteMsg::OpenAllocationClaims {
+                bond_token: bond_token.to_string(),
+                investors: vec![],
+            };
+
+            /* ================= Rules can only name subscribers ================= */
+            let rule = |investor: &str, amount: u128| InvesmentRule {
+                investor: String::from(investor),
+                currency_amount: Uint128::from(amount),
+            };
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: distributed_bond_address.to_string(),
+                        investment_rules: vec![rule(INVESTOR_1, 270), rule(ISSUER, 300)],
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::NotSubscriber { .. }));
+
+            /* ================= Escrow is released once ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: distributed_bond_address.to_string(),
+                        investment_rules: vec![rule(INVESTOR_1, 270), rule(INVESTOR_2, 300)],
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                escrow_state(&blockchain, &placeholder_address, &distributed_bond_address),
+                EscrowState::Released
+            );
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 1070);
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 430);
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    router_address.clone(),
+                    placeholder_address.clone(),
+                    &open_claims_msg(&distributed_bond_address),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::CurrencyReleased { .. }));
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    router_address.clone(),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::CloseAllocationClaims {
+                        bond_token: distributed_bond_address.to_string(),
+                        issuer: String::from(ISSUER),
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::CurrencyReleased { .. }));
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 1070);
+
+            /* ================= Refunded escrow cannot be released ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::CancelIssuance {
+                        bond_token: cancelled_bond_address.to_string(),
+                        refund_fees: false,
+                        limit: None,
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                escrow_state(&blockchain, &placeholder_address, &cancelled_bond_address),
+                EscrowState::Refunded
+            );
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    router_address,
+                    placeholder_address,
+                    &open_claims_msg(&cancelled_bond_address),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::EscrowRefunded { .. }));
+        }
+    }
+
+    mod escrow_ledger_test {
+        use super::*;
+        use cosmwasm_std::{DepsMut, Env, Response, StdResult};
+        use placeholder::{
+            helpers::{
+                EscrowLedgerResponse, EscrowResponse, EscrowState, InvesmentRule, ReconcileResponse,
+            },
+            msg::MigrateMsg,
+            state::{BOND_ESCROWS, ESCROW_LEDGERS, PLACEHOLDER_PLATFORM, SYSTEM_FEE},
+            PlaceholderErr,
+        };
+
+        // Drops every bond's escrow as if it was registered before escrows were tracked, when
+        // fees went to the system fee as they were collected
+        fn legacy_migrate(
+            deps: DepsMut,
+            env: Env,
+            msg: MigrateMsg,
+        ) -> Result<Response, PlaceholderErr> {
+            for bond_token in PLACEHOLDER_PLATFORM.load(deps.storage)?.bond_tokens {
+                let currency = BOND_ESCROWS
+                    .load(deps.storage, bond_token.clone())?
+                    .currency;
+                let key = (currency.clone(), bond_token.clone());
+                let fees = ESCROW_LEDGERS.load(deps.storage, key.clone())?.fees;
+                SYSTEM_FEE.update(deps.storage, currency, |fee| -> StdResult<_> {
+                    Ok(fee.unwrap_or_default() + fees)
+                })?;
+                ESCROW_LEDGERS.remove(deps.storage, key);
+                BOND_ESCROWS.remove(deps.storage, bond_token);
+            }
+            placeholder::contract::migrate(deps, env, msg)
+        }
+
+        fn reconcile(
+            blockchain: &App,
+            placeholder_address: &Addr,
+            currency_address: &Addr,
+        ) -> ReconcileResponse {
+            blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Reconcile {
+                        currency: currency_address.to_string(),
+                    },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn escrow_ledger_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let coupon_terms = CouponTerms {
+                rate: Decimal::zero(),
+                frequency: 1,
+                dates: vec![],
+                day_count: DayCount::Act365Fixed,
+                business_day_convention: BusinessDayConvention::Unadjusted,
+                holidays: vec![],
+            };
+            let distributed_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                coupon_terms.clone(),
+            );
+            let other_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                coupon_terms,
+            );
+
+            /* ================= Subscriptions and fees are escrowed ================= */
+            subscribe(
+                &mut blockchain,
+                &distributed_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &distributed_bond_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &other_bond_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            let response = reconcile(&blockchain, &placeholder_address, &currency_address);
+            assert_eq!(response.escrowed, Uint128::from(900_u128));
+            assert_eq!(response.pending_fees, Uint128::from(900_u128));
+            assert_eq!(response.system_fee, Uint128::zero());
+            assert_eq!(response.balance, Uint128::from(1800_u128));
+            assert_eq!(response.surplus, Uint128::zero());
+            assert_eq!(response.shortfall, Uint128::zero());
+
+            /* ================= Cancellations, releases and excess are recorded ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    distributed_bond_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(
+                        AdditionalExecuteMsg::CancelSubscription {},
+                    ),
+                    &[],
+                )
+                .unwrap();
+            set_block_time(&mut blockchain, subscription_end);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: distributed_bond_address.to_string(),
+                        investment_rules: vec![InvesmentRule {
+                            investor: String::from(INVESTOR_2),
+                            currency_amount: Uint128::from(270_u128),
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap();
+            let ledger: EscrowLedgerResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::EscrowLedger {
+                        bond_token: distributed_bond_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                ledger,
+                EscrowLedgerResponse {
+                    currency: currency_address.to_string(),
+                    subscribed: Uint128::from(600_u128),
+                    fees: Uint128::from(600_u128),
+                    refunded: Uint128::from(330_u128),
+                    fees_refunded: Uint128::zero(),
+                    fees_settled: Uint128::from(600_u128),
+                    released: Uint128::from(270_u128),
+                    held: Uint128::zero(),
+                }
+            );
+            let response = reconcile(&blockchain, &placeholder_address, &currency_address);
+            assert_eq!(response.escrowed, Uint128::from(300_u128));
+            assert_eq!(response.pending_fees, Uint128::from(300_u128));
+            assert_eq!(response.system_fee, Uint128::from(600_u128));
+            assert_eq!(response.ledger_total, Uint128::from(1200_u128));
+            assert_eq!(response.balance, Uint128::from(1200_u128));
+            assert_eq!(response.shortfall, Uint128::zero());
+
+            /* ================= Currency sent outside the ledger shows as surplus ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    currency_address.clone(),
+                    &Cw20ExecuteMsg::Transfer {
+                        recipient: placeholder_address.to_string(),
+                        amount: Uint128::from(5_u128),
+                    },
+                    &[],
+                )
+                .unwrap();
+            let response = reconcile(&blockchain, &placeholder_address, &currency_address);
+            assert_eq!(response.surplus, Uint128::from(5_u128));
+            assert_eq!(response.shortfall, Uint128::zero());
+        }
+
+        #[test]
+        fn backfill_escrow_test() {
+            let (mut blockchain, factory_address, placeholder_address, _, currency_address) =
+                setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            let placeholder_id = blockchain.store_code(Box::new(
+                ContractWrapper::new(
+                    placeholder::contract::execute,
+                    placeholder::contract::instantiate,
+                    placeholder::contract::query,
+                )
+                .with_migrate(legacy_migrate),
+            ));
+            blockchain
+                .migrate_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &MigrateMsg {},
+                    placeholder_id,
+                )
+                .unwrap();
+
+            /* ================= A bond still subscribing gets an open escrow ================= */
+            let escrow: EscrowResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Escrow {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(escrow.currency, currency_address.to_string());
+            assert_eq!(escrow.state, EscrowState::Open);
+            let ledger: EscrowLedgerResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::EscrowLedger {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(ledger.held, Uint128::from(300_u128));
+            assert_eq!(ledger.fees, Uint128::from(300_u128));
+
+            // Its fees are pending again
+            let response = reconcile(&blockchain, &placeholder_address, &currency_address);
+            assert_eq!(response.pending_fees, Uint128::from(300_u128));
+            assert_eq!(response.system_fee, Uint128::zero());
+            assert_eq!(response.shortfall, Uint128::zero());
+
+            /* ================= Investors can claim refunds from it ================= */
+            set_block_time(
+                &mut blockchain,
+                subscription_end.plus_seconds(DISTRIBUTION_WINDOW),
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    placeholder_address,
+                    &PlaceholderExecuteMsg::ClaimRefund {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 1000);
+        }
+    }
+
+    mod system_fee_test {
+        use super::*;
+        use placeholder::{
+            helpers::{FeeWithdrawalsResponse, SystemFeesResponse},
+            PlaceholderErr,
+        };
+
+        fn system_fee(blockchain: &App, placeholder_address: &Addr) -> u128 {
+            let response: SystemFeesResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SystemFees {},
+                )
+                .unwrap();
+            response.fees.first().map_or(0, |fee| fee.amount.u128())
+        }
+
+        #[test]
+        fn system_fee_test() {
+            let (mut blockchain, factory_address, placeholder_address, _, currency_address) =
+                setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_2,
+                1000,
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                400,
+            );
+            let withdraw_msg =
+                |recipients: Vec<FeeRecipient>| PlaceholderExecuteMsg::WithdrawSystemFee {
+                    currency: currency_address.to_string(),
+                    recipients,
+                };
+            let recipient = |recipient: &str, amount: Option<u128>| FeeRecipient {
+                recipient: String::from(recipient),
+                amount: amount.map(Uint128::from),
+            };
+
+            /* ================= Fees that may still be refunded are not withdrawable ================= */
+            assert_eq!(system_fee(&blockchain, &placeholder_address), 0);
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &withdraw_msg(vec![recipient(ADMIN, None)]),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::NothingToWithdraw { .. }));
+
+            // A cancellation that keeps the fee settles it
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(
+                        AdditionalExecuteMsg::CancelSubscription {},
+                    ),
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(system_fee(&blockchain, &placeholder_address), 500);
+
+            /* ================= Only admin withdraws, never more than collected ================= */
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    placeholder_address.clone(),
+                    &withdraw_msg(vec![recipient(OPERATOR, None)]),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::NotAdmin { .. }));
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &withdraw_msg(vec![
+                        recipient(ADMIN, Some(300)),
+                        recipient(OPERATOR, Some(300)),
+                    ]),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::InsufficientSystemFee { .. }));
+
+            /* ================= Partial withdrawal to several recipients ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &withdraw_msg(vec![
+                        recipient(ADMIN, Some(100)),
+                        recipient(OPERATOR, Some(50)),
+                    ]),
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, ADMIN), 100);
+            assert_eq!(balance_of(&blockchain, &currency_address, OPERATOR), 50);
+            assert_eq!(system_fee(&blockchain, &placeholder_address), 350);
+
+            /* ================= Remainder is withdrawn once ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &withdraw_msg(vec![recipient(ADMIN, None)]),
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, ADMIN), 450);
+            assert_eq!(system_fee(&blockchain, &placeholder_address), 0);
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &withdraw_msg(vec![recipient(ADMIN, None)]),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::NothingToWithdraw { .. }));
+
+            /* ================= Withdrawals never block pending refunds ================= */
+            set_block_time(
+                &mut blockchain,
+                subscription_end.plus_seconds(DISTRIBUTION_WINDOW),
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::ClaimRefund {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 1000);
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, placeholder_address.as_str()),
+                0
+            );
+
+            /* ================= Withdrawal history is paged ================= */
+            let history =
+                |start_after: Option<u64>, limit: Option<u32>| -> FeeWithdrawalsResponse {
+                    blockchain
+                        .wrap()
+                        .query_wasm_smart(
+                            placeholder_address.to_string(),
+                            &PlaceholderQueryMsg::FeeWithdrawals { start_after, limit },
+                        )
+                        .unwrap()
+                };
+            let response = history(None, Some(2));
+            assert_eq!(response.withdrawals.len(), 2);
+            assert_eq!(response.withdrawals[1].recipient, String::from(OPERATOR));
+            assert_eq!(response.withdrawals[1].amount, Uint128::from(50_u128));
+            let response = history(Some(2), None);
+            assert_eq!(response.withdrawals.len(), 1);
+            assert_eq!(response.withdrawals[0].id, 3);
+            assert_eq!(response.withdrawals[0].amount, Uint128::from(350_u128));
+        }
+    }
+
+    mod fee_split_test {
+        use super::*;
+        use placeholder::{
+            helpers::{AccruedFeesResponse, FeeShare, FeeSplitResponse, ReconcileResponse},
+            PlaceholderErr,
+        };
+
+        fn accrued_fee(blockchain: &App, placeholder_address: &Addr, recipient: &str) -> u128 {
+            let response: AccruedFeesResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::AccruedFees {
+                        recipient: String::from(recipient),
+                    },
+                )
+                .unwrap();
+            response.fees.iter().map(|fee| fee.amount.u128()).sum()
+        }
+
+        #[test]
+        fn fee_split_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let coupon_terms = CouponTerms {
+                rate: Decimal::zero(),
+                frequency: 1,
+                dates: vec![],
+                day_count: DayCount::Act365Fixed,
+                business_day_convention: BusinessDayConvention::Unadjusted,
+                holidays: vec![],
+            };
+            let arranged_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                coupon_terms.clone(),
+            );
+            let other_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                coupon_terms,
+            );
+            let share = |recipient: &str, weight: u128| FeeShare {
+                recipient: String::from(recipient),
+                weight: Uint128::from(weight),
+            };
+
+            /* ================= Admin sets a global split and a bond's own split ================= */
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::SetFeeSplit {
+                        bond_token: None,
+                        shares: Some(vec![share(OPERATOR, 2000)]),
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::NotAdmin { .. }));
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::SetFeeSplit {
+                        bond_token: None,
+                        shares: Some(vec![share(OPERATOR, 6000), share(ISSUER, 6000)]),
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::InvalidFeeSplit {}));
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::SetFeeSplit {
+                        bond_token: None,
+                        shares: Some(vec![share(OPERATOR, 2000)]),
+                    },
+                    &[],
+                )
+                .unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::SetFeeSplit {
+                        bond_token: Some(arranged_bond_address.to_string()),
+                        shares: Some(vec![share(OPERATOR, 2000), share(ISSUER, 3000)]),
+                    },
+                    &[],
+                )
+                .unwrap();
+            let response: FeeSplitResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::FeeSplit {
+                        bond_token: Some(other_bond_address.to_string()),
+                    },
+                )
+                .unwrap();
+            assert_eq!(response.shares, vec![share(OPERATOR, 2000)]);
+
+            /* ================= Shares are credited once fees are settled ================= */
+            subscribe(
+                &mut blockchain,
+                &arranged_bond_address,
+                &currency_address,
+                INVESTOR_2,
+                1000,
+            );
+            subscribe(
+                &mut blockchain,
+                &other_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &other_bond_address,
+                &currency_address,
+                INVESTOR_2,
+                600,
+            );
+            assert_eq!(accrued_fee(&blockchain, &placeholder_address, OPERATOR), 0);
+
+            // Kept on cancellation
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    other_bond_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(
+                        AdditionalExecuteMsg::CancelSubscription {},
+                    ),
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(accrued_fee(&blockchain, &placeholder_address, OPERATOR), 60);
+
+            // Kept when the issuance is cancelled
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::CancelIssuance {
+                        bond_token: arranged_bond_address.to_string(),
+                        refund_fees: false,
+                        limit: None,
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                accrued_fee(&blockchain, &placeholder_address, OPERATOR),
+                160
+            );
+            assert_eq!(accrued_fee(&blockchain, &placeholder_address, ISSUER), 150);
+
+            /* ================= Recipients claim their own shares ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::ClaimFees {},
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, OPERATOR), 160);
+            assert_eq!(accrued_fee(&blockchain, &placeholder_address, OPERATOR), 0);
+
+            /* ================= Claims never block refunds of pending fees ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::CancelIssuance {
+                        bond_token: other_bond_address.to_string(),
+                        refund_fees: true,
+                        limit: None,
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_2), 1500);
+            assert_eq!(accrued_fee(&blockchain, &placeholder_address, ISSUER), 150);
+            let response: ReconcileResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Reconcile {
+                        currency: currency_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(response.pending_fees, Uint128::zero());
+            assert_eq!(response.system_fee, Uint128::from(490_u128));
+            assert_eq!(response.accrued_fees, Uint128::from(150_u128));
+            assert_eq!(response.shortfall, Uint128::zero());
+            assert_eq!(response.surplus, Uint128::zero());
+
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::ClaimFees {},
+                    &[],
+                )
+                .unwrap();
+            for recipient in [OPERATOR, ISSUER] {
+                let err: PlaceholderErr = blockchain
+                    .execute_contract(
+                        Addr::unchecked(recipient),
+                        placeholder_address.clone(),
+                        &PlaceholderExecuteMsg::ClaimFees {},
+                        &[],
+                    )
+                    .unwrap_err()
+                    .downcast()
+                    .unwrap();
+                assert!(matches!(err, PlaceholderErr::NothingToClaim { .. }));
+            }
+        }
+    }
+
+    mod allocation_policy_test {
+        use super::*;
+        use placeholder::helpers::{
+            AllocationPolicy, AllocationResponse, EscrowResponse, EscrowState, SubscriptionResponse,
+        };
+        use router::{
+            helpers::{JobKind, JobProgressResponse},
+            msg::QueryMsg as RouterQueryMsg,
+            RouterErr,
+        };
+
+        // Every page of the preview, `limit` subscriptions at a time
+        fn preview_pages(
+            blockchain: &App,
+            placeholder_address: &Addr,
+            bond_token_address: &Addr,
+            policy: AllocationPolicy,
+            limit: Option<u32>,
+        ) -> Result<Vec<(String, u128)>, cosmwasm_std::StdError> {
+            let mut allocations = vec![];
+            let mut cursor = None;
+            loop {
+                let response: AllocationResponse = blockchain.wrap().query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::PreviewAllocation {
+                        bond_token: bond_token_address.to_string(),
+                        policy: policy.clone(),
+                        cursor,
+                        limit,
+                    },
+                )?;
+                allocations.extend(
+                    response
+                        .allocations
+                        .into_iter()
+                        .map(|rule| (rule.investor, rule.currency_amount.u128())),
+                );
+                match response.next {
+                    Some(next) => cursor = Some(next),
+                    None => return Ok(allocations),
+                }
+            }
+        }
+
+        fn preview(
+            blockchain: &App,
+            placeholder_address: &Addr,
+            bond_token_address: &Addr,
+            policy: AllocationPolicy,
+        ) -> Result<Vec<(String, u128)>, cosmwasm_std::StdError> {
+            preview_pages(
+                blockchain,
+                placeholder_address,
+                bond_token_address,
+                policy,
+                None,
+            )
+        }
+
+        #[test]
+        fn allocation_policy_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+
+            // 1100 subscribed in this order: 600, 300 then 200
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_2,
+                1200,
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                ISSUER,
+                400,
+            );
+            let response: SubscriptionResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::SubscriptionOf {
+                        bond_token: bond_token_address.to_string(),
+                        investor: String::from(ISSUER),
+                    },
+                )
+                .unwrap();
+            assert_eq!(response.sequence, 3);
+            let expected = |amounts: [u128; 3]| -> Vec<(String, u128)> {
+                vec![
+                    (String::from(INVESTOR_2), amounts[0]),
+                    (String::from(INVESTOR_1), amounts[1]),
+                    (String::from(ISSUER), amounts[2]),
+                ]
+            };
+
+            /* ================= Pro-rata rounds the running total down, in arrival order ================= */
+            let pro_rata = AllocationPolicy::ProRata {
+                total: Some(Uint128::from(551_u128)),
+            };
+            assert_eq!(
+                preview(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    pro_rata.clone()
+                ),
+                Ok(expected([300, 150, 101]))
+            );
+            assert_eq!(
+                preview(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    AllocationPolicy::ProRata {
+                        total: Some(Uint128::from(2000_u128)),
+                    }
+                ),
+                Ok(expected([600, 300, 200]))
+            );
+            // Without a hard cap, no total allocates every subscription in full
+            assert_eq!(
+                preview(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    AllocationPolicy::ProRata { total: None }
+                ),
+                Ok(expected([600, 300, 200]))
+            );
+
+            /* ================= First come, first served ================= */
+            assert_eq!(
+                preview(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    AllocationPolicy::FirstComeFirstServed {
+                        total: Some(Uint128::from(700_u128)),
+                    }
+                ),
+                Ok(expected([600, 100, 0]))
+            );
+
+            /* ================= Minimum ticket, then pro-rata ================= */
+            assert_eq!(
+                preview(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    AllocationPolicy::MinimumTicket {
+                        total: Some(Uint128::from(550_u128)),
+                        minimum_ticket: Uint128::from(150_u128),
+                    }
+                ),
+                Ok(expected([219, 173, 158]))
+            );
+            // Tickets are counted over every page before the first one is allocated
+            assert_eq!(
+                preview_pages(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    AllocationPolicy::MinimumTicket {
+                        total: Some(Uint128::from(550_u128)),
+                        minimum_ticket: Uint128::from(150_u128),
+                    },
+                    Some(1)
+                ),
+                Ok(expected([219, 173, 158]))
+            );
+            assert!(preview(
+                &blockchain,
+                &placeholder_address,
+                &bond_token_address,
+                AllocationPolicy::MinimumTicket {
+                    total: Some(Uint128::from(550_u128)),
+                    minimum_ticket: Uint128::from(200_u128),
+                }
+            )
+            .is_err());
+
+            /* ================= Anchors first, then pro-rata ================= */
+            assert_eq!(
+                preview(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    AllocationPolicy::AnchorsFirst {
+                        total: Some(Uint128::from(550_u128)),
+                        anchors: vec![String::from(ISSUER)],
+                    }
+                ),
+                Ok(expected([233, 117, 200]))
+            );
+            assert_eq!(
+                preview_pages(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    AllocationPolicy::AnchorsFirst {
+                        total: Some(Uint128::from(550_u128)),
+                        anchors: vec![String::from(ISSUER)],
+                    },
+                    Some(2)
+                ),
+                Ok(expected([233, 117, 200]))
+            );
+            assert!(preview(
+                &blockchain,
+                &placeholder_address,
+                &bond_token_address,
+                AllocationPolicy::AnchorsFirst {
+                    total: Some(Uint128::from(550_u128)),
+                    anchors: vec![String::from(OPERATOR)],
+                }
+            )
+            .is_err());
+
+            assert_eq!(
+                preview_pages(
+                    &blockchain,
+                    &placeholder_address,
+                    &bond_token_address,
+                    pro_rata.clone(),
+                    Some(1)
+                ),
+                Ok(expected([300, 150, 101]))
+            );
+
+            /* ================= Issuer distributes by policy ================= */
+            set_block_time(&mut blockchain, subscription_end);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::DistributeByPolicy {
+                        bond_token: bond_token_address.to_string(),
+                        policy: pro_rata,
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 750);
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 550);
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_2), 1100);
+        }
+
+        #[test]
+        fn allocation_job_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+
+            let other_bond_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            subscribe(
+                &mut blockchain,
+                &other_bond_address,
+                &currency_address,
+                INVESTOR_1,
+                100,
+            );
+
+            // 12 investors escrow 50 each, more than one default page
+            let investors: Vec<String> = (0..12).map(|i| format!("investor{:02}", i)).collect();
+            for investor in &investors {
+                blockchain
+                    .execute_contract(
+                        Addr::unchecked(INVESTOR_2),
+                        currency_address.clone(),
+                        &Cw20ExecuteMsg::Transfer {
+                            recipient: investor.clone(),
+                            amount: Uint128::from(100_u128),
+                        },
+                        &[],
+                    )
+                    .unwrap();
+                subscribe(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    investor,
+                    100,
+                );
+            }
+            let job_progress = |blockchain: &App, kind: JobKind| -> JobProgressResponse {
+                blockchain
+                    .wrap()
+                    .query_wasm_smart(
+                        router_address.to_string(),
+                        &RouterQueryMsg::JobProgress {
+                            bond_token: bond_token_address.to_string(),
+                            kind,
+                        },
+                    )
+                    .unwrap()
+            };
+            let distribute_by_policy = RouterExecuteMsg::DistributeByPolicy {
+                bond_token: bond_token_address.to_string(),
+                policy: AllocationPolicy::ProRata {
+                    total: Some(Uint128::from(300_u128)),
+                },
+            };
+            let continue_distribute = RouterExecuteMsg::ContinueDistribute {
+                bond_token: bond_token_address.to_string(),
+                limit: None,
+            };
+
+            /* ================= Rules added by the issuer are not mixed with a policy's ================= */
+            set_block_time(&mut blockchain, subscription_end);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::AddDistributionRules {
+                        bond_token: other_bond_address.to_string(),
+                        investment_rules: vec![InvesmentRule {
+                            investor: String::from(INVESTOR_1),
+                            currency_amount: Uint128::from(50_u128),
+                        }],
+                    },
+                    &[],
+                )
+                .unwrap();
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::DistributeByPolicy {
+                        bond_token: other_bond_address.to_string(),
+                        policy: AllocationPolicy::ProRata { total: None },
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::DistributionRulesAdded { .. }));
+
+            /* ================= The first page of allocations is staged ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &distribute_by_policy,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                job_progress(&blockchain, JobKind::Allocation),
+                JobProgressResponse {
+                    issuer: String::from(ISSUER),
+                    last_account: Some(investors[9].clone()),
+                    processed: 10,
+                    amount: Uint128::from(250_u128),
+                    done: false,
+                }
+            );
+            assert_eq!(balance_of(&blockchain, &currency_address, &investors[0]), 0);
+            let escrow: EscrowResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Escrow {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(escrow.state, EscrowState::Open);
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![],
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::JobStarted { .. }));
+
+            /* ================= Distribution starts after the last page ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &continue_distribute,
+                    &[],
+                )
+                .unwrap();
+            let progress = job_progress(&blockchain, JobKind::Allocation);
+            assert_eq!(progress.processed, 12);
+            assert_eq!(progress.amount, Uint128::from(300_u128));
+            assert!(progress.done);
+            let progress = job_progress(&blockchain, JobKind::Distribution);
+            assert_eq!(progress.processed, 10);
+            assert!(!progress.done);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &continue_distribute,
+                    &[],
+                )
+                .unwrap();
+            let progress = job_progress(&blockchain, JobKind::Distribution);
+            assert_eq!(progress.amount, Uint128::from(300_u128));
+            assert!(progress.done);
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 800);
+            for investor in &investors {
+                assert_eq!(balance_of(&blockchain, &currency_address, investor), 25);
+            }
+        }
+    }
+
+    mod merkle_allocation_test {
+        use super::*;
+        use cosmwasm_std::Binary;
+        use placeholder::{
+            helpers::{EscrowResponse, EscrowState},
+            PlaceholderErr,
+        };
+        use router::{
+            helpers::{verify_merkle_proof, AllocationClaimResponse, AllocationLeaf},
+            RouterErr,
+        };
+        use sha2::{Digest, Sha256};
+
+        fn hash_pair(left: &[u8], right: &[u8]) -> Vec<u8> {
+            let (first, second) = if left <= right {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            let mut hasher = Sha256::new();
+            hasher.update(first);
+            hasher.update(second);
+            hasher.finalize().to_vec()
+        }
+
+        // Builds every level of the tree, an odd node is promoted to the next level unchanged
+        fn build_tree(leaves: &[AllocationLeaf]) -> Vec<Vec<Vec<u8>>> {
+            let mut levels = vec![leaves
+                .iter()
+                .map(|leaf| leaf.leaf_hash().unwrap())
+                .collect::<Vec<_>>()];
+            while levels.last().unwrap().len() > 1 {
+                let next = levels
+                    .last()
+                    .unwrap()
+                    .chunks(2)
+                    .map(|pair| match pair {
+                        [left, right] => hash_pair(left, right),
+                        [single] => single.clone(),
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                levels.push(next);
+            }
+            levels
+        }
+
+        fn merkle_root(levels: &[Vec<Vec<u8>>]) -> Binary {
+            Binary::from(levels.last().unwrap()[0].clone())
+        }
+
+        fn merkle_proof(levels: &[Vec<Vec<u8>>], mut index: usize) -> Vec<Binary> {
+            let mut proof = vec![];
+            for level in &levels[..levels.len() - 1] {
+                let sibling = index ^ 1;
+                if sibling < level.len() {
+                    proof.push(Binary::from(level[sibling].clone()));
+                }
+                index /= 2;
+            }
+            proof
+        }
+
+        fn leaf(investor: &str, amount: u128) -> AllocationLeaf {
+            AllocationLeaf {
+                investor: String::from(investor),
+                amount: Uint128::from(amount),
+            }
+        }
+
+        #[test]
+        fn merkle_proof_test() {
+            for size in 1..=9_u128 {
+                let leaves: Vec<AllocationLeaf> = (0..size)
+                    .map(|i| leaf(&format!("investor{}", i), 100 * (i + 1)))
+                    .collect();
+                let levels = build_tree(&leaves);
+                let root = merkle_root(&levels);
+                for (index, leaf) in leaves.iter().enumerate() {
+                    let proof = merkle_proof(&levels, index);
+                    assert!(verify_merkle_proof(
+                        leaf.leaf_hash().unwrap(),
+                        &proof,
+                        &root
+                    ));
+
+                    // A tampered amount or a foreign proof must not verify
+                    let tampered = AllocationLeaf {
+                        investor: leaf.investor.clone(),
+                        amount: leaf.amount + Uint128::one(),
+                    };
+                    assert!(!verify_merkle_proof(
+                        tampered.leaf_hash().unwrap(),
+                        &proof,
+                        &root
+                    ));
+                    if size > 1 {
+                        let other = merkle_proof(&levels, (index + 1) % leaves.len());
+                        if other != proof {
+                            assert!(!verify_merkle_proof(
+                                leaf.leaf_hash().unwrap(),
+                                &other,
+                                &root
+                            ));
+                        }
+                    }
+                }
+            }
+
+            // Siblings must be sha256 digests
+            let leaves = vec![leaf(INVESTOR_1, 1), leaf(INVESTOR_2, 2)];
+            let levels = build_tree(&leaves);
+            let mut proof = merkle_proof(&levels, 0);
+            proof[0] = Binary::from(vec![0_u8; 31]);
+            assert!(!verify_merkle_proof(
+                leaves[0].leaf_hash().unwrap(),
+                &proof,
+                &merkle_root(&levels)
+            ));
+        }
+
+        #[test]
+        fn merkle_allocation_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+
+            // 300 and 600 escrowed after the 50% fee
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_1,
+                600,
+            );
+            subscribe(
+                &mut blockchain,
+                &bond_token_address,
+                &currency_address,
+                INVESTOR_2,
+                1200,
+            );
+            let leaves = vec![
+                leaf(INVESTOR_1, 150),
+                leaf(INVESTOR_2, 600),
+                leaf(OPERATOR, 0),
+            ];
+            let levels = build_tree(&leaves);
+            let root = merkle_root(&levels);
+
+            /* ================= Only the issuer commits a 32-byte root ================= */
+            set_block_time(&mut blockchain, subscription_end);
+            let commit = |merkle_root: Binary| RouterExecuteMsg::CommitAllocationRoot {
+                bond_token: bond_token_address.to_string(),
+                merkle_root,
+            };
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &commit(root.clone()),
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &commit(Binary::from(vec![1_u8; 20])),
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &commit(root.clone()),
+                    &[],
+                )
+                .unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &commit(root),
+                    &[],
+                )
+                .unwrap_err();
+
+            // Allocations are now claimed leaf by leaf, rules can no longer be distributed
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: vec![],
+                    },
+                    &[],
+                )
+                .unwrap_err();
+
+            /* ================= Investors claim their leaves ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    router_address.clone(),
+                    &RouterExecuteMsg::ClaimAllocation {
+                        bond_token: bond_token_address.to_string(),
+                        amount: Uint128::from(700_u128),
+                        proof: merkle_proof(&levels, 1),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    router_address.clone(),
+                    &RouterExecuteMsg::ClaimAllocation {
+                        bond_token: bond_token_address.to_string(),
+                        amount: Uint128::from(150_u128),
+                        proof: merkle_proof(&levels, 0),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            for (index, investor) in [INVESTOR_1, INVESTOR_2].into_iter().enumerate() {
+                blockchain
+                    .execute_contract(
+                        Addr::unchecked(investor),
+                        router_address.clone(),
+                        &RouterExecuteMsg::ClaimAllocation {
+                            bond_token: bond_token_address.to_string(),
+                            amount: leaves[index].amount,
+                            proof: merkle_proof(&levels, index),
+                        },
+                        &[],
+                    )
+                    .unwrap();
+            }
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &RouterExecuteMsg::ClaimAllocation {
+                        bond_token: bond_token_address.to_string(),
+                        amount: Uint128::from(150_u128),
+                        proof: merkle_proof(&levels, 0),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+
+            // 3 currency buy 2 bonds, the excess over the leaf is refunded
+            assert_eq!(
+                bond_balance_of(&blockchain, &bond_token_address, INVESTOR_1),
+                100
+            );
+            assert_eq!(
+                bond_balance_of(&blockchain, &bond_token_address, INVESTOR_2),
+                400
+            );
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_1), 550);
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_2), 800);
+            let response: AllocationClaimResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    router_address.to_string(),
+                    &router::msg::QueryMsg::AllocationClaim {
+                        bond_token: bond_token_address.to_string(),
+                        investor: String::from(INVESTOR_1),
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                response,
+                AllocationClaimResponse {
+                    claimed: true,
+                    currency_amount: Uint128::from(150_u128),
+                }
+            );
+
+            /* ================= Issuer collects the claimed proceeds once ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &RouterExecuteMsg::CollectProceeds {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::CollectProceeds {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 1250);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::CollectProceeds {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+
+            // Claimed subscriptions cannot be refunded after the deadline
+            set_block_time(
+                &mut blockchain,
+                subscription_end.plus_seconds(DISTRIBUTION_WINDOW + 1),
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    placeholder_address,
+                    &PlaceholderExecuteMsg::ClaimRefund {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+        }
+
+        #[test]
+        fn merkle_close_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+            for (investor, amount) in [(INVESTOR_1, 600), (INVESTOR_2, 600), (ISSUER, 300)] {
+                subscribe(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    investor,
+                    amount,
+                );
+            }
+            let leaves = vec![
+                leaf(INVESTOR_1, 150),
+                leaf(INVESTOR_2, 300),
+                leaf(ISSUER, 150),
+            ];
+            let levels = build_tree(&leaves);
+            set_block_time(&mut blockchain, subscription_end);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::CommitAllocationRoot {
+                        bond_token: bond_token_address.to_string(),
+                        merkle_root: merkle_root(&levels),
+                    },
+                    &[],
+                )
+                .unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &RouterExecuteMsg::ClaimAllocation {
+                        bond_token: bond_token_address.to_string(),
+                        amount: leaves[0].amount,
+                        proof: merkle_proof(&levels, 0),
+                    },
+                    &[],
+                )
+                .unwrap();
+            let claim_refund_msg = PlaceholderExecuteMsg::ClaimRefund {
+                bond_token: bond_token_address.to_string(),
+            };
+            let close_msg = RouterExecuteMsg::CloseAllocationRoot {
+                bond_token: bond_token_address.to_string(),
+            };
+
+            /* ================= Claims stay open until the distribution deadline ================= */
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &close_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::AllocationClaimsOpen { .. }));
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    placeholder_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap_err();
+
+            // The escrow cannot be released while subscriptions are still held
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    router_address.clone(),
+                    placeholder_address.clone(),
+                    &PlaceholderExecuteMsg::CloseAllocationClaims {
+                        bond_token: bond_token_address.to_string(),
+                        issuer: String::from(ISSUER),
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(
+                err,
+                PlaceholderErr::SubscriptionsUnreleased { .. }
+            ));
+
+            /* ================= Unclaimed subscriptions are refunded after it ================= */
+            set_block_time(
+                &mut blockchain,
+                subscription_end.plus_seconds(DISTRIBUTION_WINDOW),
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_2),
+                    placeholder_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_2), 2000);
+            let err: PlaceholderErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    placeholder_address.clone(),
+                    &claim_refund_msg,
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, PlaceholderErr::AllocationClaimed { .. }));
+
+            /* ================= Closing refunds the rest and pays the issuer ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &close_msg,
+                    &[],
+                )
+                .unwrap_err();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &close_msg,
+                    &[],
+                )
+                .unwrap();
+
+            // Issuer's own unclaimed 150 comes back with its 150 fee, like a refund claim would,
+            // along with the 150 claimed by the first investor
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 650);
+            assert_eq!(bond_balance_of(&blockchain, &bond_token_address, ISSUER), 0);
+            let escrow: EscrowResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Escrow {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(escrow.state, EscrowState::Released);
+
+            // Only the first investor's fee is left in the placeholder
+            assert_eq!(
+                balance_of(&blockchain, &currency_address, placeholder_address.as_str()),
+                300
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address,
+                    &RouterExecuteMsg::ClaimAllocation {
+                        bond_token: bond_token_address.to_string(),
+                        amount: leaves[2].amount,
+                        proof: merkle_proof(&levels, 2),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+        }
+    }
+
+    mod batch_job_test {
+        use super::*;
+        use bond_token::AdditionalError;
+        use placeholder::helpers::{EscrowResponse, EscrowState};
+        use router::{
+            helpers::{JobKind, JobProgressResponse},
+            msg::QueryMsg as RouterQueryMsg,
+            RouterErr,
+        };
+
+        fn job_progress(
+            blockchain: &App,
+            router_address: &Addr,
+            bond_token_address: &Addr,
+            kind: JobKind,
+        ) -> JobProgressResponse {
+            blockchain
+                .wrap()
+                .query_wasm_smart(
+                    router_address.to_string(),
+                    &RouterQueryMsg::JobProgress {
+                        bond_token: bond_token_address.to_string(),
+                        kind,
+                    },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn batch_job_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let maturity_date = subscription_end.plus_seconds(BOND_TERM);
+            let bond_token_address = create_bond_token(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                CouponTerms {
+                    rate: Decimal::zero(),
+                    frequency: 1,
+                    dates: vec![maturity_date],
+                    day_count: DayCount::Act365Fixed,
+                    business_day_convention: BusinessDayConvention::Unadjusted,
+                    holidays: vec![],
+                },
+            );
+
+            // 12 investors escrow 50 each, more than one default page
+            let investors: Vec<String> = (0..12).map(|i| format!("investor{:02}", i)).collect();
+            for investor in &investors {
+                blockchain
+                    .execute_contract(
+                        Addr::unchecked(INVESTOR_2),
+                        currency_address.clone(),
+                        &Cw20ExecuteMsg::Transfer {
+                            recipient: investor.clone(),
+                            amount: Uint128::from(100_u128),
+                        },
+                        &[],
+                    )
+                    .unwrap();
+                subscribe(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    investor,
+                    100,
+                );
+            }
+
+            /* ================= Rules are staged in several messages ================= */
+            set_block_time(&mut blockchain, subscription_end);
+            let rules = |investors: &[String]| -> Vec<InvesmentRule> {
+                investors
+                    .iter()
+                    .map(|investor| InvesmentRule {
+                        investor: investor.clone(),
+                        currency_amount: Uint128::from(30_u128),
+                    })
+                    .collect()
+            };
+            let add_rules = |investors: &[String]| RouterExecuteMsg::AddDistributionRules {
+                bond_token: bond_token_address.to_string(),
+                investment_rules: rules(investors),
+            };
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &add_rules(&investors[..6]),
+                    &[],
+                )
+                .unwrap();
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &add_rules(&investors[5..7]),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(
+                err,
+                RouterErr::DuplicateInvestmentRule { investor } if investor == investors[5]
+            ));
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &add_rules(&[String::from(INVESTOR_1)]),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(
+                err,
+                RouterErr::NotSubscriber { investor } if investor == INVESTOR_1
+            ));
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &add_rules(&investors[6..]),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::NotIssuer { .. }));
+
+            /* ================= Distribute handles the first page ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: rules(&investors[6..]),
+                    },
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                job_progress(
+                    &blockchain,
+                    &router_address,
+                    &bond_token_address,
+                    JobKind::Distribution
+                ),
+                JobProgressResponse {
+                    issuer: String::from(ISSUER),
+                    last_account: Some(investors[9].clone()),
+                    processed: 10,
+                    amount: Uint128::from(300_u128),
+                    done: false,
+                }
+            );
+            assert_eq!(
+                bond_balance_of(&blockchain, &bond_token_address, &investors[9]),
+                20
+            );
+            assert_eq!(
+                bond_balance_of(&blockchain, &bond_token_address, &investors[10]),
+                0
+            );
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 500);
+            let escrow: EscrowResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Escrow {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(escrow.state, EscrowState::Claiming);
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &add_rules(&[String::from(INVESTOR_2)]),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::JobStarted { .. }));
+
+            /* ================= Only issuer or operators continue ================= */
+            let continue_distribute = |limit: Option<u32>| RouterExecuteMsg::ContinueDistribute {
+                bond_token: bond_token_address.to_string(),
+                limit,
+            };
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(INVESTOR_1),
+                    router_address.clone(),
+                    &continue_distribute(None),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::NotIssuer { .. }));
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    router_address.clone(),
+                    &RouterExecuteMsg::SetOperators {
+                        operators: vec![Addr::unchecked(OPERATOR).to_string()],
+                        is_operators: vec![true],
+                    },
+                    &[],
+                )
+                .unwrap();
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    router_address.clone(),
+                    &continue_distribute(Some(1)),
+                    &[],
+                )
+                .unwrap();
+            let progress = job_progress(
+                &blockchain,
+                &router_address,
+                &bond_token_address,
+                JobKind::Distribution,
+            );
+            assert_eq!(progress.processed, 11);
+            assert!(!progress.done);
+
+            /* ================= Escrow is released with the last page ================= */
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &continue_distribute(None),
+                    &[],
+                )
+                .unwrap();
+            let progress = job_progress(
+                &blockchain,
+                &router_address,
+                &bond_token_address,
+                JobKind::Distribution,
+            );
+            assert_eq!(progress.processed, 12);
+            assert_eq!(progress.amount, Uint128::from(360_u128));
+            assert!(progress.done);
+            let escrow: EscrowResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Escrow {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(escrow.state, EscrowState::Released);
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 860);
+            for investor in &investors {
+                assert_eq!(
+                    bond_balance_of(&blockchain, &bond_token_address, investor),
+                    20
+                );
+                assert_eq!(balance_of(&blockchain, &currency_address, investor), 20);
+            }
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &continue_distribute(None),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::JobFinished { .. }));
+
+            /* ================= Redemption is paged the same way ================= */
+            set_block_time(&mut blockchain, maturity_date);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    currency_address.clone(),
+                    &Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: router_address.to_string(),
+                        amount: Uint128::from(360_u128),
+                        expires: None,
+                    },
+                    &[],
+                )
+                .unwrap();
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::ContinueRedeem {
+                        bond_token: bond_token_address.to_string(),
+                        limit: None,
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::JobNotFound { .. }));
+            for msg in [
+                RouterExecuteMsg::PayCoupon {
+                    bond_token: bond_token_address.to_string(),
+                    period: 1,
+                },
+                RouterExecuteMsg::Redeem {
+                    bond_token: bond_token_address.to_string(),
+                },
+            ] {
+                blockchain
+                    .execute_contract(Addr::unchecked(ISSUER), router_address.clone(), &msg, &[])
+                    .unwrap();
+            }
+            // The coupon job pays its first page of holders too
+            let progress = job_progress(
+                &blockchain,
+                &router_address,
+                &bond_token_address,
+                JobKind::Coupon,
+            );
+            assert_eq!(progress.processed, 10);
+            assert!(!progress.done);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(OPERATOR),
+                    router_address.clone(),
+                    &RouterExecuteMsg::ContinuePayCoupon {
+                        bond_token: bond_token_address.to_string(),
+                        limit: None,
+                    },
+                    &[],
+                )
+                .unwrap();
+            let progress = job_progress(
+                &blockchain,
+                &router_address,
+                &bond_token_address,
+                JobKind::Coupon,
+            );
+            assert_eq!(progress.processed, 12);
+            assert!(progress.done);
+            let progress = job_progress(
+                &blockchain,
+                &router_address,
+                &bond_token_address,
+                JobKind::Redemption,
+            );
+            assert_eq!(progress.processed, 10);
+            assert_eq!(progress.amount, Uint128::from(300_u128));
+            assert!(!progress.done);
+            assert_eq!(
+                bond_balance_of(&blockchain, &bond_token_address, &investors[10]),
+                20
+            );
+            let err: RouterErr = blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Redeem {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert!(matches!(err, RouterErr::JobStarted { .. }));
+
+            // Nothing is minted between pages, holders are redeemed on the balance they had
+            let err: BondTokenErr = blockchain
+                .execute_contract(
+                    router_address.clone(),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::Cw20ExecuteMsg(cw20_base::msg::ExecuteMsg::Mint {
+                        recipient: investors[0].to_string(),
+                        amount: Uint128::from(10_u128),
+                    }),
+                    &[],
+                )
+                .unwrap_err()
+                .downcast()
+                .unwrap();
+            assert_eq!(
+                err,
+                BondTokenErr::AdditionalError(AdditionalError::ActionNotAllowed {
+                    action: String::from("mint"),
+                })
+            );
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::ContinueRedeem {
+                        bond_token: bond_token_address.to_string(),
+                        limit: Some(5),
+                    },
+                    &[],
+                )
+                .unwrap();
+            let progress = job_progress(
+                &blockchain,
+                &router_address,
+                &bond_token_address,
+                JobKind::Redemption,
+            );
+            assert_eq!(progress.processed, 12);
+            assert_eq!(progress.amount, Uint128::from(360_u128));
+            assert!(progress.done);
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 500);
+            for investor in &investors {
+                assert_eq!(
+                    bond_balance_of(&blockchain, &bond_token_address, investor),
+                    0
+                );
+                assert_eq!(balance_of(&blockchain, &currency_address, investor), 50);
+            }
+        }
+
+        #[test]
+        fn failed_mint_test() {
+            let (
+                mut blockchain,
+                factory_address,
+                placeholder_address,
+                router_address,
+                currency_address,
+            ) = setup_platform();
+            let subscription_end = blockchain
+                .block_info()
+                .time
+                .plus_seconds(SUBSCRIPTION_PERIOD);
+            let terms = BondTerms {
+                allowlist: Some(vec![String::from(INVESTOR_1), String::from(INVESTOR_2)]),
+                ..bond_terms(
+                    &blockchain,
+                    CouponTerms {
+                        rate: Decimal::zero(),
+                        frequency: 1,
+                        dates: vec![],
+                        day_count: DayCount::Act365Fixed,
+                        business_day_convention: BusinessDayConvention::Unadjusted,
+                        holidays: vec![],
+                    },
+                )
+            };
+            let bond_token_address = create_bond_token_with_setup(
+                &mut blockchain,
+                &factory_address,
+                &currency_address,
+                FunctionSetup {
+                    transfer: true,
+                    burn: true,
+                    mint_to_investor: true,
+                    subscribe: true,
+                },
+                terms,
+            );
+            for investor in [INVESTOR_1, INVESTOR_2] {
+                subscribe(
+                    &mut blockchain,
+                    &bond_token_address,
+                    &currency_address,
+                    investor,
+                    600,
+                );
+            }
+
+            // Second investor can no longer hold the bond by distribution time
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    bond_token_address.clone(),
+                    &BondTokenExecuteMsg::AdditionalExecuteMsg(
+                        AdditionalExecuteMsg::RemoveFromAllowlist {
+                            investors: vec![String::from(INVESTOR_2)],
+                        },
+                    ),
+                    &[],
+                )
+                .unwrap();
+            set_block_time(&mut blockchain, subscription_end);
+            blockchain
+                .execute_contract(
+                    Addr::unchecked(ISSUER),
+                    router_address.clone(),
+                    &RouterExecuteMsg::Distribute {
+                        bond_token: bond_token_address.to_string(),
+                        investment_rules: [INVESTOR_1, INVESTOR_2]
+                            .into_iter()
+                            .map(|investor| InvesmentRule {
+                                investor: String::from(investor),
+                                currency_amount: Uint128::from(300_u128),
+                            })
+                            .collect(),
+                    },
+                    &[],
+                )
+                .unwrap();
+
+            /* ================= The page goes on, the investor is refunded ================= */
+            let progress = job_progress(
+                &blockchain,
+                &router_address,
+                &bond_token_address,
+                JobKind::Distribution,
+            );
+            assert_eq!(progress.processed, 2);
+            assert_eq!(progress.amount, Uint128::from(300_u128));
+            assert!(progress.done);
+            assert_eq!(
+                bond_balance_of(&blockchain, &bond_token_address, INVESTOR_1),
+                200
+            );
+            assert_eq!(
+                bond_balance_of(&blockchain, &bond_token_address, INVESTOR_2),
+                0
+            );
+            assert_eq!(balance_of(&blockchain, &currency_address, INVESTOR_2), 1700);
+            assert_eq!(balance_of(&blockchain, &currency_address, ISSUER), 800);
+            let escrow: EscrowResponse = blockchain
+                .wrap()
+                .query_wasm_smart(
+                    placeholder_address.to_string(),
+                    &PlaceholderQueryMsg::Escrow {
+                        bond_token: bond_token_address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(escrow.state, EscrowState::Released);
         }
     }
 }